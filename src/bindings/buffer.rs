@@ -1,8 +1,8 @@
-use core::marker::PhantomData;
+use core::{marker::PhantomData, ptr::NonNull};
 
 use alloc::vec::Vec;
 
-use super::types::FfiAcpiBuffer;
+use super::{functions::AcpiOsFree, types::FfiAcpiBuffer};
 
 //
 const ACPI_ALLOCATE_BUFFER: usize = usize::MAX;
@@ -10,6 +10,34 @@ const ACPI_ALLOCATE_BUFFER: usize = usize::MAX;
 #[derive(Debug)]
 pub(crate) struct BufferUnchangedError;
 
+/// A buffer which was allocated by ACPICA and returned through an [`FfiAcpiBuffer`].
+/// The memory is given back to ACPICA's allocator when this struct is dropped.
+#[derive(Debug)]
+pub(crate) struct AcpiAllocatedBuffer {
+    pointer: NonNull<u8>,
+    length: usize,
+}
+
+impl AcpiAllocatedBuffer {
+    /// Gets a pointer to the start of the buffer
+    pub(crate) fn as_ptr(&self) -> *const u8 {
+        self.pointer.as_ptr()
+    }
+
+    /// Gets the contents of the buffer as a byte slice
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        // SAFETY: The pointer and length were provided by ACPICA, which allocated this buffer
+        unsafe { core::slice::from_raw_parts(self.pointer.as_ptr(), self.length) }
+    }
+}
+
+impl Drop for AcpiAllocatedBuffer {
+    fn drop(&mut self) {
+        // SAFETY: The buffer was allocated by ACPICA using `AcpiOsAllocate`, and is not referenced anywhere else
+        unsafe { AcpiOsFree(self.pointer.as_ptr().cast()) }
+    }
+}
+
 impl FfiAcpiBuffer<'static> {
     pub const EMPTY: Self = Self {
         length: 0,
@@ -23,9 +51,12 @@ impl FfiAcpiBuffer<'static> {
         _p: PhantomData,
     };
 
-    pub(crate) fn allocate<T, U>(f: T) -> (U, Result<Vec<u8>, BufferUnchangedError>)
+    /// Calls `f` with a buffer which ACPICA will allocate, and takes ownership of the allocated memory.
+    ///
+    /// If ACPICA did not write any data to the buffer, `None` is returned.
+    pub(crate) fn allocate_owned<T, U>(f: T) -> (U, Option<AcpiAllocatedBuffer>)
     where
-        T: Fn(&mut Self) -> U,
+        T: FnOnce(&mut Self) -> U,
     {
         let mut b = Self::ALLOCATE;
 
@@ -33,15 +64,30 @@ impl FfiAcpiBuffer<'static> {
 
         // Check that the buffer has been written to
         if b.length == ACPI_ALLOCATE_BUFFER {
-            return (u, Err(BufferUnchangedError));
+            return (u, None);
         }
 
-        // SAFETY: This memory was allocated by ACPICA
-        // This means the call went through AcpiOsAllocate, which uses the system allocator
-        // Therefore, the pointer, length, capacity, and allocator are all valid to construct a Vec
-        let v = unsafe { Vec::from_raw_parts(b.pointer.cast(), b.length, b.length) };
+        let buffer = NonNull::new(b.pointer.cast()).map(|pointer| AcpiAllocatedBuffer {
+            pointer,
+            length: b.length,
+        });
+
+        (u, buffer)
+    }
+
+    pub(crate) fn allocate<T, U>(f: T) -> (U, Result<Vec<u8>, BufferUnchangedError>)
+    where
+        T: FnOnce(&mut Self) -> U,
+    {
+        let (u, buffer) = Self::allocate_owned(f);
+
+        // Copy the data out of ACPICA's allocation, which is then freed when `buffer` is dropped.
+        // The allocation can't be turned into a `Vec` directly, as `AcpiOsAllocate` stores the size before the returned pointer.
+        let v = buffer
+            .map(|buffer| buffer.as_slice().to_vec())
+            .ok_or(BufferUnchangedError);
 
-        (u, Ok(v))
+        (u, v)
     }
 }
 
@@ -51,6 +51,11 @@ pub const ACPI_NO_ADDRESS_SPACE_INIT: u32 = 128;
 pub const ACPI_SUBSYSTEM_INITIALIZE: u32 = 1;
 pub const ACPI_INITIALIZED_OK: u32 = 2;
 pub const ACPI_S_STATE_COUNT: u32 = 6;
+pub const ACPI_STATE_S1: u8 = 1;
+pub const ACPI_STATE_S2: u8 = 2;
+pub const ACPI_STATE_S3: u8 = 3;
+pub const ACPI_STATE_S4: u8 = 4;
+pub const ACPI_STATE_S5: u8 = 5;
 pub const ACPI_D_STATE_COUNT: u32 = 4;
 pub const ACPI_C_STATE_COUNT: u32 = 4;
 pub const ACPI_SLEEP_TYPE_MAX: u32 = 7;
@@ -113,6 +118,8 @@ pub const ACPI_NUM_FIXED_EVENTS: u32 = 5;
 pub const ACPI_GPE_ENABLE: u32 = 0;
 pub const ACPI_GPE_DISABLE: u32 = 1;
 pub const ACPI_GPE_CONDITIONAL_ENABLE: u32 = 2;
+pub const ACPI_GPE_LEVEL_TRIGGERED: u32 = 8;
+pub const ACPI_GPE_EDGE_TRIGGERED: u32 = 0;
 pub const ACPI_NOT_ISR: u32 = 1;
 pub const ACPI_ISR: u32 = 0;
 pub const ACPI_SYSTEM_NOTIFY: u32 = 1;
@@ -165,6 +172,12 @@ pub const ACPI_SYS_MODE_LEGACY: u32 = 2;
 pub const ACPI_SYS_MODES_MASK: u32 = 3;
 pub const ACPI_EVENT_TYPE_GPE: u32 = 0;
 pub const ACPI_EVENT_TYPE_FIXED: u32 = 1;
+pub const ACPI_EVENT_FLAG_ENABLED: u32 = 1;
+pub const ACPI_EVENT_FLAG_WAKE_ENABLED: u32 = 2;
+pub const ACPI_EVENT_FLAG_STATUS_SET: u32 = 4;
+pub const ACPI_EVENT_FLAG_ENABLE_SET: u32 = 8;
+pub const ACPI_EVENT_FLAG_HAS_HANDLER: u32 = 16;
+pub const ACPI_EVENT_FLAG_MASKED: u32 = 32;
 pub const ACPI_INIT_DEVICE_INI: u32 = 1;
 
 pub const ACPI_TABLE_EVENT_LOAD: u32 = 0;
@@ -854,6 +867,41 @@ pub const ACPI_TRACE_LAYER_ALL: u32 = 0x1FF;
 pub const ACPI_TRACE_LEVEL_DEFAULT: u32 = 16;
 pub const ACPI_TRACE_LAYER_DEFAULT: u32 = 128;
 
+pub const ACPI_READ_ONLY_MEMORY: u8 = 0;
+pub const ACPI_READ_WRITE_MEMORY: u8 = 1;
+pub const ACPI_NON_CACHEABLE_MEMORY: u8 = 0;
+pub const ACPI_CACHABLE_MEMORY: u8 = 1;
+pub const ACPI_WRITE_COMBINING_MEMORY: u8 = 2;
+pub const ACPI_PREFETCHABLE_MEMORY: u8 = 3;
+pub const ACPI_DENSE_TRANSLATION: u8 = 0;
+pub const ACPI_SPARSE_TRANSLATION: u8 = 1;
+pub const ACPI_DECODE_10: u8 = 0;
+pub const ACPI_DECODE_16: u8 = 1;
+pub const ACPI_LEVEL_SENSITIVE: u8 = 0;
+pub const ACPI_EDGE_SENSITIVE: u8 = 1;
+pub const ACPI_ACTIVE_HIGH: u8 = 0;
+pub const ACPI_ACTIVE_LOW: u8 = 1;
+pub const ACPI_ACTIVE_BOTH: u8 = 2;
+pub const ACPI_EXCLUSIVE: u8 = 0;
+pub const ACPI_SHARED: u8 = 1;
+pub const ACPI_NOT_WAKE_CAPABLE: u8 = 0;
+pub const ACPI_WAKE_CAPABLE: u8 = 1;
+pub const ACPI_NOT_BUS_MASTER: u8 = 0;
+pub const ACPI_BUS_MASTER: u8 = 1;
+pub const ACPI_MEMORY_RANGE: u8 = 0;
+pub const ACPI_IO_RANGE: u8 = 1;
+pub const ACPI_BUS_NUMBER_RANGE: u8 = 2;
+pub const ACPI_ADDRESS_NOT_FIXED: u8 = 0;
+pub const ACPI_ADDRESS_FIXED: u8 = 1;
+pub const ACPI_POS_DECODE: u8 = 0;
+pub const ACPI_SUB_DECODE: u8 = 1;
+pub const ACPI_PRODUCER: u8 = 0;
+pub const ACPI_CONSUMER: u8 = 1;
+
+pub const AML_RESOURCE_I2C_MIN_DATA_LEN: u16 = 6;
+pub const AML_RESOURCE_SPI_MIN_DATA_LEN: u16 = 9;
+pub const AML_RESOURCE_UART_MIN_DATA_LEN: u16 = 10;
+
 pub const ACPI_DMA_WIDTH8: u32 = 0;
 pub const ACPI_DMA_WIDTH16: u32 = 1;
 pub const ACPI_DMA_WIDTH32: u32 = 2;
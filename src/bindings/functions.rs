@@ -4,15 +4,15 @@ use super::types::{
     functions::{
         FfiAcpiAdrSpaceHandler, FfiAcpiAdrSpaceSetup, FfiAcpiEventHandler, FfiAcpiExceptionHandler,
         FfiAcpiGblEventHandler, FfiAcpiGpeHandler, FfiAcpiInitHandler, FfiAcpiInterfaceHandler,
-        FfiAcpiNotifyHandler, FfiAcpiObjectHandler, FfiAcpiSciHandler, FfiAcpiTableHandler,
-        FfiAcpiWalkCallback, FfiAcpiWalkResourceCallback,
+        FfiAcpiNotifyHandler, FfiAcpiObjectHandler, FfiAcpiOsdExecCallback, FfiAcpiSciHandler,
+        FfiAcpiTableHandler, FfiAcpiWalkCallback, FfiAcpiWalkResourceCallback,
     },
     object::FfiAcpiObjectType,
     tables::FfiAcpiTableHeader,
-    FfiAcpiAdtSpaceType, FfiAcpiBuffer, FfiAcpiDeviceInfo, FfiAcpiEventStatus,
-    FfiAcpiGenericAddress, FfiAcpiHandle, FfiAcpiObjectList, FfiAcpiPhysicalAddress,
-    FfiAcpiPldInfo, FfiAcpiResource, FfiAcpiResourceAddress64, FfiAcpiSize, FfiAcpiStatistics,
-    FfiAcpiString, FfiAcpiTableDesc, FfiAcpiVendorUuid,
+    FfiAcpiAdtSpaceType, FfiAcpiBuffer, FfiAcpiDeviceInfo, FfiAcpiEventStatus, FfiAcpiExecuteType,
+    FfiAcpiGenericAddress, FfiAcpiHandle, FfiAcpiIoAddress, FfiAcpiObjectList, FfiAcpiPciId,
+    FfiAcpiPhysicalAddress, FfiAcpiPldInfo, FfiAcpiResource, FfiAcpiResourceAddress64, FfiAcpiSize,
+    FfiAcpiStatistics, FfiAcpiString, FfiAcpiTableDesc, FfiAcpiVendorUuid,
 };
 
 #[allow(dead_code)]
@@ -486,3 +486,37 @@ extern "C" {
 
     pub(crate) fn AcpiSetDebuggerThreadId(ThreadId: u64);
 }
+
+// These functions are implemented by this crate in the `handler` module,
+// but are declared here so that they can be called from rust code in the same way ACPICA calls them,
+// e.g. so that memory allocated by ACPICA can be freed.
+extern "C" {
+    pub(crate) fn AcpiOsFree(Memory: *mut ::core::ffi::c_void);
+
+    pub(crate) fn AcpiOsWritePciConfiguration(
+        PciId: *mut FfiAcpiPciId,
+        Reg: u32,
+        Value: u64,
+        Width: u32,
+    ) -> AcpiStatus;
+
+    pub(crate) fn AcpiOsReadPort(
+        Address: FfiAcpiIoAddress,
+        Value: *mut u32,
+        Width: u32,
+    ) -> AcpiStatus;
+
+    pub(crate) fn AcpiOsWritePort(Address: FfiAcpiIoAddress, Value: u32, Width: u32) -> AcpiStatus;
+
+    pub(crate) fn AcpiOsStall(Microseconds: u32);
+
+    pub(crate) fn AcpiOsSleep(Milliseconds: u64);
+
+    pub(crate) fn AcpiOsWaitEventsComplete();
+
+    pub(crate) fn AcpiOsExecute(
+        Type: FfiAcpiExecuteType,
+        Function: FfiAcpiOsdExecCallback,
+        Context: *mut ::core::ffi::c_void,
+    ) -> AcpiStatus;
+}
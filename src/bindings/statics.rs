@@ -1,4 +1,4 @@
-use super::types::tables::fadt::FfiAcpiTableFadt;
+use super::types::{tables::fadt::FfiAcpiTableFadt, FfiAcpiHandle};
 
 #[allow(dead_code)]
 extern "C" {
@@ -57,4 +57,8 @@ extern "C" {
     pub(crate) static mut AcpiCurrentGpeCount: u32;
 
     pub(crate) static mut AcpiGbl_SystemAwakeAndRunning: bool;
+
+    pub(crate) static mut AcpiGbl_FadtGpeDevice: FfiAcpiHandle;
+
+    pub(crate) static mut AcpiGbl_FACS: *mut core::ffi::c_void;
 }
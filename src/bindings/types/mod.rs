@@ -164,6 +164,34 @@ pub(crate) struct FfiAcpiMemSpaceContext {
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+pub(crate) struct FfiAcpiConnectionInfo {
+    pub(crate) connection: *mut u8,
+    pub(crate) length: u16,
+    pub(crate) access_length: u8,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct FfiAcpiPccInfo {
+    pub(crate) subspace_id: u8,
+    pub(crate) length: u16,
+    pub(crate) internal_buffer: *mut u8,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct FfiAcpiFfhInfo {
+    pub(crate) offset: u64,
+    pub(crate) length: u64,
+}
+/// The part of an address space handler's context which ACPICA writes to, depending on the address space
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) union FfiAcpiRegionHandlerInfo {
+    pub(crate) connection: FfiAcpiConnectionInfo,
+    pub(crate) pcc: FfiAcpiPccInfo,
+    pub(crate) ffh: FfiAcpiFfhInfo,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
 pub(crate) struct FfiAcpiMemoryList {
     pub(crate) list_name: *const i8,
     pub(crate) list_head: *mut ::core::ffi::c_void,
@@ -0,0 +1,427 @@
+//! Code to handle accesses by AML code to operation regions in an address space
+
+use core::{ffi::c_void, fmt::Debug, ptr::addr_of};
+
+use alloc::boxed::Box;
+
+use crate::{
+    bindings::{
+        consts::{
+            ACPI_IO_MASK, ACPI_IPMI_BUFFER_SIZE, ACPI_PRM_INPUT_BUFFER_SIZE, ACPI_REGION_ACTIVATE,
+            ACPI_SERIAL_HEADER_SIZE, ACPI_SMBUS_BUFFER_SIZE, ACPI_WRITE,
+        },
+        functions::{AcpiInstallAddressSpaceHandler, AcpiRemoveAddressSpaceHandler},
+        types::{
+            functions::FfiAcpiAdrSpaceHandler, FfiAcpiFfhInfo, FfiAcpiHandle,
+            FfiAcpiPhysicalAddress, FfiAcpiRegionHandlerInfo,
+        },
+    },
+    devices::AcpiHandle,
+    status::{AcpiError, AcpiErrorAsStatusExt, AcpiStatus},
+    types::GasAddressSpace,
+};
+
+/// The size of the buffer used for the functional fixed hardware address space
+const FFH_BUFFER_SIZE: usize = 256;
+
+/// The lengths of the data transferred by each `GenericSerialBus` access attribute, or [`None`] if the attribute is reserved.
+/// The data is preceded by a status and a length byte.
+const GSBUS_PROTOCOL_LENGTHS: [Option<u8>; 16] = [
+    None,
+    None,
+    Some(0x00),
+    None,
+    Some(0x01),
+    None,
+    Some(0x01),
+    None,
+    Some(0x02),
+    None,
+    Some(0xFF),
+    Some(0xFF),
+    Some(0x02),
+    Some(0xFF),
+    Some(0xFF),
+    Some(0xFF),
+];
+
+/// An event passed to [`AddressSpaceHandler::setup`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionEvent {
+    /// An operation region is being accessed for the first time
+    Activate,
+    /// An operation region is being removed, or the handler is being uninstalled
+    Deactivate,
+}
+
+/// Whether a [`BufferTransfer`] is reading from or writing to an operation region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    /// AML code is reading from a field, and the handler should fill the buffer
+    Read,
+    /// AML code is writing to a field. The handler can write a response back into the buffer.
+    Write,
+}
+
+/// An access to an operation region in one of the address spaces which transfer buffers rather than integers:
+/// [`SMBus`], [`IPMI`], [`GenericSerialBus`], [`PlatformCommunicationsChannel`], [`PlatformRuntimeMechanism`]
+/// and [`FunctionalFixedHardware`].
+///
+/// [`SMBus`]: GasAddressSpace::SMBus
+/// [`IPMI`]: GasAddressSpace::IPMI
+/// [`GenericSerialBus`]: GasAddressSpace::GenericSerialBus
+/// [`PlatformCommunicationsChannel`]: GasAddressSpace::PlatformCommunicationsChannel
+/// [`PlatformRuntimeMechanism`]: GasAddressSpace::PlatformRuntimeMechanism
+/// [`FunctionalFixedHardware`]: GasAddressSpace::FunctionalFixedHardware
+#[derive(Debug)]
+pub struct BufferTransfer<'a> {
+    /// Whether the field is being read or written
+    pub direction: TransferDirection,
+    /// The access attribute of the field, for the [`SMBus`] and [`GenericSerialBus`] address spaces.
+    /// This is `0` for other address spaces.
+    ///
+    /// [`SMBus`]: GasAddressSpace::SMBus
+    /// [`GenericSerialBus`]: GasAddressSpace::GenericSerialBus
+    pub protocol: u16,
+    /// The raw resource descriptor from the field's `Connection`, for the [`GenericSerialBus`] address space
+    ///
+    /// [`GenericSerialBus`]: GasAddressSpace::GenericSerialBus
+    pub connection: Option<&'a [u8]>,
+    /// The buffer to read into or write from.
+    /// For [`SMBus`], [`IPMI`] and [`GenericSerialBus`], this starts with a status and a length byte.
+    ///
+    /// [`SMBus`]: GasAddressSpace::SMBus
+    /// [`IPMI`]: GasAddressSpace::IPMI
+    /// [`GenericSerialBus`]: GasAddressSpace::GenericSerialBus
+    pub buffer: &'a mut [u8],
+}
+
+/// A handler for accesses by AML code to operation regions in an address space, installed with
+/// [`install_address_space_handler`].
+///
+/// ACPICA releases the interpreter lock before calling a handler, so calls can come from several threads at once,
+/// and the handler must be [`Send`] and [`Sync`]. Only accesses to the [`GenericSerialBus`] and [`GeneralPurposeIO`]
+/// address spaces are serialized by ACPICA. For other address spaces, [`read`], [`write`] and [`transfer`] must do
+/// their own locking if the hardware needs accesses to be serialized.
+///
+/// [`GenericSerialBus`]: GasAddressSpace::GenericSerialBus
+/// [`GeneralPurposeIO`]: GasAddressSpace::GeneralPurposeIO
+/// [`read`]: AddressSpaceHandler::read
+/// [`write`]: AddressSpaceHandler::write
+/// [`transfer`]: AddressSpaceHandler::transfer
+///
+/// [`install_address_space_handler`]: AcpiHandle::install_address_space_handler
+pub trait AddressSpaceHandler: Send + Sync {
+    /// Called when an operation region in the address space is first accessed, and when it's removed.
+    /// The default implementation does nothing.
+    ///
+    /// # Errors
+    /// If this returns an error when the region is activated, the access which caused it fails
+    fn setup(&self, event: RegionEvent) -> Result<(), AcpiError> {
+        let _ = event;
+        Ok(())
+    }
+
+    /// Reads `width` bits from `address`.
+    ///
+    /// For the [`GeneralPurposeIO`] address space, `address` is the index of the first pin in the `GpioIo` resource
+    /// descriptor passed in `connection`, which comes from the field's `Connection`, and `width` is the number of
+    /// pins. `connection` is [`None`] for other address spaces.
+    ///
+    /// [`GeneralPurposeIO`]: GasAddressSpace::GeneralPurposeIO
+    ///
+    /// # Errors
+    /// The error is returned to the AML interpreter, which aborts the method doing the access
+    fn read(&self, address: u64, width: u32, connection: Option<&[u8]>) -> Result<u64, AcpiError>;
+
+    /// Writes the lowest `width` bits of `value` to `address`.
+    /// `address`, `width` and `connection` are interpreted in the same way as for [`read`][Self::read].
+    ///
+    /// # Errors
+    /// The error is returned to the AML interpreter, which aborts the method doing the access
+    fn write(
+        &self,
+        address: u64,
+        width: u32,
+        value: u64,
+        connection: Option<&[u8]>,
+    ) -> Result<(), AcpiError>;
+
+    /// Transfers a buffer to or from `address`, in an address space which uses buffers rather than integers.
+    /// The default implementation returns [`AcpiError::Support`].
+    ///
+    /// # Errors
+    /// The error is returned to the AML interpreter, which aborts the method doing the access
+    fn transfer(&self, address: u64, transfer: BufferTransfer<'_>) -> Result<(), AcpiError> {
+        let _ = (address, transfer);
+        Err(AcpiError::Support)
+    }
+}
+
+/// The context passed to ACPICA when installing a handler.
+/// ACPICA writes information about the access into `info` before calling the handler for some address spaces,
+/// so it must be the first field.
+///
+/// ACPICA only serializes calls to the handler for `GenericSerialBus` and `GeneralPurposeIO`, so the PCC and FFH info
+/// can be written while the handler is running on another thread for another region. `info` is only accessed
+/// through raw pointers, so that no reference to it exists while it's written.
+#[repr(C)]
+struct HandlerContext<H> {
+    info: FfiAcpiRegionHandlerInfo,
+    space: GasAddressSpace,
+    handler: H,
+}
+
+/// An address space handler installed by [`install_address_space_handler`].
+/// The handler is removed when this struct is dropped.
+///
+/// [`install_address_space_handler`]: AcpiHandle::install_address_space_handler
+#[must_use = "The handler is removed when the guard is dropped"]
+pub struct AddressSpaceHandlerGuard {
+    handle: AcpiHandle,
+    space: GasAddressSpace,
+    handler: FfiAcpiAdrSpaceHandler,
+    context: *mut c_void,
+    drop_context: unsafe fn(*mut c_void),
+}
+
+// SAFETY: The handler in `context` is `Send + Sync`, and ACPICA can remove the handler from any thread
+unsafe impl Send for AddressSpaceHandlerGuard {}
+// SAFETY: The guard has no methods which use the handler
+unsafe impl Sync for AddressSpaceHandlerGuard {}
+
+impl Debug for AddressSpaceHandlerGuard {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AddressSpaceHandlerGuard")
+            .field("handle", &self.handle)
+            .field("space", &self.space)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for AddressSpaceHandlerGuard {
+    fn drop(&mut self) {
+        // SAFETY: The handler was installed with these arguments
+        let r = unsafe {
+            AcpiRemoveAddressSpaceHandler(self.handle.0, self.space.to_u8(), self.handler)
+        };
+
+        // If the handler couldn't be removed, ACPICA could still call it, so the context is leaked
+        if r.as_result().is_ok() {
+            // SAFETY: The handler has been removed and all regions using it have been deactivated,
+            // so nothing else is using the context
+            unsafe { (self.drop_context)(self.context) };
+        }
+    }
+}
+
+/// Gets the length of the buffer which `value` points to for an access to `space`,
+/// or [`None`] if the address space transfers integers.
+///
+/// # Safety
+/// `info` must have been filled in by ACPICA for this access
+unsafe fn buffer_length(
+    space: GasAddressSpace,
+    function: u32,
+    info: &FfiAcpiRegionHandlerInfo,
+) -> Option<Result<usize, AcpiError>> {
+    let length = match space {
+        GasAddressSpace::SMBus => ACPI_SMBUS_BUFFER_SIZE as usize,
+        GasAddressSpace::IPMI => ACPI_IPMI_BUFFER_SIZE as usize,
+        GasAddressSpace::PlatformRuntimeMechanism => ACPI_PRM_INPUT_BUFFER_SIZE as usize,
+        GasAddressSpace::FunctionalFixedHardware => FFH_BUFFER_SIZE,
+        GasAddressSpace::GenericSerialBus => {
+            let protocol = GSBUS_PROTOCOL_LENGTHS
+                .get((function >> 16) as usize)
+                .copied();
+
+            match protocol.flatten() {
+                Some(length) => usize::from(length) + ACPI_SERIAL_HEADER_SIZE as usize,
+                None => return Some(Err(AcpiError::AmlProtocol)),
+            }
+        }
+        // SAFETY: ACPICA fills in the PCC info before activating a PCC region
+        GasAddressSpace::PlatformCommunicationsChannel => usize::from(unsafe { info.pcc.length }),
+        _ => return None,
+    };
+
+    Some(Ok(length))
+}
+
+/// Gets the raw resource descriptor from the field's `Connection` for an access to `space`,
+/// or [`None`] if the address space doesn't use connections
+///
+/// # Safety
+/// `info` must have been filled in by ACPICA for this access, and the connection buffer must outlive `'a`
+unsafe fn connection<'a>(
+    space: GasAddressSpace,
+    info: &FfiAcpiRegionHandlerInfo,
+) -> Option<&'a [u8]> {
+    match space {
+        GasAddressSpace::GenericSerialBus | GasAddressSpace::GeneralPurposeIO => {
+            // SAFETY: ACPICA fills in the connection info before each GenericSerialBus and GeneralPurposeIO access
+            let info = unsafe { info.connection };
+            (!info.connection.is_null()).then(|| {
+                // SAFETY: The connection points to the field's resource buffer, which is `length` bytes long
+                unsafe { core::slice::from_raw_parts(info.connection, usize::from(info.length)) }
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Calls the handler in `handler_context` for an access to an operation region
+unsafe extern "C" fn call_address_space_handler<H: AddressSpaceHandler>(
+    function: u32,
+    address: FfiAcpiPhysicalAddress,
+    bit_width: u32,
+    value: *mut u64,
+    handler_context: *mut c_void,
+    _region_context: *mut c_void,
+) -> AcpiStatus {
+    let context = handler_context.cast::<HandlerContext<H>>();
+
+    // SAFETY: `handler_context` was created from a `Box<HandlerContext<H>>` by `install_address_space_handler`,
+    // and isn't freed until the handler is removed. ACPICA never writes to `space` or `handler`.
+    let (space, handler) = unsafe { ((*context).space, &(*context).handler) };
+
+    // SAFETY: As above. `info` is copied with a volatile read rather than borrowed, because ACPICA can write to it
+    // while the handler is running when a PCC or FFH region is activated. If that happens during an access to a
+    // PCC or FFH region, the copy can come from the region being activated rather than the one being accessed.
+    let info = unsafe { addr_of!((*context).info).read_volatile() };
+
+    let address = address as u64;
+    let direction = if function & ACPI_IO_MASK == ACPI_WRITE {
+        TransferDirection::Write
+    } else {
+        TransferDirection::Read
+    };
+
+    // SAFETY: ACPICA has filled in `info` for this access, and the field's resource buffer outlives the access
+    let connection = unsafe { connection(space, &info) };
+
+    // SAFETY: ACPICA has filled in `info` for this access
+    let result = match unsafe { buffer_length(space, function, &info) } {
+        Some(Ok(length)) => {
+            // SAFETY: For buffer-based address spaces, ACPICA passes a buffer of this length in `value`
+            let buffer = unsafe { core::slice::from_raw_parts_mut(value.cast::<u8>(), length) };
+
+            #[allow(clippy::cast_possible_truncation)]
+            let transfer = BufferTransfer {
+                direction,
+                protocol: match space {
+                    GasAddressSpace::SMBus | GasAddressSpace::GenericSerialBus => {
+                        (function >> 16) as u16
+                    }
+                    _ => 0,
+                },
+                connection,
+                buffer,
+            };
+
+            handler.transfer(address, transfer)
+        }
+        Some(Err(e)) => Err(e),
+        None => match direction {
+            TransferDirection::Read => handler.read(address, bit_width, connection).map(|v| {
+                // SAFETY: ACPICA passes a pointer to an integer for integer-based address spaces
+                unsafe { *value = v };
+            }),
+            TransferDirection::Write => {
+                // SAFETY: As above
+                let v = unsafe { *value };
+                handler.write(address, bit_width, v, connection)
+            }
+        },
+    };
+
+    result.to_acpi_status()
+}
+
+/// Calls the handler's `setup` method when a region is activated or deactivated
+unsafe extern "C" fn call_address_space_setup<H: AddressSpaceHandler>(
+    _region: FfiAcpiHandle,
+    function: u32,
+    handler_context: *mut c_void,
+    _region_context: *mut *mut c_void,
+) -> AcpiStatus {
+    // SAFETY: `handler_context` was created from a `Box<HandlerContext<H>>` by `install_address_space_handler`,
+    // and isn't freed until the handler is removed. Only `handler` is borrowed, as ACPICA can write to `info`.
+    let handler = unsafe { &(*handler_context.cast::<HandlerContext<H>>()).handler };
+
+    let event = if function == ACPI_REGION_ACTIVATE {
+        RegionEvent::Activate
+    } else {
+        RegionEvent::Deactivate
+    };
+
+    handler.setup(event).to_acpi_status()
+}
+
+/// Frees the handler context in `context`
+///
+/// # Safety
+/// `context` must have been created from a `Box<HandlerContext<H>>`, and must not be used afterwards
+unsafe fn drop_address_space_handler<H>(context: *mut c_void) {
+    // SAFETY: `context` came from a `Box<HandlerContext<H>>`
+    drop(unsafe { Box::from_raw(context.cast::<HandlerContext<H>>()) });
+}
+
+impl AcpiHandle {
+    /// Installs a handler for accesses by AML code to operation regions in `space` below this object.
+    /// ACPICA runs the `_REG` methods of the affected regions once the handler is installed.
+    ///
+    /// The object should be a device, or the root of the namespace to handle accesses to regions anywhere in the
+    /// namespace which aren't handled by a more specific handler.
+    ///
+    /// The handler is removed when the returned guard is dropped.
+    ///
+    /// # Errors
+    /// * [`AcpiError::AlreadyExists`] if a handler for the address space is already installed on the object
+    /// * [`AcpiError::BadParameter`] if the object can't have address space handlers
+    pub fn install_address_space_handler<H>(
+        &self,
+        space: GasAddressSpace,
+        handler: H,
+    ) -> Result<AddressSpaceHandlerGuard, AcpiError>
+    where
+        H: AddressSpaceHandler + 'static,
+    {
+        let context = Box::into_raw(Box::new(HandlerContext {
+            info: FfiAcpiRegionHandlerInfo {
+                ffh: FfiAcpiFfhInfo {
+                    offset: 0,
+                    length: 0,
+                },
+            },
+            space,
+            handler,
+        }))
+        .cast::<c_void>();
+
+        // SAFETY: `context` is valid for the type expected by the handler, and stays valid until the guard is dropped
+        let r = unsafe {
+            AcpiInstallAddressSpaceHandler(
+                self.0,
+                space.to_u8(),
+                call_address_space_handler::<H>,
+                call_address_space_setup::<H>,
+                context,
+            )
+        };
+
+        if let Err(e) = r.as_result() {
+            // SAFETY: The handler wasn't installed, so nothing else has access to `context`
+            unsafe { drop_address_space_handler::<H>(context) };
+            return Err(e);
+        }
+
+        Ok(AddressSpaceHandlerGuard {
+            handle: *self,
+            space,
+            handler: call_address_space_handler::<H>,
+            context,
+            drop_context: drop_address_space_handler::<H>,
+        })
+    }
+}
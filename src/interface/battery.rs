@@ -0,0 +1,388 @@
+//! Code to read the state of control method batteries (`PNP0C0A`)
+
+use alloc::{string::String, sync::Arc, vec::Vec};
+use spin::Mutex;
+
+use crate::{
+    devices::AcpiHandle,
+    notify::{NotifyHandlerGuard, NotifyHandlerKind},
+    status::AcpiError,
+    types::object::{package_integer, AcpiArgument, AcpiValue},
+    AcpicaOperation,
+};
+
+/// The hardware ID of a control method battery
+const BATTERY_HARDWARE_ID: &str = "PNP0C0A";
+
+/// The value used in battery packages for a value which isn't known
+const BATTERY_VALUE_UNKNOWN: u64 = 0xFFFF_FFFF;
+
+/// The notification sent when the battery's `_BST` values have changed
+const NOTIFY_BATTERY_STATUS: u8 = 0x80;
+/// The notification sent when the battery's `_BIF` or `_BIX` values have changed, e.g. because it was replaced
+const NOTIFY_BATTERY_INFO: u8 = 0x81;
+
+/// Gets the integer at `index` in a battery package, or [`None`] if the value is unknown
+fn battery_value(package: &[AcpiValue], index: usize) -> Result<Option<u32>, AcpiError> {
+    let value = package_integer(package, index)?;
+
+    if value == BATTERY_VALUE_UNKNOWN {
+        Ok(None)
+    } else {
+        u32::try_from(value)
+            .map(Some)
+            .map_err(|_| AcpiError::AmlNumericOverflow)
+    }
+}
+
+/// Gets the string at `index` in a battery package.
+/// Some firmware returns buffers instead of strings, so these are converted, stopping at the first null byte.
+fn battery_string(package: &[AcpiValue], index: usize) -> Result<String, AcpiError> {
+    match package.get(index).ok_or(AcpiError::AmlPackageLimit)? {
+        AcpiValue::String(s) => Ok(s.clone()),
+        AcpiValue::Buffer(b) => {
+            let length = b.iter().position(|&c| c == 0).unwrap_or(b.len());
+            Ok(String::from_utf8_lossy(&b[..length]).into_owned())
+        }
+        _ => Err(AcpiError::AmlOperandType),
+    }
+}
+
+/// The units of a battery's capacities and rates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatteryPowerUnit {
+    /// Capacities are in mWh and rates are in mW
+    MilliWatts,
+    /// Capacities are in mAh and rates are in mA
+    MilliAmps,
+}
+
+/// Whether a battery can be recharged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatteryTechnology {
+    /// The battery can't be recharged
+    Primary,
+    /// The battery can be recharged
+    Secondary,
+}
+
+/// Static information about a battery, from its `_BIX` or `_BIF` method.
+/// Capacities are in the units given by [`power_unit`], and are [`None`] if the battery doesn't know them.
+///
+/// [`power_unit`]: BatteryInfo::power_unit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatteryInfo {
+    /// The units of the battery's capacities and rates
+    pub power_unit: BatteryPowerUnit,
+    /// The capacity of a new battery
+    pub design_capacity: Option<u32>,
+    /// The capacity of the battery when it was last fully charged
+    pub full_charge_capacity: Option<u32>,
+    /// Whether the battery can be recharged
+    pub technology: BatteryTechnology,
+    /// The voltage of a new battery, in mV
+    pub design_voltage: Option<u32>,
+    /// The capacity at which the OEM recommends warning the user that the battery is low
+    pub warning_capacity: Option<u32>,
+    /// The capacity at which the OEM considers the battery to be critically low
+    pub low_capacity: Option<u32>,
+    /// The number of charge cycles the battery has been through. This is only reported by `_BIX`.
+    pub cycle_count: Option<u32>,
+    /// The battery's model number
+    pub model: String,
+    /// The battery's serial number
+    pub serial_number: String,
+    /// The type of the battery, e.g. `"LION"`
+    pub battery_type: String,
+    /// Information from the OEM
+    pub oem_info: String,
+}
+
+impl BatteryInfo {
+    fn decode_power_unit(
+        package: &[AcpiValue],
+        index: usize,
+    ) -> Result<BatteryPowerUnit, AcpiError> {
+        match package_integer(package, index)? {
+            0 => Ok(BatteryPowerUnit::MilliWatts),
+            1 => Ok(BatteryPowerUnit::MilliAmps),
+            _ => Err(AcpiError::AmlOperandType),
+        }
+    }
+
+    fn decode_technology(
+        package: &[AcpiValue],
+        index: usize,
+    ) -> Result<BatteryTechnology, AcpiError> {
+        match package_integer(package, index)? {
+            0 => Ok(BatteryTechnology::Primary),
+            1 => Ok(BatteryTechnology::Secondary),
+            _ => Err(AcpiError::AmlOperandType),
+        }
+    }
+
+    /// Decodes the package returned by `_BIX`
+    fn from_bix(package: &[AcpiValue]) -> Result<Self, AcpiError> {
+        Ok(Self {
+            power_unit: Self::decode_power_unit(package, 1)?,
+            design_capacity: battery_value(package, 2)?,
+            full_charge_capacity: battery_value(package, 3)?,
+            technology: Self::decode_technology(package, 4)?,
+            design_voltage: battery_value(package, 5)?,
+            warning_capacity: battery_value(package, 6)?,
+            low_capacity: battery_value(package, 7)?,
+            cycle_count: battery_value(package, 8)?,
+            model: battery_string(package, 16)?,
+            serial_number: battery_string(package, 17)?,
+            battery_type: battery_string(package, 18)?,
+            oem_info: battery_string(package, 19)?,
+        })
+    }
+
+    /// Decodes the package returned by `_BIF`
+    fn from_bif(package: &[AcpiValue]) -> Result<Self, AcpiError> {
+        Ok(Self {
+            power_unit: Self::decode_power_unit(package, 0)?,
+            design_capacity: battery_value(package, 1)?,
+            full_charge_capacity: battery_value(package, 2)?,
+            technology: Self::decode_technology(package, 3)?,
+            design_voltage: battery_value(package, 4)?,
+            warning_capacity: battery_value(package, 5)?,
+            low_capacity: battery_value(package, 6)?,
+            cycle_count: None,
+            model: battery_string(package, 9)?,
+            serial_number: battery_string(package, 10)?,
+            battery_type: battery_string(package, 11)?,
+            oem_info: battery_string(package, 12)?,
+        })
+    }
+
+    /// Reads the battery's `_BIX` method, or `_BIF` if it doesn't have one
+    fn read(handle: AcpiHandle) -> Result<Self, AcpiError> {
+        match handle.evaluate_package("_BIX", &[]) {
+            Ok(package) => Self::from_bix(&package),
+            Err(AcpiError::NotFound) => Self::from_bif(&handle.evaluate_package("_BIF", &[])?),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Whether a battery is charging or discharging
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatteryChargingState {
+    /// The battery is neither charging nor discharging, e.g. because it's full
+    Idle,
+    /// The battery is discharging
+    Discharging,
+    /// The battery is charging
+    Charging,
+}
+
+/// The current state of a battery, from its `_BST` method.
+/// Values are in the units given by [`BatteryInfo::power_unit`], and are [`None`] if the battery doesn't know them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryStatus {
+    /// Whether the battery is charging or discharging
+    pub charging_state: BatteryChargingState,
+    /// Whether the battery is critically low
+    pub critical: bool,
+    /// Whether charging is being limited, e.g. to extend the battery's life
+    pub charge_limiting: bool,
+    /// The rate at which the battery is charging or discharging
+    pub rate: Option<u32>,
+    /// The remaining capacity of the battery
+    pub remaining_capacity: Option<u32>,
+    /// The battery's voltage, in mV
+    pub voltage: Option<u32>,
+}
+
+impl BatteryStatus {
+    /// Decodes the package returned by `_BST`
+    fn from_bst(package: &[AcpiValue]) -> Result<Self, AcpiError> {
+        let state = package_integer(package, 0)?;
+
+        let charging_state = if state & (1 << 1) != 0 {
+            BatteryChargingState::Charging
+        } else if state & (1 << 0) != 0 {
+            BatteryChargingState::Discharging
+        } else {
+            BatteryChargingState::Idle
+        };
+
+        Ok(Self {
+            charging_state,
+            critical: state & (1 << 2) != 0,
+            charge_limiting: state & (1 << 3) != 0,
+            rate: battery_value(package, 1)?,
+            remaining_capacity: battery_value(package, 2)?,
+            voltage: battery_value(package, 3)?,
+        })
+    }
+
+    fn read(handle: AcpiHandle) -> Result<Self, AcpiError> {
+        Self::from_bst(&handle.evaluate_package("_BST", &[])?)
+    }
+}
+
+/// The values read from a battery, which are cleared when the battery notifies that they've changed
+#[derive(Debug, Default)]
+struct BatteryCache {
+    info: Option<BatteryInfo>,
+    status: Option<BatteryStatus>,
+}
+
+/// A control method battery, found with [`batteries`].
+///
+/// The values read from the battery are cached, and refreshed when the battery sends a notification that they've
+/// changed. The notify handler is removed when this struct is dropped.
+///
+/// [`batteries`]: AcpicaOperation::batteries
+#[derive(Debug)]
+pub struct Battery {
+    handle: AcpiHandle,
+    cache: Arc<Mutex<BatteryCache>>,
+    _notify_handler: NotifyHandlerGuard,
+}
+
+impl AcpicaOperation<true, true, true, true> {
+    /// Finds the present control method batteries (`PNP0C0A`) in the namespace, and installs notify handlers
+    /// to keep their cached values up to date
+    ///
+    /// # Errors
+    /// Any error raised while installing the notify handlers
+    pub fn batteries(&self) -> Result<Vec<Battery>, AcpiError> {
+        self.find_devices(BATTERY_HARDWARE_ID)
+            .into_iter()
+            .map(Battery::new)
+            .collect()
+    }
+}
+
+impl Battery {
+    fn new(handle: AcpiHandle) -> Result<Self, AcpiError> {
+        let cache = Arc::new(Mutex::new(BatteryCache::default()));
+
+        let handler_cache = Arc::clone(&cache);
+        let notify_handler = handle.install_notify_handler(
+            NotifyHandlerKind::Device,
+            move |handle, notification| match notification.to_u8() {
+                NOTIFY_BATTERY_STATUS => {
+                    let status = BatteryStatus::read(handle).ok();
+                    handler_cache.lock().status = status;
+                }
+                NOTIFY_BATTERY_INFO => {
+                    let info = BatteryInfo::read(handle).ok();
+                    let status = BatteryStatus::read(handle).ok();
+                    *handler_cache.lock() = BatteryCache { info, status };
+                }
+                _ => {}
+            },
+        )?;
+
+        Ok(Self {
+            handle,
+            cache,
+            _notify_handler: notify_handler,
+        })
+    }
+
+    /// Gets the battery's device in the AML namespace
+    #[must_use]
+    pub fn handle(&self) -> AcpiHandle {
+        self.handle
+    }
+
+    /// Checks whether a battery is inserted in the slot, using the battery present bit of `_STA`
+    ///
+    /// # Errors
+    /// Any error raised while evaluating `_STA`
+    pub fn is_present(&self) -> Result<bool, AcpiError> {
+        Ok(self.handle.status()?.battery_present())
+    }
+
+    /// Gets the battery's static information, reading it if it isn't cached
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlPackageLimit`] or [`AcpiError::AmlOperandType`] if the returned package is malformed
+    /// * Any error raised while evaluating `_BIX` or `_BIF`
+    pub fn info(&self) -> Result<BatteryInfo, AcpiError> {
+        if let Some(info) = &self.cache.lock().info {
+            return Ok(info.clone());
+        }
+
+        let info = BatteryInfo::read(self.handle)?;
+
+        // The notify handler may have stored newer information while the lock wasn't held
+        Ok(self.cache.lock().info.get_or_insert(info).clone())
+    }
+
+    /// Gets the battery's current state, reading it if it isn't cached
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlPackageLimit`] or [`AcpiError::AmlOperandType`] if the returned package is malformed
+    /// * Any error raised while evaluating `_BST`
+    pub fn status(&self) -> Result<BatteryStatus, AcpiError> {
+        if let Some(status) = self.cache.lock().status {
+            return Ok(status);
+        }
+
+        let status = BatteryStatus::read(self.handle)?;
+
+        // The notify handler may have stored a newer state while the lock wasn't held
+        Ok(*self.cache.lock().status.get_or_insert(status))
+    }
+
+    /// Re-reads the battery's information and state, e.g. to poll a battery which doesn't send notifications
+    ///
+    /// # Errors
+    /// Any error which [`info`] or [`status`] can return
+    ///
+    /// [`info`]: Battery::info
+    /// [`status`]: Battery::status
+    pub fn refresh(&self) -> Result<(), AcpiError> {
+        *self.cache.lock() = BatteryCache::default();
+
+        self.info()?;
+        self.status()?;
+
+        Ok(())
+    }
+
+    /// Sets the remaining capacity at which the battery sends a status change notification, using `_BTP`.
+    /// [`None`] clears the trip point.
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if the battery doesn't support trip points
+    /// * Any error raised while evaluating `_BTP`
+    pub fn set_trip_point(&self, capacity: Option<u32>) -> Result<(), AcpiError> {
+        let capacity = capacity.map_or(0, u64::from);
+
+        self.handle
+            .evaluate("_BTP", &[AcpiArgument::Integer(capacity)])
+            .map(|_| ())
+    }
+}
+
+/// Tests that `_BST` packages are decoded, including unknown values
+#[test]
+fn test_battery_status_from_bst() {
+    let status = BatteryStatus::from_bst(&[
+        AcpiValue::Integer(0b0110),
+        AcpiValue::Integer(BATTERY_VALUE_UNKNOWN),
+        AcpiValue::Integer(4200),
+        AcpiValue::Integer(12_600),
+    ])
+    .unwrap();
+
+    assert_eq!(status.charging_state, BatteryChargingState::Charging);
+    assert!(status.critical);
+    assert!(!status.charge_limiting);
+    assert_eq!(status.rate, None);
+    assert_eq!(status.remaining_capacity, Some(4200));
+    assert_eq!(status.voltage, Some(12_600));
+
+    assert_eq!(
+        BatteryStatus::from_bst(&[AcpiValue::Integer(0)]),
+        Err(AcpiError::AmlPackageLimit)
+    );
+}
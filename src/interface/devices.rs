@@ -1,34 +1,53 @@
 //! Code to manage AML devices
 
 use core::{
+    cell::RefCell,
     ffi::{c_void, CStr},
     fmt::Debug,
-    ptr::{addr_of_mut, null_mut},
+    mem::offset_of,
+    ptr::{addr_of_mut, null_mut, NonNull},
 };
 
-use alloc::string::String;
+use alloc::{ffi::CString, string::String, vec::Vec};
 use bitfield_struct::bitfield;
 
 use crate::{
     bindings::{
-        consts::{ACPI_FULL_PATHNAME, ACPI_NS_ROOT_PATH, ACPI_TYPE_DEVICE},
+        consts::{ACPI_FULL_PATHNAME, ACPI_NS_ROOT_PATH, ACPI_SINGLE_NAME},
         functions::{
-            AcpiGetHandle, AcpiGetIrqRoutingTable, AcpiGetName, AcpiGetObjectInfo,
-            AcpiWalkNamespace,
+            AcpiEvaluateObject, AcpiEvaluateObjectTyped, AcpiGetHandle, AcpiGetIrqRoutingTable,
+            AcpiGetName, AcpiGetNextObject, AcpiGetObjectInfo, AcpiGetParent, AcpiGetType,
+            AcpiOsFree, AcpiSetCurrentResources, AcpiWalkNamespace, AcpiWalkResources,
+        },
+        types::{
+            FfiAcpiBuffer, FfiAcpiDeviceInfo, FfiAcpiHandle, FfiAcpiPciRoutingTable,
+            FfiAcpiPnpDeviceId, FfiAcpiResource,
         },
-        types::{FfiAcpiBuffer, FfiAcpiDeviceInfo, FfiAcpiHandle, FfiAcpiPnpDeviceId},
     },
     status::{AcpiError, AcpiErrorAsStatusExt, AcpiStatus},
-    types::{object::AcpiObjectType, AcpiPhysicalAddress},
+    types::{
+        object::{
+            AcpiArgument, AcpiArgumentList, AcpiObject, AcpiObjectType, AcpiReturnObject, AcpiValue,
+        },
+        resource::{Resource, ResourceList},
+    },
     AcpicaOperation,
 };
 
 /// A handle to an object in the AML namespace
-#[derive(Debug)]
-pub struct AcpiHandle(FfiAcpiHandle);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcpiHandle(pub(crate) FfiAcpiHandle);
 
-/// Information about a device in the AML namespace
-pub struct DeviceInfo<'a>(&'a FfiAcpiDeviceInfo);
+// SAFETY: A handle refers to a namespace node, and ACPICA's functions which take handles can be called from any
+// thread, as they take the namespace lock or interpreter lock themselves
+unsafe impl Send for AcpiHandle {}
+// SAFETY: As above. Handles have no interior mutability.
+unsafe impl Sync for AcpiHandle {}
+
+/// Information about a device in the AML namespace, returned by [`AcpiHandle::get_info`].
+///
+/// The memory ACPICA allocated for the info is freed when this struct is dropped.
+pub struct DeviceInfo(NonNull<FfiAcpiDeviceInfo>);
 
 #[bitfield(u16)]
 struct AcpiDeviceInfoValidFields {
@@ -64,105 +83,245 @@ pub struct DeviceInfoFlags {
     _reserved: (),
 }
 
-impl<'a> DeviceInfo<'a> {
-    pub(crate) fn from_ffi(ffi_ptr: &'a FfiAcpiDeviceInfo) -> Self {
-        Self(ffi_ptr)
+/// The status of a device, returned from its `_STA` method
+#[bitfield(u32)]
+pub struct DeviceStatus {
+    /// Whether the device is present
+    pub present: bool,
+    /// Whether the device is enabled and decoding its hardware resources
+    pub enabled: bool,
+    /// Whether the device should be shown in the user interface
+    pub shown_in_ui: bool,
+    /// Whether the device is functioning properly
+    pub functioning: bool,
+    /// Whether a battery is present, if the device is a battery
+    pub battery_present: bool,
+
+    #[bits(27)]
+    _reserved: (),
+}
+
+impl DeviceStatus {
+    /// The status of a device which has no `_STA` method.
+    /// The ACPI spec says that such a device is present, enabled, shown in the UI, and functioning.
+    pub const DEFAULT: Self = Self::new()
+        .with_present(true)
+        .with_enabled(true)
+        .with_shown_in_ui(true)
+        .with_functioning(true);
+}
+
+/// A device power state, as used by the `_SxD` and `_SxW` methods
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeviceState {
+    /// The device is fully on
+    D0,
+    /// A device-specific low power state which uses less power than [`D0`][DeviceState::D0]
+    D1,
+    /// A device-specific low power state which uses less power than [`D1`][DeviceState::D1]
+    D2,
+    /// The device is off, but still has power
+    D3Hot,
+    /// The device is off and has no power
+    D3Cold,
+}
+
+impl DeviceState {
+    /// Converts a value returned from an `_SxD` or `_SxW` method into a [`DeviceState`].
+    /// ACPICA uses `0xFF` to indicate that the method didn't exist, in which case [`None`] is returned.
+    #[must_use]
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::D0),
+            1 => Some(Self::D1),
+            2 => Some(Self::D2),
+            3 => Some(Self::D3Hot),
+            4 => Some(Self::D3Cold),
+            _ => None,
+        }
+    }
+}
+
+/// The address of a PCI device on its bus, decoded from its `_ADR` value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PciDeviceAddress {
+    /// The device number
+    pub device: u16,
+    /// The function number, or [`None`] if the address refers to all functions of the device
+    pub function: Option<u16>,
+}
+
+impl PciDeviceAddress {
+    /// Decodes a PCI address from an `_ADR` value. The device number is in the high word and the function number is in the low word.
+    /// A function number of `0xFFFF` refers to all functions of the device.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_adr(adr: u64) -> Self {
+        let device = (adr >> 16) as u16;
+        let function = adr as u16;
+
+        Self {
+            device,
+            function: if function == 0xFFFF {
+                None
+            } else {
+                Some(function)
+            },
+        }
+    }
+}
+
+impl DeviceInfo {
+    /// # Safety
+    /// `ptr` must point to a device info struct which was allocated by `AcpiGetObjectInfo` and not yet freed
+    pub(crate) unsafe fn from_ffi(ptr: NonNull<FfiAcpiDeviceInfo>) -> Self {
+        Self(ptr)
+    }
+
+    fn info(&self) -> &FfiAcpiDeviceInfo {
+        // SAFETY: The pointer came from ACPICA and stays valid until `self` is dropped
+        unsafe { self.0.as_ref() }
     }
 
     #[must_use]
     fn valid(&self) -> AcpiDeviceInfoValidFields {
-        AcpiDeviceInfoValidFields::from(self.0.valid)
+        AcpiDeviceInfoValidFields::from(self.info().valid)
     }
 
     /// Gets the device's name
     #[must_use]
     #[allow(clippy::missing_panics_doc)]
     pub fn name(&self) -> &str {
-        core::str::from_utf8(&self.0.name).expect("Object name should have been valid utf-8")
+        core::str::from_utf8(&self.info().name).expect("Object name should have been valid utf-8")
     }
 
     /// Gets the type of the object in the AML namespace
     #[must_use]
     pub fn object_type(&self) -> AcpiObjectType {
-        AcpiObjectType::from_type_id(self.0.object_type)
+        AcpiObjectType::from_type_id(self.info().object_type)
     }
 
     /// Gets the number of parameters, if the object is a method.
     #[must_use]
     pub fn param_count(&self) -> u8 {
-        self.0.param_count
+        self.info().param_count
     }
 
     /// Gets the device's flags
     #[must_use]
     pub fn flags(&self) -> DeviceInfoFlags {
-        DeviceInfoFlags::from(self.0.flags)
+        DeviceInfoFlags::from(self.info().flags)
     }
 
-    /// TODO: What do these numbers mean?
-    /// ACPICA source says '`_SxD` values: `0xFF` indicates not valid'
+    /// Gets the values of the device's `_S1D` to `_S4D` methods.
+    /// Each value is the highest power state the device can be in while the system is in the corresponding sleep state,
+    /// or [`None`] if the method doesn't exist.
     #[must_use]
-    pub fn highest_dstates(&self) -> [u8; 4usize] {
-        self.0.highest_dstates
+    pub fn highest_dstates(&self) -> [Option<DeviceState>; 4] {
+        if self.valid().sxds() {
+            self.info().highest_dstates.map(DeviceState::from_u8)
+        } else {
+            [None; 4]
+        }
     }
 
-    /// TODO: What do these numbers mean?
-    /// ACPICA source says '`_SxW` values: `0xFF` indicates not valid'    
+    /// Gets the values of the device's `_S0W` to `_S4W` methods.
+    /// Each value is the lowest power state the device can be in while the system is in the corresponding sleep state,
+    /// and still be able to wake the system, or [`None`] if the method doesn't exist.
     #[must_use]
-    pub fn lowest_dstates(&self) -> [u8; 5usize] {
-        self.0.lowest_dstates
+    pub fn lowest_dstates(&self) -> [Option<DeviceState>; 5] {
+        if self.valid().sxws() {
+            self.info().lowest_dstates.map(DeviceState::from_u8)
+        } else {
+            [None; 5]
+        }
     }
 
-    /// The address of the device's memory mapped registers
+    /// The value of the device's `_ADR` method, which is the address of the device on its parent bus.
+    /// The format of the address depends on the type of bus.
     #[must_use]
-    #[allow(clippy::missing_panics_doc)]
-    pub fn address(&self) -> Option<AcpiPhysicalAddress> {
-        if self.0.address == 0 {
-            None
+    pub fn address(&self) -> Option<u64> {
+        if self.valid().adr() {
+            Some(self.info().address)
         } else {
-            Some(AcpiPhysicalAddress(self.0.address.try_into().unwrap()))
+            None
         }
     }
 
+    /// The device's address decoded as a PCI device and function number.
+    /// This is only meaningful if the device is on a PCI bus.
+    #[must_use]
+    pub fn pci_address(&self) -> Option<PciDeviceAddress> {
+        self.address().map(PciDeviceAddress::from_adr)
+    }
+
     /// A string identifying the device's hardware, for instance a chip ID
     #[must_use]
     pub fn hardware_id(&self) -> Option<&str> {
+        if !self.valid().hid() {
+            return None;
+        }
+
         // SAFETY: The device ID was provided by ACPICA so it points to valid memory
-        unsafe { pnp_device_to_str(&self.0.hardware_id) }
+        unsafe { pnp_device_to_str(&self.info().hardware_id) }
     }
 
     /// A string uniquely identifying the device
     #[must_use]
     pub fn unique_id(&self) -> Option<&str> {
+        if !self.valid().uid() {
+            return None;
+        }
+
         // SAFETY: The device ID was provided by ACPICA so it points to valid memory
-        unsafe { pnp_device_to_str(&self.0.unique_id) }
+        unsafe { pnp_device_to_str(&self.info().unique_id) }
     }
 
-    /// TODO: What's this?
+    /// The device's PCI class code, from its `_CLS` method.
+    /// This is formatted as six hex digits: the base class, the subclass, and the programming interface.
     #[must_use]
     pub fn class_code(&self) -> Option<&str> {
+        if !self.valid().cls() {
+            return None;
+        }
+
         // SAFETY: The device ID was provided by ACPICA so it points to valid memory
-        unsafe { pnp_device_to_str(&self.0.class_code) }
+        unsafe { pnp_device_to_str(&self.info().class_code) }
     }
 
     /// A list of IDs which are compatible with this device
     #[allow(clippy::missing_panics_doc)]
     pub fn compatible_id_list(&self) -> impl Iterator<Item = &str> {
-        // SAFETY: TODO
-        let arr = unsafe {
-            self.0
-                .compatible_id_list
-                .ids
-                .as_slice(self.0.compatible_id_list.count.try_into().unwrap())
+        let list = &self.info().compatible_id_list;
+        let count = if self.valid().cid() {
+            list.count.try_into().unwrap()
+        } else {
+            0
         };
 
-        arr.iter().filter_map(|device| 
-                // SAFETY: TODO
+        // SAFETY: ACPICA allocates `count` device IDs after the list header
+        let arr = unsafe { list.ids.as_slice(count) };
+
+        arr.iter().filter_map(|device|
+                // SAFETY: The device ID was provided by ACPICA so it points to valid memory
                 unsafe { pnp_device_to_str(device) })
     }
+
+    /// Whether `id` is the device's hardware ID or one of its compatible IDs
+    #[must_use]
+    pub fn matches_id(&self, id: &str) -> bool {
+        self.hardware_id() == Some(id) || self.compatible_id_list().any(|cid| cid == id)
+    }
+}
+
+impl Drop for DeviceInfo {
+    fn drop(&mut self) {
+        // SAFETY: The info was allocated by ACPICA using `AcpiOsAllocate`, and is not referenced anywhere else
+        unsafe { AcpiOsFree(self.0.as_ptr().cast()) }
+    }
 }
 
-impl<'a> Debug for DeviceInfo<'a> {
+impl Debug for DeviceInfo {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("DeviceInfo")
             .field("name", &self.name())
@@ -176,18 +335,22 @@ impl<'a> Debug for DeviceInfo<'a> {
             .field("hardware_id", &self.hardware_id())
             .field("unique_id", &self.unique_id())
             .field("class_code", &self.class_code())
-            // .field("compatible_id_list", &self.compatible_id_list())
-            // .field("compatible_id_list", )
+            .field(
+                "compatible_id_list",
+                &self.compatible_id_list().collect::<Vec<_>>(),
+            )
             .finish()
     }
 }
 
+/// # Safety
+/// `device` must have been provided by ACPICA, so that its string is either null or valid for `length` bytes
 unsafe fn pnp_device_to_str(device: &FfiAcpiPnpDeviceId) -> Option<&str> {
     if device.string.is_null() {
         None
     } else {
         let bytes =
-        // SAFETY: TODO
+        // SAFETY: The string is valid for `length` bytes
             unsafe { core::slice::from_raw_parts(device.string.cast(), device.length as _) };
         let bytes = bytes.split(|&v| v == 0).next().unwrap();
         let s = core::str::from_utf8(bytes).expect("PNP Device ID should have been valid utf-8");
@@ -209,29 +372,54 @@ pub enum AcpiPciPin {
 }
 
 impl AcpiPciPin {
-    const fn from_pin_number(pin_number: u32) -> Self {
+    /// Converts a pin number from a `_PRT` entry, where 0 is `INTA#`, into an [`AcpiPciPin`]
+    const fn from_pin_number(pin_number: u32) -> Option<Self> {
         match pin_number {
-            0 => Self::IntA,
-            1 => Self::IntB,
-            2 => Self::IntC,
-            3 => Self::IntD,
-            _ => panic!("Invalid pin number"),
+            0 => Some(Self::IntA),
+            1 => Some(Self::IntB),
+            2 => Some(Self::IntC),
+            3 => Some(Self::IntD),
+            _ => None,
         }
     }
+
+    /// Converts the value of the `Interrupt Pin` register in a device's PCI configuration space into an [`AcpiPciPin`].
+    /// Returns [`None`] if the value is 0, meaning that the device doesn't use an interrupt pin, or if the value is invalid.
+    #[must_use]
+    pub const fn from_config_register(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::IntA),
+            2 => Some(Self::IntB),
+            3 => Some(Self::IntC),
+            4 => Some(Self::IntD),
+            _ => None,
+        }
+    }
+
+    /// Gets the pin which a device's interrupt pin is connected to on the other side of a PCI-to-PCI bridge.
+    /// `device` is the device number on the bridge's secondary bus.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)] // The pin number is always less than 4
+    pub fn swizzle(self, device: u16) -> Self {
+        let pin = (self as u32 + u32::from(device)) % 4;
+        Self::from_pin_number(pin).unwrap()
+    }
 }
 
 /// An entry representing the IRQ mapping for a device
-#[derive(Debug, Clone, Copy)]
-pub struct AcpiPciRoutingTableEntry<'a> {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcpiPciRoutingTableEntry {
     /// Which PCI interrupt pin the device is connected to
     pub pin: AcpiPciPin,
-    /// An address relative to the bus the device is on.
-    /// The device number is specified
+    /// The device number on the bus. The entry applies to all functions of the device.
     pub device_number: u16,
-    /// The index of a type of interrupt generated by the device
+    /// If [`source`] is [`None`], the global system interrupt which the pin is connected to.
+    /// Otherwise, the index of the interrupt resource of the [`source`] device which the pin is connected to.
+    ///
+    /// [`source`]: AcpiPciRoutingTableEntry::source
     pub source_index: u32,
-    /// A string
-    pub source: &'a str,
+    /// The path to the interrupt link device which the pin is connected to, if it's not connected directly to a global system interrupt
+    pub source: Option<String>,
 }
 
 // #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -274,184 +462,764 @@ pub struct AcpiPciRoutingTableEntry<'a> {
 // }
 
 impl AcpiHandle {
-    /// Gets the object's path in the AML namespace
+    /// Evaluates the object at `path`, relative to this handle, passing `args` if the object is a method.
+    /// If `path` is empty, the object referred to by this handle is evaluated.
+    ///
+    /// Returns `Ok(None)` if the evaluation succeeded but did not produce a value.
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if `path` contains a null byte
+    /// * [`AcpiError::NotFound`] if the object doesn't exist
+    /// * Any error raised while executing the method
+    pub fn evaluate(
+        &self,
+        path: &str,
+        args: &[AcpiArgument],
+    ) -> Result<Option<AcpiReturnObject>, AcpiError> {
+        self.evaluate_with(path, &AcpiArgumentList::from(args))
+    }
+
+    /// Evaluates the object at `path` like [`evaluate`], but takes its arguments from an [`AcpiArgumentList`].
+    ///
+    /// # Errors
+    /// Any error which [`evaluate`] can return
+    ///
+    /// [`evaluate`]: AcpiHandle::evaluate
+    pub fn evaluate_with(
+        &self,
+        path: &str,
+        args: &AcpiArgumentList,
+    ) -> Result<Option<AcpiReturnObject>, AcpiError> {
+        let path = path_to_c_string(path)?;
+        let mut arg_list = args.as_ffi();
+
+        let (r, buffer) = FfiAcpiBuffer::allocate_owned(|b| {
+            // SAFETY: The path is either null or a valid null-terminated string, and the argument list
+            // points to objects owned by `args`, which lives until the end of this function.
+            unsafe {
+                AcpiEvaluateObject(
+                    self.0,
+                    c_string_ptr(path.as_ref()),
+                    addr_of_mut!(arg_list),
+                    b,
+                )
+            }
+        });
+
+        r.as_result()?;
+
+        Ok(buffer.map(AcpiReturnObject::from_buffer))
+    }
+
+    /// Evaluates the object at `path` like [`evaluate`], but also checks that the returned value has type `object_type`.
+    ///
+    /// # Errors
+    /// * [`AcpiError::Type`] if the object's value has a different type
+    /// * [`AcpiError::AmlNoReturnValue`] if the object didn't produce a value
+    /// * Any error which [`evaluate`] can return
+    ///
+    /// [`evaluate`]: AcpiHandle::evaluate
+    pub fn evaluate_typed(
+        &self,
+        path: &str,
+        args: &[AcpiArgument],
+        object_type: AcpiObjectType,
+    ) -> Result<AcpiReturnObject, AcpiError> {
+        self.evaluate_typed_with(path, &AcpiArgumentList::from(args), object_type)
+    }
+
+    /// Evaluates the object at `path` like [`evaluate_typed`], but takes its arguments from an [`AcpiArgumentList`].
+    ///
+    /// # Errors
+    /// Any error which [`evaluate_typed`] can return
+    ///
+    /// [`evaluate_typed`]: AcpiHandle::evaluate_typed
+    pub fn evaluate_typed_with(
+        &self,
+        path: &str,
+        args: &AcpiArgumentList,
+        object_type: AcpiObjectType,
+    ) -> Result<AcpiReturnObject, AcpiError> {
+        let path = path_to_c_string(path)?;
+        let mut arg_list = args.as_ffi();
+
+        let (r, buffer) = FfiAcpiBuffer::allocate_owned(|b| {
+            // SAFETY: The path is either null or a valid null-terminated string, and the argument list
+            // points to objects owned by `args`, which lives until the end of this function.
+            unsafe {
+                AcpiEvaluateObjectTyped(
+                    self.0,
+                    c_string_ptr(path.as_ref()),
+                    addr_of_mut!(arg_list),
+                    b,
+                    object_type.to_type_id(),
+                )
+            }
+        });
+
+        r.as_result()?;
+
+        buffer
+            .map(AcpiReturnObject::from_buffer)
+            .ok_or(AcpiError::AmlNoReturnValue)
+    }
+
+    /// Evaluates the object at `path` like [`evaluate_typed`], and gets the integer it returns.
+    ///
+    /// # Errors
+    /// Any error which [`evaluate_typed`] can return
+    ///
+    /// [`evaluate_typed`]: AcpiHandle::evaluate_typed
     #[allow(clippy::missing_panics_doc)]
+    pub fn evaluate_integer(&self, path: &str, args: &[AcpiArgument]) -> Result<u64, AcpiError> {
+        let value = self.evaluate_typed(path, args, AcpiObjectType::Integer)?;
+
+        let AcpiObject::Integer(value) = value.object() else {
+            unreachable!("ACPICA should have checked the type of the return value");
+        };
+
+        Ok(value)
+    }
+
+    /// Evaluates the object at `path` like [`evaluate_typed`], and copies the elements of the package it returns.
+    ///
+    /// # Errors
+    /// Any error which [`evaluate_typed`] can return
+    ///
+    /// [`evaluate_typed`]: AcpiHandle::evaluate_typed
+    pub fn evaluate_package(
+        &self,
+        path: &str,
+        args: &[AcpiArgument],
+    ) -> Result<Vec<AcpiValue>, AcpiError> {
+        let value = self.evaluate_typed(path, args, AcpiObjectType::Package)?;
+
+        match value.to_value() {
+            AcpiValue::Package(elements) => Ok(elements),
+            _ => Err(AcpiError::AmlOperandType),
+        }
+    }
+
+    /// Gets the object's path in the AML namespace
     pub fn path(&self) -> Result<String, AcpiError> {
+        self.get_name(ACPI_FULL_PATHNAME)
+    }
+
+    /// Gets the object's four character name, without the path of its parent
+    pub fn name(&self) -> Result<String, AcpiError> {
+        self.get_name(ACPI_SINGLE_NAME)
+    }
+
+    fn get_name(&self, name_type: u32) -> Result<String, AcpiError> {
         let (r, s) =
         // SAFETY: The arguments to this function are correct
-            FfiAcpiBuffer::allocate(|b| unsafe { AcpiGetName(self.0, ACPI_FULL_PATHNAME, b) });
+            FfiAcpiBuffer::allocate(|b| unsafe { AcpiGetName(self.0, name_type, b) });
 
-        let mut s = s.unwrap();
         r.as_result()?;
+        let mut s = s.map_err(|_| AcpiError::NoMemory)?;
 
         if let Some(i) = s.iter().rposition(|x| *x != 0) {
             let new_len = i + 1;
             s.truncate(new_len);
         };
 
-        Ok(String::from_utf8(s).expect("Path should have been valid utf-8"))
+        String::from_utf8(s).map_err(|_| AcpiError::BadCharacter)
     }
 
-    /// Gets the object's device info
+    /// Gets the type of the object
+    ///
+    /// # Errors
+    /// [`AcpiError::BadParameter`] if the handle is no longer valid
+    pub fn object_type(&self) -> Result<AcpiObjectType, AcpiError> {
+        let mut object_type = 0;
+
+        // SAFETY: The arguments to this function are correct
+        unsafe { AcpiGetType(self.0, addr_of_mut!(object_type)) }.as_result()?;
+
+        Ok(AcpiObjectType::from_type_id(object_type))
+    }
+
+    /// Gets the object's parent in the AML namespace, or [`None`] if this is the root object
+    #[must_use]
+    pub fn parent(&self) -> Option<AcpiHandle> {
+        let mut handle = null_mut();
+
+        // SAFETY: The arguments to this function are correct
+        let r = unsafe { AcpiGetParent(self.0, addr_of_mut!(handle)) };
+
+        r.as_result().ok().map(|()| AcpiHandle(handle))
+    }
+
+    /// Gets the object which comes after this one in its parent's list of children
+    #[must_use]
+    pub fn next_sibling(&self) -> Option<AcpiHandle> {
+        next_object(AcpiObjectType::Any, null_mut(), self.0)
+    }
+
+    /// Iterates over the object's children in the AML namespace
+    pub fn children(&self) -> impl Iterator<Item = AcpiHandle> {
+        self.children_of_type(AcpiObjectType::Any)
+    }
+
+    /// Iterates over the object's children in the AML namespace which have type `object_type`
+    pub fn children_of_type(
+        &self,
+        object_type: AcpiObjectType,
+    ) -> impl Iterator<Item = AcpiHandle> {
+        let parent = self.0;
+        let mut child: Option<FfiAcpiHandle> = None;
+
+        core::iter::from_fn(move || {
+            let next = next_object(object_type, parent, child.unwrap_or(null_mut()))?;
+            child = Some(next.0);
+            Some(next)
+        })
+    }
+
+    /// Gets the object at `path`, relative to this object.
+    /// If `path` starts with `\`, it is treated as an absolute path from the root of the namespace.
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if the object doesn't exist
+    /// * [`AcpiError::BadParameter`] if `path` is empty or contains a null byte
+    pub fn get_child(&self, path: &str) -> Result<AcpiHandle, AcpiError> {
+        let path = path_to_c_string(path)?.ok_or(AcpiError::BadParameter)?;
+        let mut handle = null_mut();
+
+        // SAFETY: The path is a valid null-terminated string
+        unsafe { AcpiGetHandle(self.0, c_string_ptr(Some(&path)), addr_of_mut!(handle)) }
+            .as_result()?;
+
+        Ok(AcpiHandle(handle))
+    }
+
+    /// Gets the device's status by evaluating its `_STA` method.
+    /// If the device has no `_STA` method, [`DeviceStatus::DEFAULT`] is returned.
+    ///
+    /// # Errors
+    /// Any error raised while executing the `_STA` method
     #[allow(clippy::missing_panics_doc)]
+    pub fn status(&self) -> Result<DeviceStatus, AcpiError> {
+        match self.evaluate_typed("_STA", &[], AcpiObjectType::Integer) {
+            Ok(value) => {
+                let AcpiObject::Integer(status) = value.object() else {
+                    unreachable!("ACPICA should have checked the type of the return value");
+                };
+
+                // Only the lower 32 bits of `_STA` are defined
+                #[allow(clippy::cast_possible_truncation)]
+                Ok(DeviceStatus::from(status as u32))
+            }
+            Err(AcpiError::NotFound) => Ok(DeviceStatus::DEFAULT),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gets the object's device info
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if the handle is no longer valid
+    /// * [`AcpiError::NoMemory`] if the info could not be allocated
     pub fn get_info(&self) -> Result<DeviceInfo, AcpiError> {
         let mut ptr = null_mut();
 
         // SAFETY: The arguments to this function are correct
-        let r = unsafe { AcpiGetObjectInfo(self.0, &mut ptr) };
+        let r = unsafe { AcpiGetObjectInfo(self.0, addr_of_mut!(ptr)) };
         r.as_result()?;
 
-        assert!(!ptr.is_null());
+        let ptr = NonNull::new(ptr).ok_or(AcpiError::NoMemory)?;
+
+        // SAFETY: `ptr` was just allocated by `AcpiGetObjectInfo`
+        unsafe { Ok(DeviceInfo::from_ffi(ptr)) }
+    }
+
+    /// Gets the resources which the device is currently using, by evaluating its `_CRS` method
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if the device has no `_CRS` method
+    /// * [`AcpiError::Type`] if the handle isn't a device
+    /// * Any error raised while executing the `_CRS` method or parsing its resource template
+    pub fn current_resources(&self) -> Result<impl Iterator<Item = Resource>, AcpiError> {
+        self.walk_resources(c"_CRS")
+    }
+
+    /// Gets the resources which the device could be configured to use, by evaluating its `_PRS` method
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if the device has no `_PRS` method
+    /// * [`AcpiError::Type`] if the handle isn't a device
+    /// * Any error raised while executing the `_PRS` method or parsing its resource template
+    pub fn possible_resources(&self) -> Result<impl Iterator<Item = Resource>, AcpiError> {
+        self.walk_resources(c"_PRS")
+    }
+
+    /// Configures the device to use the given resources, by evaluating its `_SRS` method.
+    ///
+    /// The resources should be a choice from [`possible_resources`], in the same order.
+    /// [`Resource::StartDependent`] and [`Resource::EndDependent`] should not be included.
+    ///
+    /// [`possible_resources`]: AcpiHandle::possible_resources
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if the device has no `_SRS` method
+    /// * [`AcpiError::Type`] if the handle isn't a device
+    /// * [`AcpiError::AmlInvalidResourceType`] if one of the resources is [`Resource::Other`]
+    /// * [`AcpiError::BadParameter`] if one of the resources can't be represented in a resource descriptor
+    /// * Any error raised while executing the `_SRS` method
+    pub fn set_current_resources(&self, resources: &[Resource]) -> Result<(), AcpiError> {
+        let mut list = ResourceList::new(resources)?;
+        let mut buffer = list.as_ffi();
+
+        // SAFETY: `buffer` points to a valid resource list, which lives until after the call
+        unsafe { AcpiSetCurrentResources(self.0, addr_of_mut!(buffer)) }.as_result()
+    }
+
+    /// Picks an interrupt from the device's `_PRS` resources and configures the device to use it.
+    /// This is mainly used to route PCI interrupt link devices such as `LNKA`.
+    ///
+    /// The first [`Resource::Irq`] or [`Resource::ExtendedIrq`] from `_PRS` is used. `choose` is called with the interrupts
+    /// it allows and returns the one to use, for instance the one with the fewest devices already using it.
+    /// The device is then configured with a copy of the resource containing only that interrupt, and the interrupt is returned.
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if `_PRS` has no interrupt resources or `choose` returns [`None`]
+    /// * [`AcpiError::BadParameter`] if `choose` returns an interrupt which wasn't in the list
+    /// * Any error from [`possible_resources`] or [`set_current_resources`]
+    ///
+    /// [`possible_resources`]: AcpiHandle::possible_resources
+    /// [`set_current_resources`]: AcpiHandle::set_current_resources
+    pub fn set_irq_from_possible_resources(
+        &self,
+        choose: impl FnOnce(&[u32]) -> Option<u32>,
+    ) -> Result<u32, AcpiError> {
+        let resource = self
+            .possible_resources()?
+            .find(|r| matches!(r, Resource::Irq(_) | Resource::ExtendedIrq(_)))
+            .ok_or(AcpiError::NotFound)?;
+
+        let (resource, chosen) = match resource {
+            Resource::Irq(mut irq) => {
+                let possible = irq
+                    .interrupts
+                    .iter()
+                    .copied()
+                    .map(u32::from)
+                    .collect::<Vec<_>>();
+                let chosen = choose(&possible).ok_or(AcpiError::NotFound)?;
+                let index = possible
+                    .iter()
+                    .position(|i| *i == chosen)
+                    .ok_or(AcpiError::BadParameter)?;
+
+                irq.interrupts = alloc::vec![irq.interrupts[index]];
+                (Resource::Irq(irq), chosen)
+            }
+            Resource::ExtendedIrq(mut irq) => {
+                let chosen = choose(&irq.interrupts).ok_or(AcpiError::NotFound)?;
+
+                if !irq.interrupts.contains(&chosen) {
+                    return Err(AcpiError::BadParameter);
+                }
+
+                irq.interrupts = alloc::vec![chosen];
+                (Resource::ExtendedIrq(irq), chosen)
+            }
+            _ => unreachable!("Only interrupt resources were searched for"),
+        };
 
-        // SAFETY: `ptr` is valid
-        unsafe { Ok(DeviceInfo::from_ffi(&*ptr)) }
+        self.set_current_resources(&[resource])?;
+
+        Ok(chosen)
     }
 
-    /// If the device is a PCI bus, this method returns the IRQ mapping for the devices on that bus.
+    /// Collects the resources returned by the given method
+    fn walk_resources(&self, method: &CStr) -> Result<impl Iterator<Item = Resource>, AcpiError> {
+        let mut resources = Vec::new();
+
+        // SAFETY: `collect_resource` expects a `Vec<Resource>` as its context, and
+        // ACPICA doesn't write to the method name so the cast to a mutable pointer is sound
+        unsafe {
+            AcpiWalkResources(
+                self.0,
+                method.as_ptr().cast_mut(),
+                collect_resource,
+                addr_of_mut!(resources).cast(),
+            )
+        }
+        .as_result()?;
+
+        Ok(resources.into_iter())
+    }
+
+    /// If the device is a PCI bus, this method returns the IRQ mapping for the devices on that bus, from its `_PRT` method.
     /// Note that this is only for pin-based interrupts, and can be ignored if using MSI.
+    ///
+    /// Returns [`None`] if the device has no `_PRT` method. Entries with an invalid pin number are skipped.
+    /// To find the interrupt a device is connected to, [`resolve_pci_interrupt`] should usually be used instead.
+    ///
+    /// [`resolve_pci_interrupt`]: AcpicaOperation::resolve_pci_interrupt
+    ///
+    /// # Errors
+    /// Any error raised while executing the `_PRT` method or parsing the table it returns
     #[allow(clippy::missing_panics_doc)]
     pub fn get_irq_routing_table(
         &self,
     ) -> Result<Option<impl Iterator<Item = AcpiPciRoutingTableEntry>>, AcpiError> {
+        const SOURCE_OFFSET: usize = offset_of!(FfiAcpiPciRoutingTable, source);
+
         // SAFETY: The arguments to this function are correct
         let (r, buffer) = FfiAcpiBuffer::allocate(|b| unsafe { AcpiGetIrqRoutingTable(self.0, b) });
 
         match r.as_result() {
             Ok(()) => (),
             Err(AcpiError::NotFound) => return Ok(None),
-            Err(_) => r.as_result().unwrap(),
+            Err(e) => return Err(e),
         }
 
-        r.as_result()?;
+        let buffer = buffer.map_err(|_| AcpiError::NoMemory)?;
+        let read_u32 = |i: usize| u32::from_ne_bytes(buffer[i..i + 4].try_into().unwrap());
 
-        let buffer = buffer.unwrap();
+        let mut entries = Vec::new();
         let mut i = 0;
 
-        Ok(Some(core::iter::from_fn(move || {
-            let length = u32::from_ne_bytes(buffer[i..i + 4].try_into().unwrap());
-            let pin = u32::from_ne_bytes(buffer[i + 4..i + 8].try_into().unwrap());
+        // The table ends with an entry with a length of 0
+        while i + SOURCE_OFFSET <= buffer.len() {
+            let length = read_u32(i) as usize;
+            if length == 0 {
+                break;
+            }
+
+            let pin = read_u32(i + 4);
             let address = u64::from_ne_bytes(buffer[i + 8..i + 16].try_into().unwrap());
-            let source_index = u32::from_ne_bytes(buffer[i + 16..i + 20].try_into().unwrap());
+            let source_index = read_u32(i + 16);
+
+            let source = buffer
+                .get(i + SOURCE_OFFSET..(i + length).min(buffer.len()))
+                .unwrap_or_default();
+            let source = source.split(|b| *b == 0).next().unwrap_or_default();
+
+            i += length;
+
+            let Some(pin) = AcpiPciPin::from_pin_number(pin) else {
+                continue;
+            };
+
+            // The function number should always be 0xFFFF, meaning all functions, so it is ignored
+            entries.push(AcpiPciRoutingTableEntry {
+                pin,
+                device_number: PciDeviceAddress::from_adr(address).device,
+                source_index,
+                source: if source.is_empty() {
+                    None
+                } else {
+                    Some(String::from_utf8_lossy(source).into_owned())
+                },
+            });
+        }
 
-            if length == 0 {
-                None
-            } else {
-                // SAFETY: This property can be dereferenced as a null-terminated string
-                let source = unsafe { CStr::from_ptr(buffer[i + 24..].as_ptr().offset(4).cast()) };
+        Ok(Some(entries.into_iter()))
+    }
+}
 
-                i += length as usize;
+/// Converts a namespace path into a C string, or `None` if the path is empty
+fn path_to_c_string(path: &str) -> Result<Option<CString>, AcpiError> {
+    if path.is_empty() {
+        Ok(None)
+    } else {
+        CString::new(path)
+            .map(Some)
+            .map_err(|_| AcpiError::BadParameter)
+    }
+}
 
-                #[allow(clippy::cast_possible_truncation)]
-                let device_number = {
-                    // ACPI spec says this this word needs to be 0xFFFF (meaning all functions)
-                    assert_eq!(
-                        address as u16, 0xFFFF,
-                        "Function number of PRT address field should be 0xFFFF"
-                    );
-                    (address >> 16) as u16
-                };
+/// Gets the next child of `parent` after `child` with type `object_type`.
+/// If `child` is null, the first child is returned. Otherwise, `parent` is ignored.
+fn next_object(
+    object_type: AcpiObjectType,
+    parent: FfiAcpiHandle,
+    child: FfiAcpiHandle,
+) -> Option<AcpiHandle> {
+    let mut handle = null_mut();
+
+    // SAFETY: The arguments to this function are correct
+    let r = unsafe {
+        AcpiGetNextObject(
+            object_type.to_type_id(),
+            parent,
+            child,
+            addr_of_mut!(handle),
+        )
+    };
+
+    r.as_result().ok().map(|()| AcpiHandle(handle))
+}
 
-                Some(AcpiPciRoutingTableEntry {
-                    pin: AcpiPciPin::from_pin_number(pin),
-                    device_number,
-                    source_index,
-                    source: source.to_str().unwrap(),
-                })
-            }
-        })))
+/// A callback for `AcpiWalkResources` which copies each resource into the [`Vec<Resource>`] pointed to by `context`
+unsafe extern "C" fn collect_resource(
+    resource: *mut FfiAcpiResource,
+    context: *mut c_void,
+) -> AcpiStatus {
+    // SAFETY: `context` was passed by `walk_resources` so it is valid for this type
+    let resources: &mut Vec<Resource> = unsafe { &mut *context.cast() };
+
+    // SAFETY: `resource` was provided by ACPICA, and is only valid for the duration of this callback,
+    // so it's converted into an owned value here
+    if let Some(resource) = unsafe { Resource::from_ffi(resource) } {
+        resources.push(resource);
     }
+
+    AcpiStatus::OK
+}
+
+fn c_string_ptr(s: Option<&CString>) -> *mut i8 {
+    // ACPICA doesn't write to the path, so the cast to a mutable pointer is sound
+    s.map_or(null_mut(), |s| s.as_ptr().cast_mut())
 }
 
-struct ScanContext<'a, T, F>
+/// The value returned from a callback passed to [`walk_namespace`], which determines how the walk continues.
+///
+/// [`walk_namespace`]: AcpicaOperation::walk_namespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkDecision<T> {
+    /// Continue walking the namespace, including the children of the current object
+    Continue,
+    /// Continue walking the namespace, but don't visit the children of the current object.
+    /// This has the same effect as [`Continue`] when returned from the ascending callback, as the children have already been visited.
+    ///
+    /// [`Continue`]: WalkDecision::Continue
+    SkipChildren,
+    /// Stop walking the namespace, and return the given value from [`walk_namespace`]
+    ///
+    /// [`walk_namespace`]: AcpicaOperation::walk_namespace
+    Stop(T),
+}
+
+struct WalkContext<T, D, A>
 where
-    F: Fn(AcpiHandle, u32) -> Option<T>,
+    D: FnMut(AcpiHandle, u32) -> WalkDecision<T>,
+    A: FnMut(AcpiHandle, u32) -> WalkDecision<T>,
 {
-    function: &'a F,
-    result: &'a mut Option<T>,
+    descending: D,
+    ascending: A,
+    result: Option<T>,
 }
 
-unsafe extern "C" fn scan_single_device<T, F>(
+impl<T, D, A> WalkContext<T, D, A>
+where
+    D: FnMut(AcpiHandle, u32) -> WalkDecision<T>,
+    A: FnMut(AcpiHandle, u32) -> WalkDecision<T>,
+{
+    /// Converts the decision from a callback into a status for ACPICA
+    fn process(&mut self, decision: WalkDecision<T>) -> AcpiStatus {
+        match decision {
+            WalkDecision::Continue => AcpiStatus::OK,
+            WalkDecision::SkipChildren => AcpiError::ControlDepth.to_acpi_status(),
+            WalkDecision::Stop(result) => {
+                self.result = Some(result);
+                AcpiError::ControlTerminate.to_acpi_status()
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn walk_descending<T, D, A>(
     handle: FfiAcpiHandle,
     nesting_level: u32,
     context_ptr: *mut c_void,
-    return_value: *mut *mut c_void,
+    _return_value: *mut *mut c_void,
 ) -> AcpiStatus
 where
-    F: Fn(AcpiHandle, u32) -> Option<T>,
+    D: FnMut(AcpiHandle, u32) -> WalkDecision<T>,
+    A: FnMut(AcpiHandle, u32) -> WalkDecision<T>,
 {
-    if context_ptr.is_null() || return_value.is_null() {
+    if context_ptr.is_null() {
         return AcpiError::BadParameter.to_acpi_status();
     }
 
-    // SAFETY: `context` was passed by `scan_devices` so it is valid for this type
-    let context: &mut ScanContext<T, F> = unsafe { &mut *context_ptr.cast() };
-
-    let handle = AcpiHandle(handle);
+    // SAFETY: `context` was passed by `walk_namespace` so it is valid for this type
+    let context: &mut WalkContext<T, D, A> = unsafe { &mut *context_ptr.cast() };
 
-    let v = (context.function)(handle, nesting_level);
+    let decision = (context.descending)(AcpiHandle(handle), nesting_level);
+    context.process(decision)
+}
 
-    if let Some(result) = v {
-        *context.result = Some(result);
-        // SAFETY:
-        unsafe { *return_value = context_ptr }
+unsafe extern "C" fn walk_ascending<T, D, A>(
+    handle: FfiAcpiHandle,
+    nesting_level: u32,
+    context_ptr: *mut c_void,
+    _return_value: *mut *mut c_void,
+) -> AcpiStatus
+where
+    D: FnMut(AcpiHandle, u32) -> WalkDecision<T>,
+    A: FnMut(AcpiHandle, u32) -> WalkDecision<T>,
+{
+    if context_ptr.is_null() {
+        return AcpiError::BadParameter.to_acpi_status();
     }
 
-    AcpiStatus::OK
-}
+    // SAFETY: `context` was passed by `walk_namespace` so it is valid for this type
+    let context: &mut WalkContext<T, D, A> = unsafe { &mut *context_ptr.cast() };
 
-// ACPICA needs a callback for after a device has been recursively scanned
-unsafe extern "C" fn scan_single_device_ascending(
-    _handle: FfiAcpiHandle,
-    _nesting_level: u32,
-    _context: *mut c_void,
-    _return_value: *mut *mut c_void,
-) -> AcpiStatus {
-    AcpiStatus::OK
+    match (context.ascending)(AcpiHandle(handle), nesting_level) {
+        // The children have already been visited, so there's nothing to skip
+        WalkDecision::SkipChildren => AcpiStatus::OK,
+        decision => context.process(decision),
+    }
 }
 
-impl AcpicaOperation<true, true, true, true> {
-    /// Calls a callback for each device in the AML namespace
+impl<const E: bool, const I: bool> AcpicaOperation<true, true, E, I> {
+    /// Gets a handle to the root of the AML namespace
     #[allow(clippy::missing_panics_doc)]
-    pub fn scan_devices<F: Fn(AcpiHandle, u32) -> Option<T>, T>(&self, function: F) -> Option<T> {
-        let mut out: Option<T> = None;
-        let mut out_ptr: *mut T = null_mut();
-
+    #[must_use]
+    pub fn root(&self) -> AcpiHandle {
         let mut handle: FfiAcpiHandle = null_mut();
 
         // SAFETY: These arguments are valid
-        unsafe {
+        let r = unsafe {
             let mut root_path_copy = *ACPI_NS_ROOT_PATH;
 
             AcpiGetHandle(null_mut(), root_path_copy.as_mut_ptr().cast(), &mut handle)
         };
 
-        let mut context = ScanContext {
-            function: &function,
-            result: &mut out,
+        // The root object always exists once the namespace has been loaded
+        r.as_result().unwrap();
+
+        AcpiHandle(handle)
+    }
+
+    /// Gets the object at `path` relative to the root of the AML namespace
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if the object doesn't exist
+    /// * [`AcpiError::BadParameter`] if `path` is empty or contains a null byte
+    pub fn get_handle(&self, path: &str) -> Result<AcpiHandle, AcpiError> {
+        self.root().get_child(path)
+    }
+
+    /// Walks the AML namespace below `start`, visiting objects of type `object_type` up to `max_depth` levels deep.
+    /// If `object_type` is [`AcpiObjectType::Any`], every object is visited.
+    ///
+    /// `descending` is called for each object before its children are visited, and `ascending` is called after.
+    /// Both callbacks are passed the object and its depth below `start`, and return a [`WalkDecision`] to control the walk.
+    /// If either callback returns [`WalkDecision::Stop`], the walk ends and the value is returned.
+    ///
+    /// The namespace is not locked while the callbacks run, so they can evaluate AML methods.
+    ///
+    /// # Errors
+    /// [`AcpiError::BadParameter`] if `start` is not a valid handle
+    pub fn walk_namespace<T, D, A>(
+        &self,
+        start: AcpiHandle,
+        object_type: AcpiObjectType,
+        max_depth: u32,
+        descending: D,
+        ascending: A,
+    ) -> Result<Option<T>, AcpiError>
+    where
+        D: FnMut(AcpiHandle, u32) -> WalkDecision<T>,
+        A: FnMut(AcpiHandle, u32) -> WalkDecision<T>,
+    {
+        let mut context = WalkContext {
+            descending,
+            ascending,
+            result: None,
         };
 
-        // SAFETY: The arguments are correct
-        let return_value = unsafe {
+        // SAFETY: The callbacks are generic over the type of `context`
+        let r = unsafe {
             AcpiWalkNamespace(
-                ACPI_TYPE_DEVICE,
-                handle,
-                u32::MAX,
-                scan_single_device::<T, F>,
-                scan_single_device_ascending,
-                core::ptr::addr_of_mut!(context).cast(),
-                addr_of_mut!(out_ptr).cast(),
+                object_type.to_type_id(),
+                start.0,
+                max_depth,
+                walk_descending::<T, D, A>,
+                walk_ascending::<T, D, A>,
+                addr_of_mut!(context).cast(),
+                null_mut(),
             )
         };
 
+        match r.as_result() {
+            // A `Stop` from the ascending callback is passed back to the caller
+            Ok(()) | Err(AcpiError::ControlTerminate) => Ok(context.result),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl AcpicaOperation<true, true, true, true> {
+    /// Calls a callback for each device in the AML namespace
+    #[allow(clippy::missing_panics_doc)]
+    pub fn scan_devices<F: Fn(AcpiHandle, u32) -> Option<T>, T>(&self, function: F) -> Option<T> {
+        self.walk_namespace(
+            self.root(),
+            AcpiObjectType::Device,
+            u32::MAX,
+            |handle, depth| match function(handle, depth) {
+                Some(result) => WalkDecision::Stop(result),
+                None => WalkDecision::Continue,
+            },
+            |_, _| WalkDecision::Continue,
+        )
         // AcpiWalkNamespace only returns BadParameter as an error, so if this fails it is this library's fault.
-        return_value.as_result().unwrap();
+        .unwrap()
+    }
 
-        if let Some(ref o) = out {
-            assert_eq!(o as *const T, out_ptr.cast());
-        } else {
-            assert!(out_ptr.is_null());
-        }
+    /// Calls a callback for each device in the AML namespace which is present, according to its `_STA` method.
+    ///
+    /// * If a device is neither present nor functioning, or its `_STA` method fails, neither it nor its children are visited.
+    /// * If a device is not present but is functioning, it is skipped but its children are still visited.
+    ///
+    /// This differs from ACPICA's `AcpiGetDevices`, which also passes devices that are functioning but not present
+    /// to its callback.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn scan_present_devices<F: Fn(AcpiHandle, u32) -> Option<T>, T>(
+        &self,
+        function: F,
+    ) -> Option<T> {
+        self.walk_namespace(
+            self.root(),
+            AcpiObjectType::Device,
+            u32::MAX,
+            |handle, depth| {
+                let Ok(status) = handle.status() else {
+                    return WalkDecision::SkipChildren;
+                };
+
+                if !status.present() {
+                    return if status.functioning() {
+                        WalkDecision::Continue
+                    } else {
+                        WalkDecision::SkipChildren
+                    };
+                }
+
+                match function(handle, depth) {
+                    Some(result) => WalkDecision::Stop(result),
+                    None => WalkDecision::Continue,
+                }
+            },
+            |_, _| WalkDecision::Continue,
+        )
+        // AcpiWalkNamespace only returns BadParameter as an error, so if this fails it is this library's fault.
+        .unwrap()
+    }
+
+    /// Gets all the present devices whose hardware ID or compatible IDs include `id`, such as `"PNP0C0A"` for batteries.
+    /// Devices are visited in the same order as [`scan_present_devices`].
+    ///
+    /// [`scan_present_devices`]: AcpicaOperation::scan_present_devices
+    #[must_use]
+    pub fn find_devices(&self, id: &str) -> Vec<AcpiHandle> {
+        let devices = RefCell::new(Vec::new());
+
+        self.scan_present_devices(|device, _| {
+            if device.get_info().is_ok_and(|info| info.matches_id(id)) {
+                devices.borrow_mut().push(device);
+            }
+
+            None::<()>
+        });
 
-        out
+        devices.into_inner()
     }
 }
@@ -0,0 +1,586 @@
+//! A driver for the embedded controller (EC), which AML code uses to access batteries, thermal sensors,
+//! the lid switch and other platform hardware
+
+use core::{
+    ffi::c_void,
+    fmt::Debug,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    sync::Arc,
+};
+use log::warn;
+use spin::Mutex;
+
+use crate::{
+    address_space::{AddressSpaceHandler, AddressSpaceHandlerGuard},
+    bindings::{
+        consts::ACPI_WAIT_FOREVER,
+        functions::{
+            AcpiAcquireGlobalLock, AcpiOsExecute, AcpiOsReadPort, AcpiOsStall, AcpiOsWritePort,
+            AcpiReleaseGlobalLock,
+        },
+        types::FfiAcpiExecuteType,
+    },
+    devices::{AcpiHandle, WalkDecision},
+    gpe::{GpeHandlerGuard, GpeHandlerResult, GpeTrigger},
+    status::AcpiError,
+    types::{
+        object::AcpiObjectType, resource::Resource, AcpiGenericAddress, AcpiIoAddress,
+        GasAddressSpace,
+    },
+    AcpicaOperation, AcpicaOperationFullyInitialized,
+};
+
+/// The hardware ID of an embedded controller device
+const EC_HARDWARE_ID: &str = "PNP0C09";
+
+/// Set in the status register when the EC has written a byte to the data register
+const EC_STATUS_OBF: u8 = 1 << 0;
+/// Set in the status register until the EC has read the last byte written to the command or data register
+const EC_STATUS_IBF: u8 = 1 << 1;
+/// Set in the status register when the EC has an event which should be read with the query command
+const EC_STATUS_SCI_EVT: u8 = 1 << 5;
+
+const EC_COMMAND_READ: u8 = 0x80;
+const EC_COMMAND_WRITE: u8 = 0x81;
+const EC_COMMAND_QUERY: u8 = 0x84;
+
+/// The time to wait between polls of the status register, in microseconds
+const EC_POLL_INTERVAL: u32 = 10;
+/// The number of times to poll the status register before giving up, which adds up to one second
+const EC_POLL_LIMIT: u32 = 100_000;
+
+/// Reads a byte from an I/O port through `AcpiOsReadPort`, and so through the OS's [`read_port_u8`]
+///
+/// [`read_port_u8`]: crate::handler::AcpiHandler::read_port_u8
+fn read_port(port: AcpiIoAddress) -> Result<u8, AcpiError> {
+    let mut value = 0;
+
+    // SAFETY: `value` is valid for writes. The port belongs to the EC, so reading it has no other side effects.
+    unsafe { AcpiOsReadPort(port.0 as u64, core::ptr::addr_of_mut!(value), 8).as_result()? };
+
+    #[allow(clippy::cast_possible_truncation)] // Only 8 bits were read
+    Ok(value as u8)
+}
+
+/// Writes a byte to an I/O port through `AcpiOsWritePort`, and so through the OS's [`write_port_u8`]
+///
+/// [`write_port_u8`]: crate::handler::AcpiHandler::write_port_u8
+fn write_port(port: AcpiIoAddress, value: u8) -> Result<(), AcpiError> {
+    // SAFETY: The port belongs to the EC, and the caller is following the EC protocol
+    unsafe { AcpiOsWritePort(port.0 as u64, u32::from(value), 8).as_result() }
+}
+
+/// Gets the I/O port described by a register in the ECDT
+fn ecdt_port(register: AcpiGenericAddress) -> Result<AcpiIoAddress, AcpiError> {
+    if register.space_id != GasAddressSpace::SystemIO {
+        return Err(AcpiError::Support);
+    }
+
+    let port = usize::try_from(register.address).map_err(|_| AcpiError::BadAddress)?;
+
+    Ok(AcpiIoAddress(port))
+}
+
+/// Gets the base port of an I/O resource
+fn resource_port(resource: &Resource) -> Option<AcpiIoAddress> {
+    match resource {
+        Resource::Io(io) => Some(AcpiIoAddress(io.minimum.into())),
+        Resource::FixedIo(io) => Some(AcpiIoAddress(io.address.into())),
+        _ => None,
+    }
+}
+
+/// The state of an EC which is shared between the [`EmbeddedController`], its region handler and its GPE handler
+struct EcInner {
+    command: AcpiIoAddress,
+    data: AcpiIoAddress,
+    /// Held for the duration of each transaction, so that bytes from different transactions don't interleave
+    transaction: Mutex<()>,
+    /// Whether the device's `_GLK` method says that the ACPI global lock must be held while accessing the EC
+    use_global_lock: AtomicBool,
+    /// Whether queries have been queued to run with `AcpiOsExecute`
+    query_pending: AtomicBool,
+    /// The EC's device, which `_Qxx` methods are evaluated on
+    device: Mutex<Option<AcpiHandle>>,
+}
+
+impl EcInner {
+    fn new(command: AcpiIoAddress, data: AcpiIoAddress) -> Self {
+        Self {
+            command,
+            data,
+            transaction: Mutex::new(()),
+            use_global_lock: AtomicBool::new(false),
+            query_pending: AtomicBool::new(false),
+            device: Mutex::new(None),
+        }
+    }
+
+    /// Sets the EC's device and reads its `_GLK` method
+    fn set_device(&self, device: AcpiHandle) {
+        let use_global_lock = device
+            .evaluate_integer("_GLK", &[])
+            .is_ok_and(|glk| glk != 0);
+
+        self.use_global_lock
+            .store(use_global_lock, Ordering::Release);
+        *self.device.lock() = Some(device);
+    }
+
+    fn status(&self) -> Result<u8, AcpiError> {
+        read_port(self.command)
+    }
+
+    /// Polls the status register until `condition` returns `true`
+    fn wait_for(&self, condition: impl Fn(u8) -> bool) -> Result<(), AcpiError> {
+        for _ in 0..EC_POLL_LIMIT {
+            if condition(self.status()?) {
+                return Ok(());
+            }
+
+            // SAFETY: Stalling has no preconditions
+            unsafe { AcpiOsStall(EC_POLL_INTERVAL) };
+        }
+
+        Err(AcpiError::Time)
+    }
+
+    fn send_command(&self, command: u8) -> Result<(), AcpiError> {
+        self.wait_for(|status| status & EC_STATUS_IBF == 0)?;
+        write_port(self.command, command)
+    }
+
+    fn send_data(&self, data: u8) -> Result<(), AcpiError> {
+        self.wait_for(|status| status & EC_STATUS_IBF == 0)?;
+        write_port(self.data, data)
+    }
+
+    fn receive_data(&self) -> Result<u8, AcpiError> {
+        self.wait_for(|status| status & EC_STATUS_OBF != 0)?;
+        read_port(self.data)
+    }
+
+    /// Runs `f` while holding the transaction lock, and the global lock if the EC needs it
+    fn transaction<T>(&self, f: impl FnOnce() -> Result<T, AcpiError>) -> Result<T, AcpiError> {
+        let _transaction = self.transaction.lock();
+
+        if !self.use_global_lock.load(Ordering::Acquire) {
+            return f();
+        }
+
+        let mut handle = 0;
+
+        #[allow(clippy::cast_possible_truncation)] // ACPI_WAIT_FOREVER fits in a u16
+        // SAFETY: `handle` is valid for writes
+        unsafe {
+            AcpiAcquireGlobalLock(ACPI_WAIT_FOREVER as u16, core::ptr::addr_of_mut!(handle))
+                .as_result()?;
+        };
+
+        let result = f();
+
+        // SAFETY: The global lock was acquired with this handle
+        unsafe { AcpiReleaseGlobalLock(handle).as_result()? };
+
+        result
+    }
+
+    fn read(&self, address: u8) -> Result<u8, AcpiError> {
+        self.transaction(|| {
+            self.send_command(EC_COMMAND_READ)?;
+            self.send_data(address)?;
+            self.receive_data()
+        })
+    }
+
+    fn write(&self, address: u8, value: u8) -> Result<(), AcpiError> {
+        self.transaction(|| {
+            self.send_command(EC_COMMAND_WRITE)?;
+            self.send_data(address)?;
+            self.send_data(value)?;
+            self.wait_for(|status| status & EC_STATUS_IBF == 0)
+        })
+    }
+
+    fn query(&self) -> Result<Option<u8>, AcpiError> {
+        let query = self.transaction(|| {
+            self.send_command(EC_COMMAND_QUERY)?;
+            self.receive_data()
+        })?;
+
+        Ok((query != 0).then_some(query))
+    }
+
+    /// Queues [`run_queries`] with `AcpiOsExecute` if the EC has an event and queries aren't already queued
+    fn queue_queries(self: &Arc<Self>) {
+        if !self
+            .status()
+            .is_ok_and(|status| status & EC_STATUS_SCI_EVT != 0)
+        {
+            return;
+        }
+
+        if self.query_pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let context = Arc::into_raw(Arc::clone(self)).cast_mut().cast::<c_void>();
+
+        // SAFETY: `run_queries` takes ownership of the reference in `context`
+        let r = unsafe { AcpiOsExecute(FfiAcpiExecuteType::GpeHandler, run_queries, context) };
+
+        if r.as_result().is_err() {
+            self.query_pending.store(false, Ordering::Release);
+
+            // SAFETY: The callback wasn't queued, so the reference in `context` wasn't taken
+            drop(unsafe { Arc::from_raw(context.cast_const().cast::<Self>()) });
+        }
+    }
+
+    /// Queries the EC until it has no more events, evaluating the `_Qxx` method for each one
+    fn run_queries(&self) {
+        loop {
+            loop {
+                let query = match self.query() {
+                    Ok(Some(query)) => query,
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("Failed to query embedded controller: {e:?}");
+                        break;
+                    }
+                };
+
+                let device = *self.device.lock();
+                let Some(device) = device else { continue };
+
+                match device.evaluate(&format!("_Q{query:02X}"), &[]) {
+                    Ok(_) | Err(AcpiError::NotFound) => {}
+                    Err(e) => {
+                        warn!("Failed to evaluate _Q{query:02X} on embedded controller: {e:?}");
+                    }
+                }
+            }
+
+            self.query_pending.store(false, Ordering::Release);
+
+            // An event could have been raised after the last query, while the GPE handler saw that queries were pending
+            let more_events = self
+                .status()
+                .is_ok_and(|status| status & EC_STATUS_SCI_EVT != 0);
+
+            if !more_events || self.query_pending.swap(true, Ordering::AcqRel) {
+                break;
+            }
+        }
+    }
+}
+
+/// Runs the queries for the EC in `context`
+unsafe extern "C" fn run_queries(context: *mut c_void) {
+    // SAFETY: `context` was created from an `Arc<EcInner>` by `queue_queries`, which gave up its reference
+    let ec = unsafe { Arc::from_raw(context.cast_const().cast::<EcInner>()) };
+
+    ec.run_queries();
+}
+
+/// The handler for the `EmbeddedControl` address space, which splits accesses into single bytes
+struct EcRegionHandler(Arc<EcInner>);
+
+impl EcRegionHandler {
+    /// Gets the addresses of the bytes to access for an access of `width` bits at `address`
+    fn byte_addresses(
+        address: u64,
+        width: u32,
+    ) -> Result<impl Iterator<Item = (u32, u8)>, AcpiError> {
+        if width == 0 || !width.is_multiple_of(8) || width > 64 {
+            return Err(AcpiError::BadParameter);
+        }
+
+        let start = u8::try_from(address).map_err(|_| AcpiError::BadParameter)?;
+        let count = width / 8;
+
+        if u32::from(start) + count > 0x100 {
+            return Err(AcpiError::BadParameter);
+        }
+
+        #[allow(clippy::cast_possible_truncation)] // The last address was checked above
+        Ok((0..count).map(move |i| (i * 8, start + i as u8)))
+    }
+}
+
+impl AddressSpaceHandler for EcRegionHandler {
+    fn read(&self, address: u64, width: u32, _connection: Option<&[u8]>) -> Result<u64, AcpiError> {
+        let mut value = 0;
+
+        for (shift, address) in Self::byte_addresses(address, width)? {
+            value |= u64::from(self.0.read(address)?) << shift;
+        }
+
+        Ok(value)
+    }
+
+    fn write(
+        &self,
+        address: u64,
+        width: u32,
+        value: u64,
+        _connection: Option<&[u8]>,
+    ) -> Result<(), AcpiError> {
+        for (shift, address) in Self::byte_addresses(address, width)? {
+            #[allow(clippy::cast_possible_truncation)]
+            self.0.write(address, (value >> shift) as u8)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An embedded controller, found with [`ecdt_embedded_controller`] or [`find_embedded_controller`].
+///
+/// The EC is installed as the handler for the [`EmbeddedController`] address space, so AML code can access its
+/// registers. Once [`enable_events`] is called, the EC's events are dispatched to its `_Qxx` methods.
+/// The handlers are removed when this struct is dropped.
+///
+/// [`ecdt_embedded_controller`]: AcpicaOperation::ecdt_embedded_controller
+/// [`find_embedded_controller`]: AcpicaOperation::find_embedded_controller
+/// [`EmbeddedController`]: GasAddressSpace::EmbeddedController
+/// [`enable_events`]: EmbeddedController::enable_events
+pub struct EmbeddedController {
+    gpe_handler: Option<GpeHandlerGuard>,
+    _region_handler: AddressSpaceHandlerGuard,
+    inner: Arc<EcInner>,
+    gpe: Option<u32>,
+    ecdt_id: Option<String>,
+}
+
+impl Debug for EmbeddedController {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EmbeddedController")
+            .field("command", &self.inner.command)
+            .field("data", &self.inner.data)
+            .field("gpe", &self.gpe)
+            .field("device", &self.device())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<const E: bool, const I: bool> AcpicaOperation<true, true, E, I> {
+    /// Sets up the embedded controller described by the ECDT, if the system has one.
+    ///
+    /// The region handler is installed at the root of the namespace, so that AML code can access the EC before the
+    /// namespace is initialized, e.g. from `_INI` and `_REG` methods. This should be called before
+    /// [`enable_subsystem`]. Once ACPICA is fully initialized, [`EmbeddedController::enable_events`] should be called.
+    ///
+    /// [`enable_subsystem`]: AcpicaOperation::enable_subsystem
+    ///
+    /// # Errors
+    /// * [`AcpiError::Support`] if the ECDT's registers aren't in system I/O space
+    /// * Any error raised while installing the region handler
+    pub fn ecdt_embedded_controller(&self) -> Result<Option<EmbeddedController>, AcpiError> {
+        let Some(ecdt) = self.ecdt() else {
+            return Ok(None);
+        };
+
+        let inner = EcInner::new(ecdt_port(ecdt.control())?, ecdt_port(ecdt.data())?);
+
+        EmbeddedController::new(
+            self.root(),
+            inner,
+            Some(ecdt.gpe().into()),
+            ecdt.id().map(ToString::to_string),
+        )
+        .map(Some)
+    }
+
+    /// Finds the first present embedded controller device (`PNP0C09`) in the namespace and sets it up.
+    /// The EC's command and data ports are taken from its `_CRS` method, and its GPE from its `_GPE` method.
+    ///
+    /// Like [`ecdt_embedded_controller`], this only needs the namespace to be loaded, so it can be used before
+    /// [`enable_subsystem`] on systems without an ECDT. It shouldn't be used if the EC was already set up by
+    /// [`ecdt_embedded_controller`]. [`EmbeddedController::enable_events`] should be called to handle the EC's events.
+    ///
+    /// [`ecdt_embedded_controller`]: AcpicaOperation::ecdt_embedded_controller
+    /// [`enable_subsystem`]: AcpicaOperation::enable_subsystem
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotExist`] if the device's `_CRS` doesn't contain both ports
+    /// * Any error raised while evaluating `_CRS` or installing the region handler
+    pub fn find_embedded_controller(&self) -> Result<Option<EmbeddedController>, AcpiError> {
+        let device = self.walk_namespace(
+            self.root(),
+            AcpiObjectType::Device,
+            u32::MAX,
+            |device, _| {
+                // Follow the same rules as `scan_present_devices` for devices which aren't present
+                let Ok(status) = device.status() else {
+                    return WalkDecision::SkipChildren;
+                };
+
+                if !status.present() {
+                    return if status.functioning() {
+                        WalkDecision::Continue
+                    } else {
+                        WalkDecision::SkipChildren
+                    };
+                }
+
+                if device
+                    .get_info()
+                    .is_ok_and(|info| info.matches_id(EC_HARDWARE_ID))
+                {
+                    WalkDecision::Stop(device)
+                } else {
+                    WalkDecision::Continue
+                }
+            },
+            |_, _| WalkDecision::Continue,
+        )?;
+
+        let Some(device) = device else {
+            return Ok(None);
+        };
+
+        // The data port comes first in the EC's resources, followed by the command port
+        let mut ports = device
+            .current_resources()?
+            .filter_map(|resource| resource_port(&resource));
+        let (Some(data), Some(command)) = (ports.next(), ports.next()) else {
+            return Err(AcpiError::NotExist);
+        };
+
+        // `_GPE` can also return a package referring to a GPE block device, which isn't supported
+        #[allow(clippy::cast_possible_truncation)]
+        let gpe = device
+            .evaluate_integer("_GPE", &[])
+            .ok()
+            .map(|gpe| gpe as u32);
+
+        let inner = EcInner::new(command, data);
+        inner.set_device(device);
+
+        EmbeddedController::new(device, inner, gpe, None).map(Some)
+    }
+}
+
+impl EmbeddedController {
+    fn new(
+        handle: AcpiHandle,
+        inner: EcInner,
+        gpe: Option<u32>,
+        ecdt_id: Option<String>,
+    ) -> Result<Self, AcpiError> {
+        let inner = Arc::new(inner);
+        let region_handler = handle.install_address_space_handler(
+            GasAddressSpace::EmbeddedController,
+            EcRegionHandler(Arc::clone(&inner)),
+        )?;
+
+        Ok(Self {
+            gpe_handler: None,
+            _region_handler: region_handler,
+            inner,
+            gpe,
+            ecdt_id,
+        })
+    }
+
+    /// Gets the EC's device in the AML namespace.
+    /// For an EC from the ECDT, this is [`None`] until [`enable_events`] is called.
+    ///
+    /// [`enable_events`]: EmbeddedController::enable_events
+    #[must_use]
+    pub fn device(&self) -> Option<AcpiHandle> {
+        *self.inner.device.lock()
+    }
+
+    /// Gets the number of the GPE which the EC raises when it has an event, if it has one
+    #[must_use]
+    pub fn gpe(&self) -> Option<u32> {
+        self.gpe
+    }
+
+    /// Installs a handler for the EC's GPE and enables it, so that the EC's events are dispatched to the `_Qxx`
+    /// methods on its device. The methods are evaluated in a deferred context created by the OS's [`execute`] method.
+    ///
+    /// For an EC from the ECDT, the device is looked up using the path in the ECDT.
+    ///
+    /// [`execute`]: crate::handler::AcpiHandler::execute
+    ///
+    /// # Errors
+    /// * [`AcpiError::AlreadyExists`] if events are already enabled
+    /// * [`AcpiError::NotExist`] if the EC has no GPE or device
+    /// * Any error raised while installing the GPE handler or enabling the GPE
+    pub fn enable_events(
+        &mut self,
+        acpica: &AcpicaOperationFullyInitialized,
+    ) -> Result<(), AcpiError> {
+        if self.gpe_handler.is_some() {
+            return Err(AcpiError::AlreadyExists);
+        }
+
+        let gpe = acpica.gpe(self.gpe.ok_or(AcpiError::NotExist)?);
+
+        if self.device().is_none() {
+            let id = self.ecdt_id.as_deref().ok_or(AcpiError::NotExist)?;
+            self.inner.set_device(acpica.get_handle(id)?);
+        }
+
+        let inner = Arc::clone(&self.inner);
+        let guard = gpe.install_handler(GpeTrigger::Edge, move |_| {
+            inner.queue_queries();
+            GpeHandlerResult::Reenable
+        })?;
+
+        gpe.enable()?;
+        self.gpe_handler = Some(guard);
+
+        // Handle any event which was raised before the GPE was enabled
+        self.inner.queue_queries();
+
+        Ok(())
+    }
+
+    /// Reads the EC register at `address`
+    ///
+    /// # Errors
+    /// * [`AcpiError::Time`] if the EC didn't respond
+    /// * Any error raised while accessing the EC's ports or acquiring the global lock
+    pub fn read(&self, address: u8) -> Result<u8, AcpiError> {
+        self.inner.read(address)
+    }
+
+    /// Writes `value` to the EC register at `address`
+    ///
+    /// # Errors
+    /// * [`AcpiError::Time`] if the EC didn't respond
+    /// * Any error raised while accessing the EC's ports or acquiring the global lock
+    pub fn write(&self, address: u8, value: u8) -> Result<(), AcpiError> {
+        self.inner.write(address, value)
+    }
+
+    /// Reads the number of the EC's next pending event, or [`None`] if there are no pending events.
+    /// The event is removed from the EC, so the OS is responsible for handling it.
+    ///
+    /// # Errors
+    /// * [`AcpiError::Time`] if the EC didn't respond
+    /// * Any error raised while accessing the EC's ports or acquiring the global lock
+    pub fn query(&self) -> Result<Option<u8>, AcpiError> {
+        self.inner.query()
+    }
+}
+
+/// Tests that accesses are split into bytes in little-endian order, and that accesses outside the EC's 256 registers are rejected
+#[test]
+fn test_ec_byte_addresses() {
+    let bytes = EcRegionHandler::byte_addresses(0xFC, 32).unwrap();
+    assert!(bytes.eq([(0, 0xFC), (8, 0xFD), (16, 0xFE), (24, 0xFF)]));
+
+    assert!(EcRegionHandler::byte_addresses(0xFD, 32).is_err());
+    assert!(EcRegionHandler::byte_addresses(0x100, 8).is_err());
+    assert!(EcRegionHandler::byte_addresses(0, 12).is_err());
+}
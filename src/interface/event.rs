@@ -0,0 +1,416 @@
+//! Code to handle fixed ACPI events, such as the power button being pressed, and to observe all events signalled through the SCI
+
+use core::{ffi::c_void, fmt::Debug};
+
+use alloc::boxed::Box;
+
+use crate::{
+    bindings::{
+        consts::{
+            ACPI_EVENT_FLAG_ENABLED, ACPI_EVENT_FLAG_ENABLE_SET, ACPI_EVENT_FLAG_HAS_HANDLER,
+            ACPI_EVENT_FLAG_STATUS_SET, ACPI_EVENT_FLAG_WAKE_ENABLED, ACPI_EVENT_GLOBAL,
+            ACPI_EVENT_PMTIMER, ACPI_EVENT_POWER_BUTTON, ACPI_EVENT_RTC, ACPI_EVENT_SLEEP_BUTTON,
+            ACPI_EVENT_TYPE_FIXED, ACPI_EVENT_TYPE_GPE, ACPI_INTERRUPT_HANDLED,
+            ACPI_INTERRUPT_NOT_HANDLED,
+        },
+        functions::{
+            AcpiClearEvent, AcpiDisableEvent, AcpiEnableEvent, AcpiGetEventStatus,
+            AcpiInstallFixedEventHandler, AcpiInstallGlobalEventHandler, AcpiInstallSciHandler,
+            AcpiOsWaitEventsComplete, AcpiRemoveFixedEventHandler, AcpiRemoveSciHandler,
+        },
+        types::{
+            functions::{FfiAcpiEventHandler, FfiAcpiSciHandler},
+            FfiAcpiEventStatus, FfiAcpiHandle,
+        },
+    },
+    gpe::Gpe,
+    status::AcpiError,
+    types::AcpiInterruptHandledStatus,
+    AcpicaOperation,
+};
+
+/// An event signalled through the fixed hardware registers described by the FADT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixedEvent {
+    /// The PM timer's top bit has changed
+    PmTimer,
+    /// The power button has been pressed
+    PowerButton,
+    /// The sleep button has been pressed
+    SleepButton,
+    /// The real time clock's alarm has fired
+    Rtc,
+    /// The firmware has released the global lock. ACPICA handles this event itself.
+    GlobalLock,
+}
+
+impl FixedEvent {
+    pub(crate) fn from_u32(event: u32) -> Option<Self> {
+        match event {
+            ACPI_EVENT_PMTIMER => Some(Self::PmTimer),
+            ACPI_EVENT_POWER_BUTTON => Some(Self::PowerButton),
+            ACPI_EVENT_SLEEP_BUTTON => Some(Self::SleepButton),
+            ACPI_EVENT_RTC => Some(Self::Rtc),
+            ACPI_EVENT_GLOBAL => Some(Self::GlobalLock),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_u32(self) -> u32 {
+        match self {
+            Self::PmTimer => ACPI_EVENT_PMTIMER,
+            Self::PowerButton => ACPI_EVENT_POWER_BUTTON,
+            Self::SleepButton => ACPI_EVENT_SLEEP_BUTTON,
+            Self::Rtc => ACPI_EVENT_RTC,
+            Self::GlobalLock => ACPI_EVENT_GLOBAL,
+        }
+    }
+}
+
+/// The state of a fixed event, returned by [`fixed_event_status`]
+///
+/// [`fixed_event_status`]: AcpicaOperation::fixed_event_status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct FixedEventStatus {
+    /// Whether the event is enabled
+    pub enabled: bool,
+    /// Whether the event is enabled to wake the system
+    pub wake_enabled: bool,
+    /// Whether the event's status bit is set in hardware, meaning the event has fired
+    pub status_set: bool,
+    /// Whether the event's enable bit is set in hardware
+    pub enable_set: bool,
+    /// Whether the event has a handler installed
+    pub has_handler: bool,
+}
+
+impl FixedEventStatus {
+    fn from_ffi(status: FfiAcpiEventStatus) -> Self {
+        Self {
+            enabled: status & ACPI_EVENT_FLAG_ENABLED != 0,
+            wake_enabled: status & ACPI_EVENT_FLAG_WAKE_ENABLED != 0,
+            status_set: status & ACPI_EVENT_FLAG_STATUS_SET != 0,
+            enable_set: status & ACPI_EVENT_FLAG_ENABLE_SET != 0,
+            has_handler: status & ACPI_EVENT_FLAG_HAS_HANDLER != 0,
+        }
+    }
+}
+
+impl AcpicaOperation<true, true, true, true> {
+    /// Installs a handler for a fixed event, and enables the event.
+    ///
+    /// The handler is called in interrupt context, after ACPICA has cleared the event's status bit,
+    /// so it should only do as much work as is needed to schedule the event's processing.
+    ///
+    /// The event is disabled and the handler removed when the returned guard is dropped.
+    ///
+    /// ```ignore
+    /// let _guard = acpica.install_fixed_event_handler(FixedEvent::PowerButton, || {
+    ///     // Wake up a kernel thread which shuts down the system
+    ///     schedule_shutdown();
+    /// })?;
+    /// ```
+    ///
+    /// # Errors
+    /// * [`AcpiError::AlreadyExists`] if the event already has a handler, which is always the case for [`FixedEvent::GlobalLock`]
+    /// * Any error raised by ACPICA while enabling the event
+    pub fn install_fixed_event_handler<F>(
+        &self,
+        event: FixedEvent,
+        handler: F,
+    ) -> Result<FixedEventHandlerGuard, AcpiError>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let context = Box::into_raw(Box::new(handler)).cast::<c_void>();
+
+        // SAFETY: `context` is valid for the type expected by the handler, and stays valid until the guard is dropped
+        let r = unsafe {
+            AcpiInstallFixedEventHandler(event.to_u32(), call_fixed_event_handler::<F>, context)
+        };
+
+        if let Err(e) = r.as_result() {
+            // SAFETY: The handler wasn't installed, so nothing else has access to `context`
+            unsafe { drop_event_handler::<F>(context) };
+            return Err(e);
+        }
+
+        Ok(FixedEventHandlerGuard {
+            event,
+            handler: call_fixed_event_handler::<F>,
+            context,
+            drop_context: drop_event_handler::<F>,
+        })
+    }
+
+    /// Sets the enable bit of a fixed event, so that it raises an SCI when it fires.
+    ///
+    /// # Errors
+    /// * [`AcpiError::NoHardwareResponse`] if the enable bit couldn't be set
+    pub fn enable_fixed_event(&self, event: FixedEvent) -> Result<(), AcpiError> {
+        // SAFETY: ACPICA is fully initialized
+        unsafe { AcpiEnableEvent(event.to_u32(), 0).as_result() }
+    }
+
+    /// Clears the enable bit of a fixed event
+    ///
+    /// # Errors
+    /// * [`AcpiError::NoHardwareResponse`] if the enable bit couldn't be cleared
+    pub fn disable_fixed_event(&self, event: FixedEvent) -> Result<(), AcpiError> {
+        // SAFETY: ACPICA is fully initialized
+        unsafe { AcpiDisableEvent(event.to_u32(), 0).as_result() }
+    }
+
+    /// Clears the status bit of a fixed event
+    ///
+    /// # Errors
+    /// Any error raised by ACPICA while writing to the status register
+    pub fn clear_fixed_event(&self, event: FixedEvent) -> Result<(), AcpiError> {
+        // SAFETY: ACPICA is fully initialized
+        unsafe { AcpiClearEvent(event.to_u32()).as_result() }
+    }
+
+    /// Gets the software and hardware state of a fixed event
+    ///
+    /// # Errors
+    /// Any error raised by ACPICA while reading the status and enable registers
+    pub fn fixed_event_status(&self, event: FixedEvent) -> Result<FixedEventStatus, AcpiError> {
+        let mut status = 0;
+
+        // SAFETY: ACPICA is fully initialized
+        unsafe {
+            AcpiGetEventStatus(event.to_u32(), core::ptr::addr_of_mut!(status)).as_result()?;
+        };
+
+        Ok(FixedEventStatus::from_ffi(status))
+    }
+
+    /// Installs a handler which is called for every GPE and fixed event, before the event is dispatched.
+    /// This is intended for tracing and statistics, and can't change how the event is handled.
+    ///
+    /// The handler is called in interrupt context. ACPICA only supports one global event handler, and has no way to
+    /// remove it, so it stays installed until ACPICA is terminated.
+    ///
+    /// # Errors
+    /// * [`AcpiError::AlreadyExists`] if a global event handler has already been installed
+    pub fn install_global_event_handler<F>(&self, handler: F) -> Result<(), AcpiError>
+    where
+        F: Fn(AcpiEvent) + Send + Sync + 'static,
+    {
+        let context = Box::into_raw(Box::new(handler)).cast::<c_void>();
+
+        // SAFETY: `context` is valid for the type expected by the handler, and is never freed once the handler is installed
+        let r = unsafe { AcpiInstallGlobalEventHandler(call_global_event_handler::<F>, context) };
+
+        if let Err(e) = r.as_result() {
+            // SAFETY: The handler wasn't installed, so nothing else has access to `context`
+            unsafe { drop_event_handler::<F>(context) };
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Installs a handler which is called every time the SCI fires, after ACPICA has dispatched any GPEs and fixed events.
+    /// This allows the OS to chain its own processing onto the SCI handler installed through [`install_interrupt_handler`].
+    ///
+    /// The handler is called in interrupt context. Its return value is combined with those of ACPICA's own handlers
+    /// to determine whether the interrupt was handled.
+    ///
+    /// The handler is removed when the returned guard is dropped.
+    ///
+    /// [`install_interrupt_handler`]: crate::handler::AcpiHandler::install_interrupt_handler
+    ///
+    /// # Errors
+    /// * [`AcpiError::AlreadyExists`] if another SCI handler using the same closure type is already installed,
+    ///   as ACPICA identifies SCI handlers by their function pointer
+    pub fn install_sci_handler<F>(&self, handler: F) -> Result<SciHandlerGuard, AcpiError>
+    where
+        F: Fn() -> AcpiInterruptHandledStatus + Send + Sync + 'static,
+    {
+        let context = Box::into_raw(Box::new(handler)).cast::<c_void>();
+
+        // SAFETY: `context` is valid for the type expected by the handler, and stays valid until the guard is dropped
+        let r = unsafe { AcpiInstallSciHandler(call_sci_handler::<F>, context) };
+
+        if let Err(e) = r.as_result() {
+            // SAFETY: The handler wasn't installed, so nothing else has access to `context`
+            unsafe { drop_event_handler::<F>(context) };
+            return Err(e);
+        }
+
+        Ok(SciHandlerGuard {
+            handler: call_sci_handler::<F>,
+            context,
+            drop_context: drop_event_handler::<F>,
+        })
+    }
+}
+
+/// A fixed event handler installed by [`install_fixed_event_handler`].
+/// The event is disabled and the handler removed when this struct is dropped.
+///
+/// [`install_fixed_event_handler`]: AcpicaOperation::install_fixed_event_handler
+#[must_use = "The handler is removed when the guard is dropped"]
+pub struct FixedEventHandlerGuard {
+    event: FixedEvent,
+    handler: FfiAcpiEventHandler,
+    context: *mut c_void,
+    drop_context: unsafe fn(*mut c_void),
+}
+
+// SAFETY: The closure in `context` is `Send + Sync`, and ACPICA can remove the handler from any thread
+unsafe impl Send for FixedEventHandlerGuard {}
+// SAFETY: The guard has no methods which use the closure
+unsafe impl Sync for FixedEventHandlerGuard {}
+
+impl Debug for FixedEventHandlerGuard {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FixedEventHandlerGuard")
+            .field("event", &self.event)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for FixedEventHandlerGuard {
+    fn drop(&mut self) {
+        // SAFETY: The handler was installed with these arguments
+        let r = unsafe { AcpiRemoveFixedEventHandler(self.event.to_u32(), self.handler) };
+
+        // If the handler couldn't be removed, ACPICA could still call it, so the closure is leaked
+        if r.as_result().is_ok() {
+            // ACPICA clears the handler without synchronizing with the SCI handler, which reads it without a lock,
+            // so another CPU could still be running the closure. Unlike `AcpiRemoveGpeHandler`,
+            // `AcpiRemoveFixedEventHandler` doesn't wait for it.
+            // SAFETY: Waiting for events has no preconditions
+            unsafe { AcpiOsWaitEventsComplete() };
+
+            // SAFETY: The handler has been removed, and any call to it which was already running has finished
+            unsafe { (self.drop_context)(self.context) };
+        }
+    }
+}
+
+/// Calls the closure in `context`
+unsafe extern "C" fn call_fixed_event_handler<F>(context: *mut c_void) -> u32
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    // The SCI handler reads the handler and its context separately, so it can see the context after it's been cleared
+    if context.is_null() {
+        return ACPI_INTERRUPT_NOT_HANDLED;
+    }
+
+    // SAFETY: `context` was created from a `Box<F>` by `install_fixed_event_handler`. It isn't freed until the handler
+    // has been removed and in-flight calls have finished.
+    let handler = unsafe { &*context.cast::<F>() };
+
+    handler();
+
+    ACPI_INTERRUPT_HANDLED
+}
+
+/// Frees the closure in `context`
+///
+/// # Safety
+/// `context` must have been created from a `Box<F>`, and must not be used afterwards
+unsafe fn drop_event_handler<F>(context: *mut c_void) {
+    // SAFETY: `context` came from a `Box<F>`
+    drop(unsafe { Box::from_raw(context.cast::<F>()) });
+}
+
+/// An event passed to the handler installed by [`install_global_event_handler`]
+///
+/// [`install_global_event_handler`]: AcpicaOperation::install_global_event_handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcpiEvent {
+    /// The type of event and which event it is
+    pub kind: AcpiEventKind,
+}
+
+/// The type of an [`AcpiEvent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcpiEventKind {
+    /// A General Purpose Event. GPEs in the FADT's GPE blocks have no [`block_device`], the same as those from
+    /// [`AcpicaOperation::gpe`], so they can be enabled, cleared and finished as usual.
+    ///
+    /// [`block_device`]: Gpe::block_device
+    Gpe(Gpe),
+    /// A fixed event
+    Fixed(FixedEvent),
+}
+
+/// An SCI handler installed by [`install_sci_handler`]. The handler is removed when this struct is dropped.
+///
+/// [`install_sci_handler`]: AcpicaOperation::install_sci_handler
+#[must_use = "The handler is removed when the guard is dropped"]
+pub struct SciHandlerGuard {
+    handler: FfiAcpiSciHandler,
+    context: *mut c_void,
+    drop_context: unsafe fn(*mut c_void),
+}
+
+// SAFETY: The closure in `context` is `Send + Sync`, and ACPICA can remove the handler from any thread
+unsafe impl Send for SciHandlerGuard {}
+// SAFETY: The guard has no methods which use the closure
+unsafe impl Sync for SciHandlerGuard {}
+
+impl Debug for SciHandlerGuard {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SciHandlerGuard").finish_non_exhaustive()
+    }
+}
+
+impl Drop for SciHandlerGuard {
+    fn drop(&mut self) {
+        // SAFETY: The handler was installed with this function pointer
+        let r = unsafe { AcpiRemoveSciHandler(self.handler) };
+
+        // If the handler couldn't be removed, ACPICA could still call it, so the closure is leaked
+        if r.as_result().is_ok() {
+            // SAFETY: ACPICA removes the handler while holding the GPE lock, which is also held while SCI handlers
+            // are called, so nothing else is using the closure
+            unsafe { (self.drop_context)(self.context) };
+        }
+    }
+}
+
+/// Calls the closure in `context` with the decoded event
+unsafe extern "C" fn call_global_event_handler<F>(
+    event_type: u32,
+    device: FfiAcpiHandle,
+    event_number: u32,
+    context: *mut c_void,
+) where
+    F: Fn(AcpiEvent) + Send + Sync + 'static,
+{
+    // SAFETY: `context` was created from a `Box<F>` by `install_global_event_handler`, and is never freed
+    let handler = unsafe { &*context.cast::<F>() };
+
+    let kind = match event_type {
+        // ACPICA passes the `\_GPE` scope as the device for the FADT's GPE blocks, which `from_ffi` maps to null
+        ACPI_EVENT_TYPE_GPE => AcpiEventKind::Gpe(Gpe::from_ffi(device, event_number)),
+        ACPI_EVENT_TYPE_FIXED => match FixedEvent::from_u32(event_number) {
+            Some(event) => AcpiEventKind::Fixed(event),
+            None => return,
+        },
+        _ => return,
+    };
+
+    handler(AcpiEvent { kind });
+}
+
+/// Calls the closure in `context`, and converts its return value for ACPICA
+unsafe extern "C" fn call_sci_handler<F>(context: *mut c_void) -> u32
+where
+    F: Fn() -> AcpiInterruptHandledStatus + Send + Sync + 'static,
+{
+    // SAFETY: `context` was created from a `Box<F>` by `install_sci_handler`, and isn't freed until the handler is removed
+    let handler = unsafe { &*context.cast::<F>() };
+
+    match handler() {
+        AcpiInterruptHandledStatus::Handled => ACPI_INTERRUPT_HANDLED,
+        AcpiInterruptHandledStatus::NotHandled => ACPI_INTERRUPT_NOT_HANDLED,
+    }
+}
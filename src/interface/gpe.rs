@@ -0,0 +1,473 @@
+//! Code to manage General Purpose Events (GPEs)
+
+use core::{ffi::c_void, fmt::Debug, ptr::addr_of};
+
+use alloc::boxed::Box;
+
+use crate::{
+    bindings::{
+        consts::{
+            ACPI_EVENT_FLAG_ENABLED, ACPI_EVENT_FLAG_ENABLE_SET, ACPI_EVENT_FLAG_HAS_HANDLER,
+            ACPI_EVENT_FLAG_MASKED, ACPI_EVENT_FLAG_STATUS_SET, ACPI_EVENT_FLAG_WAKE_ENABLED,
+            ACPI_GPE_DISABLE, ACPI_GPE_EDGE_TRIGGERED, ACPI_GPE_ENABLE, ACPI_GPE_LEVEL_TRIGGERED,
+            ACPI_INTERRUPT_HANDLED, ACPI_INTERRUPT_NOT_HANDLED, ACPI_REENABLE_GPE,
+        },
+        functions::{
+            AcpiClearGpe, AcpiDisableGpe, AcpiEnableGpe, AcpiFinishGpe, AcpiGetGpeStatus,
+            AcpiInstallGpeHandler, AcpiInstallGpeRawHandler, AcpiMaskGpe, AcpiRemoveGpeHandler,
+            AcpiSetGpe, AcpiUpdateAllGpes,
+        },
+        statics::AcpiGbl_FadtGpeDevice,
+        types::{functions::FfiAcpiGpeHandler, FfiAcpiEventStatus, FfiAcpiHandle},
+    },
+    devices::AcpiHandle,
+    status::AcpiError,
+    types::AcpiInterruptHandledStatus,
+    AcpicaOperation,
+};
+
+/// A General Purpose Event, identified by its number and the GPE block it is in.
+///
+/// A [`Gpe`] can be obtained from [`AcpicaOperation::gpe`] for GPEs in the FADT's GPE blocks,
+/// or [`AcpicaOperation::block_gpe`] for GPEs in a GPE block device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gpe {
+    /// The GPE block device, or null for the FADT's GPE blocks
+    device: FfiAcpiHandle,
+    number: u32,
+}
+
+/// How a GPE is triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpeTrigger {
+    /// The GPE's status bit is cleared by ACPICA before the handler is called
+    Edge,
+    /// The GPE's status bit is cleared by ACPICA after the handler finishes, when the GPE is re-enabled
+    Level,
+}
+
+impl GpeTrigger {
+    fn to_u32(self) -> u32 {
+        match self {
+            Self::Edge => ACPI_GPE_EDGE_TRIGGERED,
+            Self::Level => ACPI_GPE_LEVEL_TRIGGERED,
+        }
+    }
+}
+
+/// What ACPICA should do after a GPE handler returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpeHandlerResult {
+    /// ACPICA should clear the GPE if it is level triggered, and re-enable it
+    Reenable,
+    /// The GPE should be left disabled. The handler's driver must call [`Gpe::finish`] once it has
+    /// finished processing the event, e.g. from a deferred context.
+    LeaveDisabled,
+}
+
+/// The state of a GPE, returned by [`Gpe::status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct GpeStatus {
+    /// Whether the GPE is enabled for runtime events
+    pub enabled: bool,
+    /// Whether the GPE is enabled to wake the system
+    pub wake_enabled: bool,
+    /// Whether the GPE's status bit is set in hardware, meaning the event has fired
+    pub status_set: bool,
+    /// Whether the GPE's enable bit is set in hardware
+    pub enable_set: bool,
+    /// Whether the GPE has a handler or method to dispatch to
+    pub has_handler: bool,
+    /// Whether the GPE is masked
+    pub masked: bool,
+}
+
+impl GpeStatus {
+    fn from_ffi(status: FfiAcpiEventStatus) -> Self {
+        Self {
+            enabled: status & ACPI_EVENT_FLAG_ENABLED != 0,
+            wake_enabled: status & ACPI_EVENT_FLAG_WAKE_ENABLED != 0,
+            status_set: status & ACPI_EVENT_FLAG_STATUS_SET != 0,
+            enable_set: status & ACPI_EVENT_FLAG_ENABLE_SET != 0,
+            has_handler: status & ACPI_EVENT_FLAG_HAS_HANDLER != 0,
+            masked: status & ACPI_EVENT_FLAG_MASKED != 0,
+        }
+    }
+}
+
+impl AcpicaOperation<true, true, true, true> {
+    /// Gets the GPE with the given number in the GPE blocks described by the FADT
+    #[must_use]
+    pub fn gpe(&self, number: u32) -> Gpe {
+        Gpe {
+            device: core::ptr::null_mut(),
+            number,
+        }
+    }
+
+    /// Gets the GPE with the given number in the GPE block device `device`
+    #[must_use]
+    pub fn block_gpe(&self, device: AcpiHandle, number: u32) -> Gpe {
+        Gpe {
+            device: device.0,
+            number,
+        }
+    }
+
+    /// Calls the ACPICA function `AcpiUpdateAllGpes`.
+    ///
+    /// This enables all GPEs which have an `_Lxx` or `_Exx` method and aren't used to wake the system.
+    /// It should be called once all devices' `_PRW` methods have been evaluated to find wake GPEs.
+    ///
+    /// # Errors
+    /// Any error raised by ACPICA while enabling the GPEs
+    pub fn update_all_gpes(&self) -> Result<(), AcpiError> {
+        // SAFETY: ACPICA is fully initialized
+        unsafe { AcpiUpdateAllGpes().as_result() }
+    }
+}
+
+impl Gpe {
+    /// Converts the device and number which ACPICA passes to GPE handlers into a [`Gpe`].
+    ///
+    /// For the FADT's GPE blocks, ACPICA passes the `\_GPE` scope as the device, but functions such as
+    /// `AcpiEnableGpe` only accept null for those blocks, so it's mapped to null.
+    pub(crate) fn from_ffi(device: FfiAcpiHandle, number: u32) -> Self {
+        // SAFETY: ACPICA sets this when the FADT's GPE blocks are initialized, before any GPE handler can be called,
+        // and never changes it afterwards
+        let fadt_gpe_device = unsafe { addr_of!(AcpiGbl_FadtGpeDevice).read() };
+
+        Self::from_ffi_with_fadt_device(device, number, fadt_gpe_device)
+    }
+
+    fn from_ffi_with_fadt_device(
+        device: FfiAcpiHandle,
+        number: u32,
+        fadt_gpe_device: FfiAcpiHandle,
+    ) -> Self {
+        if device == fadt_gpe_device {
+            Self {
+                device: core::ptr::null_mut(),
+                number,
+            }
+        } else {
+            Self { device, number }
+        }
+    }
+
+    /// Gets the number of the GPE within its block
+    #[must_use]
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// Gets the GPE block device which the GPE is in, or [`None`] if it is in one of the FADT's GPE blocks
+    #[must_use]
+    pub fn block_device(&self) -> Option<AcpiHandle> {
+        if self.device.is_null() {
+            None
+        } else {
+            Some(AcpiHandle(self.device))
+        }
+    }
+
+    /// Adds a reference to the GPE, enabling it if it wasn't already enabled.
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if the GPE doesn't exist
+    /// * [`AcpiError::NoHandler`] if the GPE has no handler or method to dispatch to
+    pub fn enable(&self) -> Result<(), AcpiError> {
+        // SAFETY: A `Gpe` is only created once ACPICA is initialized
+        unsafe { AcpiEnableGpe(self.device, self.number).as_result() }
+    }
+
+    /// Removes a reference to the GPE, disabling it if there are no other references.
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if the GPE doesn't exist
+    /// * [`AcpiError::Limit`] if the GPE isn't enabled
+    pub fn disable(&self) -> Result<(), AcpiError> {
+        // SAFETY: A `Gpe` is only created once ACPICA is initialized
+        unsafe { AcpiDisableGpe(self.device, self.number).as_result() }
+    }
+
+    /// Sets or clears the GPE's enable bit in hardware, without changing its reference count.
+    ///
+    /// This is intended for drivers which need to temporarily stop a GPE from firing.
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if the GPE doesn't exist
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), AcpiError> {
+        let action = if enabled {
+            ACPI_GPE_ENABLE
+        } else {
+            ACPI_GPE_DISABLE
+        };
+
+        // The action constants are 0 and 1
+        #[allow(clippy::cast_possible_truncation)]
+        // SAFETY: A `Gpe` is only created once ACPICA is initialized
+        unsafe {
+            AcpiSetGpe(self.device, self.number, action as u8).as_result()
+        }
+    }
+
+    /// Masks or unmasks the GPE. A masked GPE isn't dispatched even if it is enabled,
+    /// which can be used to stop a misbehaving GPE from flooding the system.
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if the GPE doesn't exist
+    pub fn set_masked(&self, masked: bool) -> Result<(), AcpiError> {
+        // SAFETY: A `Gpe` is only created once ACPICA is initialized
+        unsafe { AcpiMaskGpe(self.device, self.number, masked).as_result() }
+    }
+
+    /// Clears the GPE's status bit in hardware
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if the GPE doesn't exist
+    pub fn clear(&self) -> Result<(), AcpiError> {
+        // SAFETY: A `Gpe` is only created once ACPICA is initialized
+        unsafe { AcpiClearGpe(self.device, self.number).as_result() }
+    }
+
+    /// Clears the GPE if it is level triggered and re-enables it.
+    /// This should be called after a handler returned [`GpeHandlerResult::LeaveDisabled`] and the event has been processed.
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if the GPE doesn't exist
+    pub fn finish(&self) -> Result<(), AcpiError> {
+        // SAFETY: A `Gpe` is only created once ACPICA is initialized
+        unsafe { AcpiFinishGpe(self.device, self.number).as_result() }
+    }
+
+    /// Gets the software and hardware state of the GPE
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if the GPE doesn't exist
+    pub fn status(&self) -> Result<GpeStatus, AcpiError> {
+        let mut status = 0;
+
+        // SAFETY: A `Gpe` is only created once ACPICA is initialized
+        unsafe {
+            AcpiGetGpeStatus(self.device, self.number, core::ptr::addr_of_mut!(status))
+                .as_result()?;
+        };
+
+        Ok(GpeStatus::from_ffi(status))
+    }
+
+    /// Installs a handler for the GPE. This replaces any `_Lxx` or `_Exx` method for the GPE.
+    ///
+    /// The handler is called in interrupt context, after ACPICA has disabled the GPE and, if it is edge triggered,
+    /// cleared it. If the handler returns [`GpeHandlerResult::Reenable`], ACPICA re-enables the GPE.
+    /// The GPE still needs to be enabled with [`enable`] before the handler is called.
+    ///
+    /// The handler is removed when the returned guard is dropped.
+    ///
+    /// [`enable`]: Gpe::enable
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if the GPE doesn't exist
+    /// * [`AcpiError::AlreadyExists`] if the GPE already has a handler
+    pub fn install_handler<F>(
+        &self,
+        trigger: GpeTrigger,
+        handler: F,
+    ) -> Result<GpeHandlerGuard, AcpiError>
+    where
+        F: Fn(Gpe) -> GpeHandlerResult + Send + Sync + 'static,
+    {
+        let context = Box::into_raw(Box::new(handler)).cast::<c_void>();
+
+        // SAFETY: `context` is valid for the type expected by the handler, and stays valid until the guard is dropped
+        let r = unsafe {
+            AcpiInstallGpeHandler(
+                self.device,
+                self.number,
+                trigger.to_u32(),
+                call_gpe_handler::<F>,
+                context,
+            )
+        };
+
+        self.guard(
+            r.as_result(),
+            call_gpe_handler::<F>,
+            context,
+            drop_gpe_handler::<F>,
+        )
+    }
+
+    /// Installs a raw handler for the GPE. This replaces any `_Lxx` or `_Exx` method for the GPE.
+    ///
+    /// Unlike [`install_handler`], ACPICA doesn't disable, clear or re-enable the GPE around the handler,
+    /// so the handler is responsible for managing the GPE itself, e.g. by calling [`clear`] and [`finish`].
+    /// The handler is called in interrupt context.
+    ///
+    /// The handler is removed when the returned guard is dropped.
+    ///
+    /// [`install_handler`]: Gpe::install_handler
+    /// [`clear`]: Gpe::clear
+    /// [`finish`]: Gpe::finish
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if the GPE doesn't exist
+    /// * [`AcpiError::AlreadyExists`] if the GPE already has a handler
+    pub fn install_raw_handler<F>(
+        &self,
+        trigger: GpeTrigger,
+        handler: F,
+    ) -> Result<GpeHandlerGuard, AcpiError>
+    where
+        F: Fn(Gpe) -> AcpiInterruptHandledStatus + Send + Sync + 'static,
+    {
+        let context = Box::into_raw(Box::new(handler)).cast::<c_void>();
+
+        // SAFETY: `context` is valid for the type expected by the handler, and stays valid until the guard is dropped
+        let r = unsafe {
+            AcpiInstallGpeRawHandler(
+                self.device,
+                self.number,
+                trigger.to_u32(),
+                call_raw_gpe_handler::<F>,
+                context,
+            )
+        };
+
+        self.guard(
+            r.as_result(),
+            call_raw_gpe_handler::<F>,
+            context,
+            drop_gpe_handler::<F>,
+        )
+    }
+
+    /// Creates a guard for an installed handler, or frees the closure if installation failed
+    fn guard(
+        &self,
+        result: Result<(), AcpiError>,
+        handler: FfiAcpiGpeHandler,
+        context: *mut c_void,
+        drop_context: unsafe fn(*mut c_void),
+    ) -> Result<GpeHandlerGuard, AcpiError> {
+        if let Err(e) = result {
+            // SAFETY: The handler wasn't installed, so nothing else has access to `context`
+            unsafe { drop_context(context) };
+            return Err(e);
+        }
+
+        Ok(GpeHandlerGuard {
+            gpe: *self,
+            handler,
+            context,
+            drop_context,
+        })
+    }
+}
+
+/// A GPE handler installed by [`install_handler`] or [`install_raw_handler`].
+/// The handler is removed when this struct is dropped, and the GPE's `_Lxx` or `_Exx` method is restored if it has one.
+///
+/// [`install_handler`]: Gpe::install_handler
+/// [`install_raw_handler`]: Gpe::install_raw_handler
+#[must_use = "The handler is removed when the guard is dropped"]
+pub struct GpeHandlerGuard {
+    gpe: Gpe,
+    handler: FfiAcpiGpeHandler,
+    context: *mut c_void,
+    drop_context: unsafe fn(*mut c_void),
+}
+
+// SAFETY: The closure in `context` is `Send + Sync`, and ACPICA can remove the handler from any thread
+unsafe impl Send for GpeHandlerGuard {}
+// SAFETY: The guard has no methods which use the closure
+unsafe impl Sync for GpeHandlerGuard {}
+
+impl Debug for GpeHandlerGuard {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GpeHandlerGuard")
+            .field("gpe", &self.gpe)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for GpeHandlerGuard {
+    fn drop(&mut self) {
+        // SAFETY: The handler was installed with these arguments
+        let r = unsafe { AcpiRemoveGpeHandler(self.gpe.device, self.gpe.number, self.handler) };
+
+        // If the handler couldn't be removed, ACPICA could still call it, so the closure is leaked
+        if r.as_result().is_ok() {
+            // SAFETY: The handler has been removed, so ACPICA won't start another call to it. Raw handlers are
+            // called without the GPE lock held, so a call could still be in progress when the handler is unlinked,
+            // but `AcpiRemoveGpeHandler` calls `AcpiOsWaitEventsComplete` before returning, which waits for it.
+            unsafe { (self.drop_context)(self.context) };
+        }
+    }
+}
+
+/// Calls the closure in `context`, and converts its return value for ACPICA
+unsafe extern "C" fn call_gpe_handler<F>(
+    device: FfiAcpiHandle,
+    number: u32,
+    context: *mut c_void,
+) -> u32
+where
+    F: Fn(Gpe) -> GpeHandlerResult + Send + Sync + 'static,
+{
+    // SAFETY: `context` was created from a `Box<F>` by `install_handler`, and isn't freed until the handler is removed
+    let handler = unsafe { &*context.cast::<F>() };
+
+    match handler(Gpe::from_ffi(device, number)) {
+        GpeHandlerResult::Reenable => ACPI_INTERRUPT_HANDLED | ACPI_REENABLE_GPE,
+        GpeHandlerResult::LeaveDisabled => ACPI_INTERRUPT_HANDLED,
+    }
+}
+
+/// Calls the closure in `context`, and converts its return value for ACPICA
+unsafe extern "C" fn call_raw_gpe_handler<F>(
+    device: FfiAcpiHandle,
+    number: u32,
+    context: *mut c_void,
+) -> u32
+where
+    F: Fn(Gpe) -> AcpiInterruptHandledStatus + Send + Sync + 'static,
+{
+    // SAFETY: `context` was created from a `Box<F>` by `install_raw_handler`, and isn't freed until the handler is removed
+    let handler = unsafe { &*context.cast::<F>() };
+
+    match handler(Gpe::from_ffi(device, number)) {
+        AcpiInterruptHandledStatus::Handled => ACPI_INTERRUPT_HANDLED,
+        AcpiInterruptHandledStatus::NotHandled => ACPI_INTERRUPT_NOT_HANDLED,
+    }
+}
+
+/// Frees the closure in `context`
+///
+/// # Safety
+/// `context` must have been created from a `Box<F>`, and must not be used afterwards
+unsafe fn drop_gpe_handler<F>(context: *mut c_void) {
+    // SAFETY: `context` came from a `Box<F>`
+    drop(unsafe { Box::from_raw(context.cast::<F>()) });
+}
+
+/// Tests that the `\_GPE` scope passed for the FADT's GPE blocks is mapped to null
+#[test]
+fn test_gpe_from_ffi() {
+    let scope = 0_u8;
+    let block = 0_u8;
+    let scope: FfiAcpiHandle = addr_of!(scope).cast_mut().cast();
+    let block: FfiAcpiHandle = addr_of!(block).cast_mut().cast();
+
+    let fadt_gpe = Gpe::from_ffi_with_fadt_device(scope, 0x16, scope);
+    assert!(fadt_gpe.block_device().is_none());
+    assert_eq!(fadt_gpe.number(), 0x16);
+
+    let block_gpe = Gpe::from_ffi_with_fadt_device(block, 3, scope);
+    assert_eq!(block_gpe.block_device(), Some(AcpiHandle(block)));
+
+    let null_gpe = Gpe::from_ffi_with_fadt_device(core::ptr::null_mut(), 1, scope);
+    assert!(null_gpe.block_device().is_none());
+}
@@ -55,12 +55,17 @@ extern "C" fn acpi_os_read_pci_configuration(
 
 #[export_name = "AcpiOsWritePciConfiguration"]
 extern "C" fn acpi_os_write_pci_configuration(
-    pci_id: FfiAcpiPciId,
+    pci_id: *const FfiAcpiPciId,
     reg: u32,
     value: u64,
     width: u32,
 ) -> AcpiStatus {
-    let pci_id = AcpiPciId::from_ffi(pci_id);
+    if pci_id.is_null() {
+        return AcpiError::BadParameter.to_acpi_status();
+    }
+
+    // SAFETY: `pci_id` is non-null so it is valid
+    let pci_id = unsafe { AcpiPciId::from_ffi(pci_id.read()) };
 
     let mut interface = OS_INTERFACE.lock();
     let interface = interface.as_mut().unwrap();
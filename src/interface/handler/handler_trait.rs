@@ -367,9 +367,13 @@ pub unsafe trait AcpiHandler {
     /// * This method is only called from `AcpiOsWaitEventsComplete`
     ///
     /// # Implementation Safety
-    /// * This method must not return until all
+    /// * This method must not return until all tasks run with [`execute`] have completed, and until every
+    ///   interrupt handler installed with [`install_interrupt_handler`] which was running when it was called
+    ///   has returned. ACPICA calls raw GPE handlers from the SCI handler without holding any lock, and relies
+    ///   on this method to know that a removed handler is no longer running before its context is freed.
     ///
     /// [`execute`]: AcpiHandler::execute
+    /// [`install_interrupt_handler`]: AcpiHandler::install_interrupt_handler
     unsafe fn wait_for_events(&mut self);
 
     /// Sleep the current kernel thread for the given number of milliseconds
@@ -451,10 +455,10 @@ pub unsafe trait AcpiHandler {
     /// This method allows the OS to do any final processing before entering the new state.
     /// The default implementation is a no-op.
     ///
+    /// `reg_a` and `reg_b` are the values which ACPICA is about to write to the `PM1a` and `PM1b` control registers.
+    ///
     /// # Safety
     /// * This method is only called from `AcpiOsEnterSleep`
-    ///
-    // TODO: Figure out what reg_a and reg_b are and add docs
     #[allow(unused_variables)]
     unsafe fn enter_sleep(&mut self, state: u8, reg_a: u32, reg_b: u32) -> Result<(), AcpiError> {
         Ok(())
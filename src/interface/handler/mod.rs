@@ -8,9 +8,7 @@ pub use handler_trait::AcpiHandler;
 use alloc::ffi::CString;
 
 use crate::{
-    bindings::functions::{
-        AcpiDebugTrace, AcpiEnterSleepState, AcpiEnterSleepStatePrep, AcpiGetTimer,
-    },
+    bindings::functions::{AcpiDebugTrace, AcpiEnterSleepState, AcpiEnterSleepStatePrep},
     AcpicaOperation,
 };
 
@@ -40,27 +38,38 @@ impl<const TI: bool, const TL: bool, const E: bool, const I: bool> AcpicaOperati
     }
 }
 
-// TODO: check what level of initialization these really need
 impl AcpicaOperation<true, true, true, true> {
-    /// TODO: docs
+    /// Reads the PM timer's counter.
+    ///
+    /// # Panics
+    /// If the system has no PM timer
+    #[deprecated(note = "use `PmTimer::read`")]
+    pub fn get_timer(&mut self) -> u32 {
+        self.pm_timer()
+            .and_then(|timer| timer.read())
+            .expect("the system should have a PM timer")
+    }
+
+    /// Rust binding to the ACPICA `AcpiEnterSleepStatePrep` function.
+    ///
+    /// # Safety
+    /// * This must be called with interrupts enabled
+    #[deprecated(note = "use `prepare_sleep`, which takes a `SleepState`")]
     pub unsafe fn enter_sleep_state_prep(&mut self, state: u8) -> Result<(), AcpiError> {
-        // SAFETY: TODO
+        // SAFETY: ACPICA is fully initialized and the caller guarantees that interrupts are enabled
         unsafe { AcpiEnterSleepStatePrep(state).as_result() }
     }
 
-    /// TODO: docs
+    /// Rust binding to the ACPICA `AcpiEnterSleepState` function.
+    ///
+    /// # Safety
+    /// * The state must have been prepared with [`enter_sleep_state_prep`]
+    /// * Interrupts must be disabled on the current CPU, and all other CPUs must be stopped
+    ///
+    /// [`enter_sleep_state_prep`]: AcpicaOperation::enter_sleep_state_prep
+    #[deprecated(note = "use `PreparedSleep::enter`, or `power_off` for S5")]
     pub unsafe fn enter_sleep_state(&mut self, state: u8) -> Result<(), AcpiError> {
-        // SAFETY: TODO
+        // SAFETY: The caller guarantees that the state was prepared and that interrupts are disabled
         unsafe { AcpiEnterSleepState(state).as_result() }
     }
-
-    /// TODO: docs
-    pub fn get_timer(&mut self) -> u32 {
-        let mut x = 0;
-
-        // SAFETY: TODO
-        unsafe { AcpiGetTimer(&mut x).as_result().unwrap() }
-
-        x
-    }
 }
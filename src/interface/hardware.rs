@@ -0,0 +1,310 @@
+//! Code to access the fixed hardware registers described by the FADT, including the PM timer
+
+use core::time::Duration;
+
+use crate::{
+    bindings::{
+        consts::{
+            ACPI_BITREG_ARB_DISABLE, ACPI_BITREG_BUS_MASTER_RLD, ACPI_BITREG_BUS_MASTER_STATUS,
+            ACPI_BITREG_GLOBAL_LOCK_ENABLE, ACPI_BITREG_GLOBAL_LOCK_RELEASE,
+            ACPI_BITREG_GLOBAL_LOCK_STATUS, ACPI_BITREG_PCIEXP_WAKE_DISABLE,
+            ACPI_BITREG_PCIEXP_WAKE_STATUS, ACPI_BITREG_POWER_BUTTON_ENABLE,
+            ACPI_BITREG_POWER_BUTTON_STATUS, ACPI_BITREG_RT_CLOCK_ENABLE,
+            ACPI_BITREG_RT_CLOCK_STATUS, ACPI_BITREG_SCI_ENABLE, ACPI_BITREG_SLEEP_BUTTON_ENABLE,
+            ACPI_BITREG_SLEEP_BUTTON_STATUS, ACPI_BITREG_SLEEP_ENABLE, ACPI_BITREG_SLEEP_TYPE,
+            ACPI_BITREG_TIMER_ENABLE, ACPI_BITREG_TIMER_STATUS, ACPI_BITREG_WAKE_STATUS,
+            ACPI_PM_TIMER_FREQUENCY,
+        },
+        functions::{
+            AcpiGetTimer, AcpiGetTimerResolution, AcpiReadBitRegister, AcpiWriteBitRegister,
+        },
+    },
+    status::AcpiError,
+    AcpicaOperation,
+};
+
+/// A field in one of the fixed PM1 status, PM1 enable, PM1 control or PM2 control registers.
+///
+/// Status bits are cleared by writing 1 to them, which ACPICA handles when they are written with
+/// [`write_bit_register`].
+///
+/// [`write_bit_register`]: AcpicaOperation::write_bit_register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitRegister {
+    /// `TMR_STS` in PM1 status - the top bit of the PM timer has changed
+    TimerStatus,
+    /// `BM_STS` in PM1 status - a bus master has requested the system bus
+    BusMasterStatus,
+    /// `GBL_STS` in PM1 status - the firmware has released the global lock
+    GlobalLockStatus,
+    /// `PWRBTN_STS` in PM1 status - the power button has been pressed
+    PowerButtonStatus,
+    /// `SLPBTN_STS` in PM1 status - the sleep button has been pressed
+    SleepButtonStatus,
+    /// `RTC_STS` in PM1 status - the real time clock's alarm has fired
+    RtClockStatus,
+    /// `WAK_STS` in PM1 status - the system has woken from a sleep state
+    WakeStatus,
+    /// `PCIEXP_WAKE_STS` in PM1 status - a PCI Express device has woken the system
+    PciExpressWakeStatus,
+    /// `TMR_EN` in PM1 enable - whether the PM timer raises an SCI when its top bit changes
+    TimerEnable,
+    /// `GBL_EN` in PM1 enable - whether releasing the global lock raises an SCI
+    GlobalLockEnable,
+    /// `PWRBTN_EN` in PM1 enable - whether pressing the power button raises an SCI
+    PowerButtonEnable,
+    /// `SLPBTN_EN` in PM1 enable - whether pressing the sleep button raises an SCI
+    SleepButtonEnable,
+    /// `RTC_EN` in PM1 enable - whether the real time clock's alarm raises an SCI
+    RtClockEnable,
+    /// `PCIEXP_WAKE_DIS` in PM1 enable - whether PCI Express devices are prevented from waking the system
+    PciExpressWakeDisable,
+    /// `SCI_EN` in PM1 control - whether the system is in ACPI mode, so power management events raise SCIs rather than SMIs
+    SciEnable,
+    /// `BM_RLD` in PM1 control - whether bus master requests move the CPU from C3 to C0
+    BusMasterReload,
+    /// `GBL_RLS` in PM1 control - set to tell the firmware that the OS has released the global lock
+    GlobalLockRelease,
+    /// `SLP_TYP` in PM1 control - the type of sleep state to enter when [`SleepEnable`][BitRegister::SleepEnable] is set
+    SleepType,
+    /// `SLP_EN` in PM1 control - set to enter the sleep state in [`SleepType`][BitRegister::SleepType]
+    SleepEnable,
+    /// `ARB_DIS` in PM2 control - whether the bus arbiter is disabled
+    ArbiterDisable,
+}
+
+impl BitRegister {
+    fn to_u32(self) -> u32 {
+        match self {
+            Self::TimerStatus => ACPI_BITREG_TIMER_STATUS,
+            Self::BusMasterStatus => ACPI_BITREG_BUS_MASTER_STATUS,
+            Self::GlobalLockStatus => ACPI_BITREG_GLOBAL_LOCK_STATUS,
+            Self::PowerButtonStatus => ACPI_BITREG_POWER_BUTTON_STATUS,
+            Self::SleepButtonStatus => ACPI_BITREG_SLEEP_BUTTON_STATUS,
+            Self::RtClockStatus => ACPI_BITREG_RT_CLOCK_STATUS,
+            Self::WakeStatus => ACPI_BITREG_WAKE_STATUS,
+            Self::PciExpressWakeStatus => ACPI_BITREG_PCIEXP_WAKE_STATUS,
+            Self::TimerEnable => ACPI_BITREG_TIMER_ENABLE,
+            Self::GlobalLockEnable => ACPI_BITREG_GLOBAL_LOCK_ENABLE,
+            Self::PowerButtonEnable => ACPI_BITREG_POWER_BUTTON_ENABLE,
+            Self::SleepButtonEnable => ACPI_BITREG_SLEEP_BUTTON_ENABLE,
+            Self::RtClockEnable => ACPI_BITREG_RT_CLOCK_ENABLE,
+            Self::PciExpressWakeDisable => ACPI_BITREG_PCIEXP_WAKE_DISABLE,
+            Self::SciEnable => ACPI_BITREG_SCI_ENABLE,
+            Self::BusMasterReload => ACPI_BITREG_BUS_MASTER_RLD,
+            Self::GlobalLockRelease => ACPI_BITREG_GLOBAL_LOCK_RELEASE,
+            Self::SleepType => ACPI_BITREG_SLEEP_TYPE,
+            Self::SleepEnable => ACPI_BITREG_SLEEP_ENABLE,
+            Self::ArbiterDisable => ACPI_BITREG_ARB_DISABLE,
+        }
+    }
+}
+
+impl<const TL: bool, const E: bool, const I: bool> AcpicaOperation<true, TL, E, I> {
+    /// Reads a field from the fixed hardware registers.
+    /// The value is shifted down, so single bit fields are read as 0 or 1.
+    ///
+    /// # Errors
+    /// Any error raised while reading the register
+    pub fn read_bit_register(&self, register: BitRegister) -> Result<u32, AcpiError> {
+        let mut value = 0;
+
+        // SAFETY: The tables are initialized, so ACPICA has parsed the FADT to find the registers
+        unsafe {
+            AcpiReadBitRegister(register.to_u32(), core::ptr::addr_of_mut!(value)).as_result()?;
+        };
+
+        Ok(value)
+    }
+
+    /// Writes a field in the fixed hardware registers, preserving the other fields in the register.
+    /// The value is shifted up into the field's position, so single bit fields are written as 0 or 1.
+    ///
+    /// Writing 1 to a status field clears it, and writing 0 does nothing.
+    ///
+    /// # Errors
+    /// Any error raised while writing the register
+    pub fn write_bit_register(&self, register: BitRegister, value: u32) -> Result<(), AcpiError> {
+        // SAFETY: The tables are initialized, so ACPICA has parsed the FADT to find the registers
+        unsafe { AcpiWriteBitRegister(register.to_u32(), value).as_result() }
+    }
+
+    /// Gets the PM timer, which is a fixed-frequency counter that can be used as a time source
+    /// before other timers have been calibrated.
+    ///
+    /// # Errors
+    /// * [`AcpiError::Support`] if the system has no PM timer
+    pub fn pm_timer(&self) -> Result<PmTimer, AcpiError> {
+        let mut resolution = 0;
+
+        // SAFETY: The tables are initialized, so ACPICA has parsed the FADT to find the timer
+        unsafe {
+            AcpiGetTimerResolution(core::ptr::addr_of_mut!(resolution)).as_result()?;
+        };
+
+        let timer = PmTimer { resolution };
+
+        // Check that the timer exists
+        timer.read()?;
+
+        Ok(timer)
+    }
+}
+
+/// The PM timer, a counter which increments at [`FREQUENCY`] and wraps around once it reaches its maximum value.
+/// The timer is either 24 or 32 bits wide.
+///
+/// This struct can be obtained from [`pm_timer`].
+///
+/// [`FREQUENCY`]: PmTimer::FREQUENCY
+/// [`pm_timer`]: AcpicaOperation::pm_timer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PmTimer {
+    resolution: u32,
+}
+
+impl PmTimer {
+    /// The frequency of the PM timer in Hz
+    pub const FREQUENCY: u32 = ACPI_PM_TIMER_FREQUENCY;
+
+    /// Gets the number of bits in the timer's counter, which is either 24 or 32
+    #[must_use]
+    pub fn resolution(&self) -> u32 {
+        self.resolution
+    }
+
+    /// Gets the largest value of the counter, after which it wraps around to 0
+    #[must_use]
+    pub fn max_ticks(&self) -> u32 {
+        u32::MAX >> (32 - self.resolution)
+    }
+
+    /// Reads the current value of the counter
+    ///
+    /// # Errors
+    /// * [`AcpiError::Support`] if the system has no PM timer
+    pub fn read(&self) -> Result<u32, AcpiError> {
+        let mut ticks = 0;
+
+        // SAFETY: A `PmTimer` is only created once the tables are initialized
+        unsafe {
+            AcpiGetTimer(core::ptr::addr_of_mut!(ticks)).as_result()?;
+        };
+
+        Ok(ticks)
+    }
+
+    /// Gets the number of ticks between two values of the counter, assuming that it has wrapped around at most once
+    #[must_use]
+    pub fn ticks_between(&self, start: u32, end: u32) -> u32 {
+        end.wrapping_sub(start) & self.max_ticks()
+    }
+
+    /// Gets the time between two values of the counter, assuming that it has wrapped around at most once.
+    /// A 24 bit timer wraps around about every 4.7 seconds, and a 32 bit timer about every 20 minutes.
+    #[must_use]
+    pub fn duration_between(&self, start: u32, end: u32) -> Duration {
+        let ticks = u64::from(self.ticks_between(start, end));
+
+        Duration::from_nanos(ticks * 1_000_000_000 / u64::from(Self::FREQUENCY))
+    }
+
+    /// Gets the time since `start`, which is a value previously read from the counter.
+    ///
+    /// # Errors
+    /// * [`AcpiError::Support`] if the system has no PM timer
+    pub fn elapsed_since(&self, start: u32) -> Result<Duration, AcpiError> {
+        Ok(self.duration_between(start, self.read()?))
+    }
+
+    /// Busy-waits for `duration`
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if `duration` is too long to be measured without the counter wrapping more than once
+    /// * [`AcpiError::Support`] if the system has no PM timer
+    pub fn busy_wait(&self, duration: Duration) -> Result<(), AcpiError> {
+        let ticks = self.duration_to_ticks(duration)?;
+        let start = self.read()?;
+
+        while self.ticks_between(start, self.read()?) < ticks {
+            core::hint::spin_loop();
+        }
+
+        Ok(())
+    }
+
+    /// Measures the frequency of another counter, such as the TSC, by busy-waiting on the PM timer for `duration`.
+    /// `read_counter` is called at the start and end of the wait, and the frequency is returned in Hz.
+    ///
+    /// Longer durations give more accurate results. Interrupts should be disabled while this runs.
+    ///
+    /// ```ignore
+    /// let timer = acpica.pm_timer()?;
+    /// // SAFETY: The TSC is always readable
+    /// let tsc_frequency = timer.calibrate_counter(Duration::from_millis(50), || unsafe { core::arch::x86_64::_rdtsc() })?;
+    /// ```
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if `duration` is too long to be measured without the counter wrapping more than once
+    /// * [`AcpiError::Support`] if the system has no PM timer
+    pub fn calibrate_counter(
+        &self,
+        duration: Duration,
+        mut read_counter: impl FnMut() -> u64,
+    ) -> Result<u64, AcpiError> {
+        let ticks = self.duration_to_ticks(duration)?;
+
+        // Wait for the timer to change, so that the measurement starts at the beginning of a tick
+        let first = self.read()?;
+        let mut start = first;
+        while start == first {
+            start = self.read()?;
+        }
+
+        let counter_start = read_counter();
+
+        let elapsed = loop {
+            let elapsed = self.ticks_between(start, self.read()?);
+            if elapsed >= ticks {
+                break elapsed;
+            }
+
+            core::hint::spin_loop();
+        };
+
+        let counter_end = read_counter();
+
+        let frequency = u128::from(counter_end.wrapping_sub(counter_start))
+            * u128::from(Self::FREQUENCY)
+            / u128::from(elapsed);
+
+        Ok(u64::try_from(frequency).unwrap_or(u64::MAX))
+    }
+
+    /// Converts a duration to a number of ticks which can be waited for without the counter wrapping more than once
+    fn duration_to_ticks(self, duration: Duration) -> Result<u32, AcpiError> {
+        let ticks = duration.as_nanos() * u128::from(Self::FREQUENCY) / 1_000_000_000;
+
+        match u32::try_from(ticks) {
+            Ok(ticks) if ticks > 0 && ticks < self.max_ticks() => Ok(ticks),
+            _ => Err(AcpiError::BadParameter),
+        }
+    }
+}
+
+#[test]
+fn test_pm_timer_wraparound() {
+    let timer = PmTimer { resolution: 24 };
+
+    assert_eq!(timer.max_ticks(), 0x00FF_FFFF);
+    assert_eq!(timer.ticks_between(0x00FF_FFF0, 0x0000_0010), 0x20);
+    assert_eq!(
+        timer.duration_between(0, PmTimer::FREQUENCY),
+        Duration::from_secs(1)
+    );
+
+    let timer = PmTimer { resolution: 32 };
+
+    assert_eq!(timer.ticks_between(u32::MAX, 1), 2);
+    assert_eq!(timer.ticks_between(5, 5), 0);
+}
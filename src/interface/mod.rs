@@ -15,9 +15,22 @@ use self::{handler::AcpiHandler, status::AcpiError};
 
 pub mod handler;
 
+pub mod address_space;
+pub mod battery;
 pub mod devices;
+pub mod embedded_controller;
+pub mod event;
+pub mod gpe;
+pub mod hardware;
+pub mod notify;
+pub mod pci;
+pub mod power;
+pub mod power_devices;
+pub mod processor;
+pub mod sleep;
 pub mod status;
 mod tables;
+pub mod thermal;
 pub mod types;
 
 static OS_INTERFACE: Mutex<Option<OsInterface>> = Mutex::new(None);
@@ -0,0 +1,213 @@
+//! Code to handle notifications sent to devices by the AML `Notify` operator
+
+use core::{ffi::c_void, fmt::Debug};
+
+use alloc::boxed::Box;
+
+use crate::{
+    bindings::{
+        consts::{ACPI_ALL_NOTIFY, ACPI_DEVICE_NOTIFY, ACPI_SYSTEM_NOTIFY},
+        functions::{AcpiInstallNotifyHandler, AcpiRemoveNotifyHandler},
+        types::{functions::FfiAcpiNotifyHandler, FfiAcpiHandle},
+    },
+    devices::AcpiHandle,
+    status::AcpiError,
+};
+
+/// Which notifications a notify handler receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyHandlerKind {
+    /// Notifications with values from `0x00` to `0x7F`, which are defined by the ACPI spec
+    System,
+    /// Notifications with values from `0x80` to `0xFF`, which are specific to the type of device
+    Device,
+    /// All notifications
+    All,
+}
+
+impl NotifyHandlerKind {
+    fn to_u32(self) -> u32 {
+        match self {
+            Self::System => ACPI_SYSTEM_NOTIFY,
+            Self::Device => ACPI_DEVICE_NOTIFY,
+            Self::All => ACPI_ALL_NOTIFY,
+        }
+    }
+}
+
+/// A notification sent to a device by the AML `Notify` operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notification {
+    /// The OS should re-enumerate the devices on the bus below the device
+    BusCheck,
+    /// The OS should check whether the device has been inserted or removed
+    DeviceCheck,
+    /// The device has woken the system
+    DeviceWake,
+    /// The device should be ejected
+    EjectRequest,
+    /// The OS should check the device without re-enumerating the bus it's on
+    DeviceCheckLight,
+    /// The device can't work at the frequency of the bus it's on
+    FrequencyMismatch,
+    /// The device can't work in the mode of the bus it's on
+    BusModeMismatch,
+    /// The device has a power fault
+    PowerFault,
+    /// The OS should re-evaluate the device's `_OSC` or `_PDC` method, as its capabilities have changed
+    CapabilitiesCheck,
+    /// The OS should re-evaluate the device's `_PLD` method
+    DevicePldCheck,
+    /// Another value from `0x00` to `0x7F`, which is either reserved or defined by a newer version of the ACPI spec
+    OtherSystem(u8),
+    /// A value from `0x80` to `0xFF`, whose meaning depends on the type of device.
+    /// For instance, `0x80` on a battery means that its status has changed.
+    DeviceSpecific(u8),
+}
+
+impl Notification {
+    /// Decodes a notification value.
+    /// Values above `0xFF` aren't valid, so only the lowest byte is used.
+    #[must_use]
+    pub fn from_u32(value: u32) -> Self {
+        let [value, ..] = value.to_le_bytes();
+
+        match value {
+            0x00 => Self::BusCheck,
+            0x01 => Self::DeviceCheck,
+            0x02 => Self::DeviceWake,
+            0x03 => Self::EjectRequest,
+            0x04 => Self::DeviceCheckLight,
+            0x05 => Self::FrequencyMismatch,
+            0x06 => Self::BusModeMismatch,
+            0x07 => Self::PowerFault,
+            0x08 => Self::CapabilitiesCheck,
+            0x09 => Self::DevicePldCheck,
+            0x0A..=0x7F => Self::OtherSystem(value),
+            0x80..=0xFF => Self::DeviceSpecific(value),
+        }
+    }
+
+    /// Gets the value of the notification
+    #[must_use]
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::BusCheck => 0x00,
+            Self::DeviceCheck => 0x01,
+            Self::DeviceWake => 0x02,
+            Self::EjectRequest => 0x03,
+            Self::DeviceCheckLight => 0x04,
+            Self::FrequencyMismatch => 0x05,
+            Self::BusModeMismatch => 0x06,
+            Self::PowerFault => 0x07,
+            Self::CapabilitiesCheck => 0x08,
+            Self::DevicePldCheck => 0x09,
+            Self::OtherSystem(v) | Self::DeviceSpecific(v) => v,
+        }
+    }
+}
+
+/// A notify handler installed by [`install_notify_handler`]. The handler is removed when this struct is dropped.
+///
+/// [`install_notify_handler`]: AcpiHandle::install_notify_handler
+#[must_use = "The handler is removed when the guard is dropped"]
+pub struct NotifyHandlerGuard {
+    handle: AcpiHandle,
+    kind: NotifyHandlerKind,
+    handler: FfiAcpiNotifyHandler,
+    context: *mut c_void,
+    drop_context: unsafe fn(*mut c_void),
+}
+
+// SAFETY: The closure in `context` is `Send + Sync`, and ACPICA can remove the handler from any thread
+unsafe impl Send for NotifyHandlerGuard {}
+// SAFETY: The guard has no methods which use the closure
+unsafe impl Sync for NotifyHandlerGuard {}
+
+impl Debug for NotifyHandlerGuard {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("NotifyHandlerGuard")
+            .field("handle", &self.handle)
+            .field("kind", &self.kind)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for NotifyHandlerGuard {
+    fn drop(&mut self) {
+        // SAFETY: The handler was installed with these arguments
+        let r = unsafe { AcpiRemoveNotifyHandler(self.handle.0, self.kind.to_u32(), self.handler) };
+
+        // If the handler couldn't be removed, ACPICA could still call it, so the closure is leaked
+        if r.as_result().is_ok() {
+            // SAFETY: The handler has been removed, and ACPICA waits for running handlers to finish before
+            // returning from `AcpiRemoveNotifyHandler`, so nothing else is using the closure
+            unsafe { (self.drop_context)(self.context) };
+        }
+    }
+}
+
+/// Calls the closure in `context` with the decoded notification
+unsafe extern "C" fn call_notify_handler<F>(device: FfiAcpiHandle, value: u32, context: *mut c_void)
+where
+    F: Fn(AcpiHandle, Notification) + Send + Sync + 'static,
+{
+    // SAFETY: `context` was created from a `Box<F>` by `install_notify_handler`, and isn't freed until the handler is removed
+    let handler = unsafe { &*context.cast::<F>() };
+
+    handler(AcpiHandle(device), Notification::from_u32(value));
+}
+
+/// Frees the closure in `context`
+///
+/// # Safety
+/// `context` must have been created from a `Box<F>`, and must not be used afterwards
+unsafe fn drop_notify_handler<F>(context: *mut c_void) {
+    // SAFETY: `context` came from a `Box<F>`
+    drop(unsafe { Box::from_raw(context.cast::<F>()) });
+}
+
+impl AcpiHandle {
+    /// Installs a handler which is called when AML code sends a notification to this object with the `Notify` operator.
+    /// The handler is passed the object which received the notification and the decoded notification value.
+    ///
+    /// The handler is removed when the returned guard is dropped.
+    /// ACPICA runs notify handlers in a deferred context created by the OS's [`execute`] method, not in interrupt context.
+    ///
+    /// The object can be the root of the namespace, to receive notifications sent to any object.
+    ///
+    /// [`execute`]: crate::handler::AcpiHandler::execute
+    ///
+    /// # Errors
+    /// * [`AcpiError::AlreadyExists`] if a handler of the same kind is already installed on the object
+    /// * [`AcpiError::Type`] if the object can't receive notifications
+    pub fn install_notify_handler<F>(
+        &self,
+        kind: NotifyHandlerKind,
+        handler: F,
+    ) -> Result<NotifyHandlerGuard, AcpiError>
+    where
+        F: Fn(AcpiHandle, Notification) + Send + Sync + 'static,
+    {
+        let context = Box::into_raw(Box::new(handler)).cast::<c_void>();
+
+        // SAFETY: `context` is valid for the type expected by the handler, and stays valid until the guard is dropped
+        let r = unsafe {
+            AcpiInstallNotifyHandler(self.0, kind.to_u32(), call_notify_handler::<F>, context)
+        };
+
+        if let Err(e) = r.as_result() {
+            // SAFETY: The handler wasn't installed, so nothing else has access to `context`
+            unsafe { drop_notify_handler::<F>(context) };
+            return Err(e);
+        }
+
+        Ok(NotifyHandlerGuard {
+            handle: *self,
+            kind,
+            handler: call_notify_handler::<F>,
+            context,
+            drop_context: drop_notify_handler::<F>,
+        })
+    }
+}
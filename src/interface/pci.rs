@@ -0,0 +1,191 @@
+//! Code to find which interrupts PCI devices' interrupt pins are connected to
+
+use crate::{
+    devices::{AcpiHandle, AcpiPciPin},
+    status::AcpiError,
+    types::{
+        resource::{InterruptPolarity, InterruptTrigger, Resource},
+        tables::madt::{self, MadtRecord},
+    },
+    AcpicaOperation,
+};
+
+/// The interrupt which a PCI device's interrupt pin is connected to, found by [`resolve_pci_interrupt`]
+///
+/// [`resolve_pci_interrupt`]: AcpicaOperation::resolve_pci_interrupt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PciInterrupt {
+    /// The global system interrupt which the pin is connected to
+    pub gsi: u32,
+    /// How the interrupt is triggered
+    pub triggering: InterruptTrigger,
+    /// Which level or edge of the interrupt is active
+    pub polarity: InterruptPolarity,
+}
+
+impl PciInterrupt {
+    /// PCI interrupts are level triggered and active low unless an interrupt link device says otherwise
+    fn pci_default(gsi: u32) -> Self {
+        Self {
+            gsi,
+            triggering: InterruptTrigger::Level,
+            polarity: InterruptPolarity::ActiveLow,
+        }
+    }
+}
+
+/// An interrupt read from the resources of an interrupt link device
+struct LinkInterrupt {
+    interrupt: PciInterrupt,
+    /// Whether the interrupt came from an `IRQ` descriptor, so the number is an ISA IRQ rather than a global system interrupt
+    isa: bool,
+}
+
+impl AcpicaOperation<true, true, true, true> {
+    /// Finds the global system interrupt which a PCI device's interrupt pin is connected to.
+    ///
+    /// `bus` is the object in the AML namespace for the bridge which the device is behind, either a host bridge
+    /// or a PCI-to-PCI bridge. `device` is the device number on that bridge's secondary bus.
+    ///
+    /// If the bridge has no `_PRT` method, it is assumed to be a PCI-to-PCI bridge which follows the standard
+    /// swizzling of interrupt pins, and the bridge's own `_ADR` is used to look up the pin in its parent's `_PRT`.
+    ///
+    /// If the `_PRT` entry refers to an interrupt link device, the interrupt is read from the link device's `_CRS`.
+    /// If the link device is disabled, it is configured to use the first interrupt in its `_PRS`. To choose a different
+    /// interrupt, [`AcpiHandle::set_irq_from_possible_resources`] should be called on the link device first.
+    ///
+    /// ISA IRQs from link devices are converted to global system interrupts using the interrupt source overrides in the MADT.
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if no `_PRT` has an entry for the device, or a link device has no interrupt
+    /// * Any error raised while evaluating the `_PRT` method or the link device's methods
+    pub fn resolve_pci_interrupt(
+        &self,
+        bus: AcpiHandle,
+        device: u16,
+        pin: AcpiPciPin,
+    ) -> Result<PciInterrupt, AcpiError> {
+        let mut bus = bus;
+        let mut device = device;
+        let mut pin = pin;
+
+        let table = loop {
+            if let Some(table) = bus.get_irq_routing_table()? {
+                break table;
+            }
+
+            // Move up through a PCI-to-PCI bridge with no `_PRT`
+            let bridge = bus.get_info()?.pci_address().ok_or(AcpiError::NotFound)?;
+
+            pin = pin.swizzle(device);
+            device = bridge.device;
+            bus = bus.parent().ok_or(AcpiError::NotFound)?;
+        };
+
+        let entry = table
+            .into_iter()
+            .find(|e| e.device_number == device && e.pin == pin)
+            .ok_or(AcpiError::NotFound)?;
+
+        let Some(source) = entry.source else {
+            return Ok(PciInterrupt::pci_default(entry.source_index));
+        };
+
+        let link = bus.get_child(&source)?;
+        let interrupt = link_interrupt(link, entry.source_index)?;
+
+        if interrupt.isa {
+            Ok(self.apply_source_override(interrupt.interrupt))
+        } else {
+            Ok(interrupt.interrupt)
+        }
+    }
+
+    /// Applies an interrupt source override from the MADT to an ISA IRQ, if there is one
+    fn apply_source_override(&self, interrupt: PciInterrupt) -> PciInterrupt {
+        if self.table(*b"APIC").is_none() {
+            return interrupt;
+        }
+
+        let madt = self.madt();
+
+        let flags = madt.records().find_map(|record| match record {
+            MadtRecord::IoApicInterruptSourceOverride {
+                irq_source,
+                global_system_interrupt,
+                flags,
+                ..
+            } if u32::from(irq_source) == interrupt.gsi => Some((global_system_interrupt, flags)),
+            _ => None,
+        });
+
+        let Some((gsi, flags)) = flags else {
+            return interrupt;
+        };
+
+        // Flags which conform to the bus keep the values from the link device
+        let triggering = match flags.trigger_mode() {
+            madt::InterruptTriggerMode::EdgeTriggered => InterruptTrigger::Edge,
+            madt::InterruptTriggerMode::LevelTriggered => InterruptTrigger::Level,
+            _ => interrupt.triggering,
+        };
+
+        let polarity = match flags.polarity() {
+            madt::InterruptPolarity::ActiveHigh => InterruptPolarity::ActiveHigh,
+            madt::InterruptPolarity::ActiveLow => InterruptPolarity::ActiveLow,
+            _ => interrupt.polarity,
+        };
+
+        PciInterrupt {
+            gsi,
+            triggering,
+            polarity,
+        }
+    }
+}
+
+/// Gets the interrupt which an interrupt link device is using, configuring it from `_PRS` if it's disabled.
+/// `index` is the `_PRT` source index, which is the index of the interrupt's descriptor in the link's `_CRS`.
+fn link_interrupt(link: AcpiHandle, index: u32) -> Result<LinkInterrupt, AcpiError> {
+    if let Some(interrupt) = nth_interrupt(link.current_resources()?, index) {
+        return Ok(interrupt);
+    }
+
+    link.set_irq_from_possible_resources(|interrupts| interrupts.first().copied())?;
+
+    nth_interrupt(link.current_resources()?, index).ok_or(AcpiError::NotFound)
+}
+
+/// Gets the first interrupt from the `index`th resource descriptor, counting all types of descriptor,
+/// if it's an interrupt resource and the interrupt isn't 0.
+/// Link devices use an interrupt of 0, or an empty list, to indicate that they are disabled.
+fn nth_interrupt(
+    mut resources: impl Iterator<Item = Resource>,
+    index: u32,
+) -> Option<LinkInterrupt> {
+    let interrupt = match resources.nth(index as usize)? {
+        Resource::Irq(irq) => LinkInterrupt {
+            interrupt: PciInterrupt {
+                gsi: irq.interrupts.first().copied().map_or(0, u32::from),
+                triggering: irq.triggering,
+                polarity: irq.polarity,
+            },
+            isa: true,
+        },
+        Resource::ExtendedIrq(irq) => LinkInterrupt {
+            interrupt: PciInterrupt {
+                gsi: irq.interrupts.first().copied().unwrap_or(0),
+                triggering: irq.triggering,
+                polarity: irq.polarity,
+            },
+            isa: false,
+        },
+        _ => return None,
+    };
+
+    if interrupt.interrupt.gsi == 0 {
+        None
+    } else {
+        Some(interrupt)
+    }
+}
@@ -0,0 +1,503 @@
+//! Code to switch power resources with reference counting, and to move devices between device power states
+
+use alloc::vec::Vec;
+use spin::Mutex;
+
+use crate::{
+    bindings::functions::AcpiOsSleep,
+    devices::{AcpiHandle, DeviceInfo, DeviceState},
+    sleep::SleepState,
+    status::AcpiError,
+    types::object::{AcpiArgument, AcpiObject, AcpiObjectPowerResource, AcpiObjectType},
+};
+
+/// The device power states which have `_PSx` and `_PRx` methods, in order
+const CONTROLLABLE_STATES: [DeviceState; 4] = [
+    DeviceState::D0,
+    DeviceState::D1,
+    DeviceState::D2,
+    DeviceState::D3Hot,
+];
+
+/// A power resource in the AML namespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerResource(AcpiHandle);
+
+impl PowerResource {
+    /// Wraps the power resource at `handle`
+    ///
+    /// # Errors
+    /// * [`AcpiError::Type`] if the object isn't a power resource
+    /// * [`AcpiError::BadParameter`] if the handle is no longer valid
+    pub fn new(handle: AcpiHandle) -> Result<Self, AcpiError> {
+        if handle.object_type()? == AcpiObjectType::PowerResource {
+            Ok(Self(handle))
+        } else {
+            Err(AcpiError::Type)
+        }
+    }
+
+    /// Gets the power resource's object in the AML namespace
+    #[must_use]
+    pub fn handle(&self) -> AcpiHandle {
+        self.0
+    }
+
+    /// Gets the power resource's system level and resource order
+    ///
+    /// # Errors
+    /// Any error raised while evaluating the power resource object
+    #[allow(clippy::missing_panics_doc)]
+    pub fn info(&self) -> Result<AcpiObjectPowerResource, AcpiError> {
+        let value = self
+            .0
+            .evaluate_typed("", &[], AcpiObjectType::PowerResource)?;
+
+        let AcpiObject::PowerResource(info) = value.object() else {
+            unreachable!("ACPICA should have checked the type of the return value");
+        };
+
+        Ok(info)
+    }
+
+    /// Checks whether the power resource is on, using `_STA`
+    ///
+    /// # Errors
+    /// Any error raised while evaluating `_STA`
+    pub fn is_on(&self) -> Result<bool, AcpiError> {
+        Ok(self.0.evaluate_integer("_STA", &[])? & 1 != 0)
+    }
+
+    fn set_on(self, on: bool) -> Result<(), AcpiError> {
+        let method = if on { "_ON" } else { "_OFF" };
+        self.0.evaluate(method, &[]).map(|_| ())
+    }
+}
+
+/// Gets the power resources listed in a device's `_PRx` method, or an empty list if the method doesn't exist
+fn state_resources(
+    device: AcpiHandle,
+    state: DeviceState,
+) -> Result<Vec<PowerResource>, AcpiError> {
+    let path = match state {
+        DeviceState::D0 => "_PR0",
+        DeviceState::D1 => "_PR1",
+        DeviceState::D2 => "_PR2",
+        DeviceState::D3Hot => "_PR3",
+        DeviceState::D3Cold => return Ok(Vec::new()),
+    };
+
+    let package = match device.evaluate_package(path, &[]) {
+        Ok(package) => package,
+        Err(AcpiError::NotFound) => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    package
+        .iter()
+        .map(|resource| {
+            resource
+                .as_reference()
+                .map(PowerResource)
+                .ok_or(AcpiError::AmlOperandType)
+        })
+        .collect()
+}
+
+/// Sorts power resources into the order they should be turned on in. They should be turned off in the reverse order.
+fn sort_resources(resources: &mut [PowerResource]) -> Result<(), AcpiError> {
+    let mut orders = Vec::with_capacity(resources.len());
+    for resource in resources.iter() {
+        orders.push((resource.info()?.resource_order(), *resource));
+    }
+
+    orders.sort_by_key(|&(order, _)| order);
+
+    for (resource, (_, sorted)) in resources.iter_mut().zip(orders) {
+        *resource = sorted;
+    }
+
+    Ok(())
+}
+
+/// A power resource which a [`PowerManager`] has turned on, and how many times
+#[derive(Debug)]
+struct ResourceReference {
+    resource: PowerResource,
+    count: usize,
+    /// Whether a thread is evaluating the resource's `_ON` or `_OFF` method
+    busy: bool,
+}
+
+/// A device whose power state has been set by a [`PowerManager`]
+#[derive(Debug)]
+struct ManagedDevice {
+    device: AcpiHandle,
+    state: DeviceState,
+    /// The power resources referenced for the device's current state
+    resources: Vec<PowerResource>,
+}
+
+#[derive(Debug, Default)]
+struct ManagerState {
+    resources: Vec<ResourceReference>,
+    devices: Vec<ManagedDevice>,
+}
+
+impl ManagerState {
+    /// Marks a resource as busy and returns its reference count, or returns [`None`] if it's already busy
+    fn claim(&mut self, resource: PowerResource) -> Option<usize> {
+        let Some(reference) = self.resources.iter_mut().find(|r| r.resource == resource) else {
+            self.resources.push(ResourceReference {
+                resource,
+                count: 0,
+                busy: true,
+            });
+            return Some(0);
+        };
+
+        if reference.busy {
+            return None;
+        }
+
+        reference.busy = true;
+        Some(reference.count)
+    }
+
+    /// Stores a resource's new reference count and marks it as no longer busy.
+    /// Resources with no references are forgotten.
+    fn unclaim(&mut self, resource: PowerResource, count: usize) {
+        let Some(index) = self.resources.iter().position(|r| r.resource == resource) else {
+            return;
+        };
+
+        if count == 0 {
+            self.resources.swap_remove(index);
+        } else {
+            self.resources[index].count = count;
+            self.resources[index].busy = false;
+        }
+    }
+}
+
+/// Tracks which devices are using each power resource, so that a shared power resource is only turned off once
+/// every device which needs it has been put into a state which doesn't.
+///
+/// Only one [`PowerManager`] should be used to control the devices in the namespace,
+/// as power resources turned on by another [`PowerManager`] or by AML aren't counted.
+///
+/// The reference counts are kept behind a spin lock, which isn't held while AML methods are evaluated.
+/// Instead, while one thread evaluates a power resource's `_ON` or `_OFF` method, other threads using the same
+/// resource wait using [`AcpiHandler::sleep`].
+///
+/// [`AcpiHandler::sleep`]: crate::handler::AcpiHandler::sleep
+#[derive(Debug, Default)]
+pub struct PowerManager {
+    state: Mutex<ManagerState>,
+}
+
+impl PowerManager {
+    /// Creates a [`PowerManager`] which isn't referencing any power resources
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits until no other thread is turning a power resource on or off, then marks it as busy.
+    /// Returns the resource's reference count.
+    fn claim(&self, resource: PowerResource) -> usize {
+        loop {
+            if let Some(count) = self.state.lock().claim(resource) {
+                return count;
+            }
+
+            // SAFETY: Sleeping has no preconditions
+            unsafe { AcpiOsSleep(1) };
+        }
+    }
+
+    /// Turns a power resource on using `_ON` if it isn't already referenced, and adds a reference to it
+    ///
+    /// # Errors
+    /// Any error raised while evaluating `_ON`. The reference isn't added.
+    pub fn acquire(&self, resource: PowerResource) -> Result<(), AcpiError> {
+        let count = self.claim(resource);
+        let result = if count == 0 {
+            resource.set_on(true)
+        } else {
+            Ok(())
+        };
+
+        let count = if result.is_ok() { count + 1 } else { count };
+        self.state.lock().unclaim(resource, count);
+
+        result
+    }
+
+    /// Removes a reference to a power resource, and turns it off using `_OFF` if there are none left
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotAcquired`] if the power resource isn't referenced
+    /// * Any error raised while evaluating `_OFF`. The reference isn't removed, as the resource is still on.
+    pub fn release(&self, resource: PowerResource) -> Result<(), AcpiError> {
+        let count = self.claim(resource);
+        let result = match count {
+            0 => Err(AcpiError::NotAcquired),
+            1 => resource.set_on(false),
+            _ => Ok(()),
+        };
+
+        let count = if result.is_ok() { count - 1 } else { count };
+        self.state.lock().unclaim(resource, count);
+
+        result
+    }
+
+    /// Acquires each resource in turn, releasing them all again if one can't be turned on
+    fn acquire_all(&self, resources: &[PowerResource]) -> Result<(), AcpiError> {
+        for (i, resource) in resources.iter().enumerate() {
+            if let Err(e) = self.acquire(*resource) {
+                // The original error is more useful than any raised while undoing
+                let _ = self.release_all(&resources[..i]);
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Releases resources in the reverse of the order they were acquired in, returning the first error raised
+    fn release_all(&self, resources: &[PowerResource]) -> Result<(), AcpiError> {
+        let mut result = Ok(());
+
+        for resource in resources.iter().rev() {
+            if let Err(e) = self.release(*resource) {
+                result = result.and(Err(e));
+            }
+        }
+
+        result
+    }
+
+    /// Gets the number of references to a power resource
+    #[must_use]
+    pub fn reference_count(&self, resource: PowerResource) -> usize {
+        self.state
+            .lock()
+            .resources
+            .iter()
+            .find(|r| r.resource == resource)
+            .map_or(0, |r| r.count)
+    }
+
+    /// Gets a device's power state.
+    ///
+    /// If this [`PowerManager`] has set the device's state, that state is returned. Otherwise the state is read using
+    /// `_PSC`, or if the device doesn't have `_PSC`, it is the shallowest state whose `_PRx` power resources are all on.
+    ///
+    /// # Errors
+    /// * [`AcpiError::Support`] if the device has neither `_PSC` nor any `_PRx` methods
+    /// * [`AcpiError::AmlOperandValue`] if `_PSC` returns an invalid state
+    /// * Any error raised while evaluating `_PSC`, `_PRx` or a power resource's `_STA`
+    pub fn device_state(&self, device: AcpiHandle) -> Result<DeviceState, AcpiError> {
+        let managed = self
+            .state
+            .lock()
+            .devices
+            .iter()
+            .find(|d| d.device == device)
+            .map(|d| d.state);
+
+        if let Some(state) = managed {
+            return Ok(state);
+        }
+
+        match device.evaluate_integer("_PSC", &[]) {
+            Ok(state) => {
+                return u8::try_from(state)
+                    .ok()
+                    .and_then(DeviceState::from_u8)
+                    .ok_or(AcpiError::AmlOperandValue);
+            }
+            Err(AcpiError::NotFound) => {}
+            Err(e) => return Err(e),
+        }
+
+        let mut has_resources = false;
+        for state in CONTROLLABLE_STATES {
+            let resources = state_resources(device, state)?;
+            if resources.is_empty() {
+                continue;
+            }
+
+            has_resources = true;
+
+            let mut all_on = true;
+            for resource in resources {
+                all_on &= resource.is_on()?;
+            }
+
+            if all_on {
+                return Ok(state);
+            }
+        }
+
+        if has_resources {
+            Ok(DeviceState::D3Cold)
+        } else {
+            Err(AcpiError::Support)
+        }
+    }
+
+    /// Moves a device into a new power state.
+    ///
+    /// The power resources in the new state's `_PRx` are referenced first, in resource order, then the state's `_PSx`
+    /// method is evaluated if it exists, and finally the power resources which were referenced for the device's
+    /// previous state are released. [`D3Cold`] uses `_PS3` and references no power resources.
+    ///
+    /// The device's parent should be put into [`D0`] first, and its children put into a deeper state first.
+    ///
+    /// [`D0`]: DeviceState::D0
+    /// [`D3Cold`]: DeviceState::D3Cold
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlOperandType`] if a `_PRx` contains something other than references
+    /// * Any error raised while evaluating `_PRx`, `_PSx` or the power resources' methods.
+    ///   If the device couldn't be moved to the new state, the references taken for it are released.
+    pub fn set_device_state(
+        &self,
+        device: AcpiHandle,
+        state: DeviceState,
+    ) -> Result<(), AcpiError> {
+        let mut resources = state_resources(device, state)?;
+        sort_resources(&mut resources)?;
+
+        let method = match state {
+            DeviceState::D0 => "_PS0",
+            DeviceState::D1 => "_PS1",
+            DeviceState::D2 => "_PS2",
+            DeviceState::D3Hot | DeviceState::D3Cold => "_PS3",
+        };
+
+        self.acquire_all(&resources)?;
+
+        match device.evaluate(method, &[]) {
+            Ok(_) | Err(AcpiError::NotFound) => {}
+            Err(e) => {
+                let _ = self.release_all(&resources);
+                return Err(e);
+            }
+        }
+
+        let previous = {
+            let mut manager = self.state.lock();
+
+            if let Some(entry) = manager.devices.iter_mut().find(|d| d.device == device) {
+                entry.state = state;
+                core::mem::replace(&mut entry.resources, resources)
+            } else {
+                manager.devices.push(ManagedDevice {
+                    device,
+                    state,
+                    resources,
+                });
+                Vec::new()
+            }
+        };
+
+        self.release_all(&previous)
+    }
+
+    /// Puts a bus into or out of the power state it needs to enumerate its children, using `_PSE`
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if the device doesn't have a `_PSE` method
+    /// * Any error raised while evaluating `_PSE`
+    pub fn set_enumeration_power(
+        &self,
+        device: AcpiHandle,
+        enabled: bool,
+    ) -> Result<(), AcpiError> {
+        device
+            .evaluate("_PSE", &[AcpiArgument::Integer(u64::from(enabled))])
+            .map(|_| ())
+    }
+}
+
+/// The range of power states a device may be put into while the system is in a sleep state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SleepDeviceStates {
+    /// The shallowest power state permitted
+    pub shallowest: DeviceState,
+    /// The deepest power state permitted
+    pub deepest: DeviceState,
+}
+
+impl SleepDeviceStates {
+    fn from_sxd_sxw(
+        highest_dstates: [Option<DeviceState>; 4],
+        lowest_dstates: [Option<DeviceState>; 5],
+        sleep_state: SleepState,
+        wake: bool,
+    ) -> Self {
+        let index = usize::from(sleep_state.to_u8());
+
+        // `_SxD` starts at S1, and `_SxW` at S0
+        let shallowest = highest_dstates
+            .get(index - 1)
+            .copied()
+            .flatten()
+            .unwrap_or(DeviceState::D0);
+
+        let deepest = if wake {
+            lowest_dstates
+                .get(index)
+                .copied()
+                .flatten()
+                .unwrap_or(shallowest)
+        } else {
+            DeviceState::D3Cold
+        };
+
+        Self {
+            shallowest,
+            deepest: deepest.max(shallowest),
+        }
+    }
+}
+
+impl DeviceInfo {
+    /// Gets the power states the device may be put into while the system is in `sleep_state`,
+    /// using the values of `_SxD` and `_SxW`.
+    ///
+    /// If `wake` is true, the deepest state is one from which the device can still wake the system.
+    /// Devices without `_SxW` can't be assumed to wake from anything deeper than the shallowest state.
+    #[must_use]
+    pub fn sleep_device_states(&self, sleep_state: SleepState, wake: bool) -> SleepDeviceStates {
+        SleepDeviceStates::from_sxd_sxw(
+            self.highest_dstates(),
+            self.lowest_dstates(),
+            sleep_state,
+            wake,
+        )
+    }
+}
+
+/// Tests that `_SxD` and `_SxW` values are combined into the permitted range of device states
+#[test]
+fn test_sleep_device_states() {
+    let highest = [None, None, Some(DeviceState::D2), None];
+    let lowest = [None, None, None, Some(DeviceState::D3Hot), None];
+
+    let s3 = |wake| SleepDeviceStates::from_sxd_sxw(highest, lowest, SleepState::S3, wake);
+    assert_eq!(s3(false).shallowest, DeviceState::D2);
+    assert_eq!(s3(false).deepest, DeviceState::D3Cold);
+    assert_eq!(s3(true).deepest, DeviceState::D3Hot);
+
+    let s4 = SleepDeviceStates::from_sxd_sxw(highest, lowest, SleepState::S4, true);
+    assert_eq!(s4.shallowest, DeviceState::D0);
+    assert_eq!(s4.deepest, DeviceState::D0);
+
+    let s5 = SleepDeviceStates::from_sxd_sxw(highest, lowest, SleepState::S5, false);
+    assert_eq!(s5.deepest, DeviceState::D3Cold);
+}
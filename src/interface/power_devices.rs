@@ -0,0 +1,275 @@
+//! Drivers for the simple devices which a power policy needs: AC adapters (`ACPI0003`), lid switches (`PNP0C0D`),
+//! and control method power and sleep buttons (`PNP0C0C` and `PNP0C0E`)
+
+use alloc::vec::Vec;
+
+use crate::{
+    devices::AcpiHandle,
+    notify::{Notification, NotifyHandlerGuard, NotifyHandlerKind},
+    status::AcpiError,
+    AcpicaOperation,
+};
+
+const AC_ADAPTER_HARDWARE_ID: &str = "ACPI0003";
+const LID_HARDWARE_ID: &str = "PNP0C0D";
+const POWER_BUTTON_HARDWARE_ID: &str = "PNP0C0C";
+const SLEEP_BUTTON_HARDWARE_ID: &str = "PNP0C0E";
+
+/// The notification sent by these devices when their state changes, or when a button is pressed
+const NOTIFY_STATUS_CHANGE: u8 = 0x80;
+
+/// Whether an AC adapter is supplying power
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcAdapterState {
+    /// The system is running on AC power
+    Online,
+    /// The system is running on battery power
+    Offline,
+}
+
+impl AcAdapterState {
+    fn read(handle: AcpiHandle) -> Result<Self, AcpiError> {
+        match handle.evaluate_integer("_PSR", &[])? {
+            0 => Ok(Self::Offline),
+            _ => Ok(Self::Online),
+        }
+    }
+}
+
+/// Whether a lid is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LidState {
+    /// The lid is open
+    Open,
+    /// The lid is closed
+    Closed,
+}
+
+impl LidState {
+    fn read(handle: AcpiHandle) -> Result<Self, AcpiError> {
+        match handle.evaluate_integer("_LID", &[])? {
+            0 => Ok(Self::Closed),
+            _ => Ok(Self::Open),
+        }
+    }
+}
+
+/// A device which reports a state and notifies the OS when it changes, so that a power policy can handle AC adapters,
+/// lids and buttons in the same way
+pub trait PowerDevice {
+    /// The value returned by [`state`][PowerDevice::state]
+    type State;
+    /// The value passed to the callback installed by [`on_change`][PowerDevice::on_change]
+    type Event;
+
+    /// Gets the device in the AML namespace
+    fn handle(&self) -> AcpiHandle;
+
+    /// Reads the device's current state
+    ///
+    /// # Errors
+    /// Any error raised while evaluating the AML method which reports the state
+    fn state(&self) -> Result<Self::State, AcpiError>;
+
+    /// Installs a callback which is called when the device notifies the OS of a change.
+    /// The callback is removed when the returned guard is dropped.
+    ///
+    /// # Errors
+    /// Any error which [`install_notify_handler`] can return
+    ///
+    /// [`install_notify_handler`]: AcpiHandle::install_notify_handler
+    fn on_change<F>(&self, callback: F) -> Result<NotifyHandlerGuard, AcpiError>
+    where
+        F: Fn(Self::Event) + Send + Sync + 'static;
+}
+
+/// Installs a notify handler which re-reads a device's state with `read` and passes it to `callback`
+/// whenever the device sends [`NOTIFY_STATUS_CHANGE`]
+fn on_status_change<T: 'static, F>(
+    handle: AcpiHandle,
+    read: fn(AcpiHandle) -> Result<T, AcpiError>,
+    callback: F,
+) -> Result<NotifyHandlerGuard, AcpiError>
+where
+    F: Fn(T) + Send + Sync + 'static,
+{
+    handle.install_notify_handler(NotifyHandlerKind::Device, move |handle, notification| {
+        if notification.to_u8() == NOTIFY_STATUS_CHANGE {
+            if let Ok(state) = read(handle) {
+                callback(state);
+            }
+        }
+    })
+}
+
+/// An AC adapter, found with [`ac_adapters`]. Its state is read using `_PSR`.
+///
+/// [`ac_adapters`]: AcpicaOperation::ac_adapters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcAdapter(AcpiHandle);
+
+impl PowerDevice for AcAdapter {
+    type State = AcAdapterState;
+    type Event = AcAdapterState;
+
+    fn handle(&self) -> AcpiHandle {
+        self.0
+    }
+
+    fn state(&self) -> Result<AcAdapterState, AcpiError> {
+        AcAdapterState::read(self.0)
+    }
+
+    fn on_change<F>(&self, callback: F) -> Result<NotifyHandlerGuard, AcpiError>
+    where
+        F: Fn(AcAdapterState) + Send + Sync + 'static,
+    {
+        on_status_change(self.0, AcAdapterState::read, callback)
+    }
+}
+
+/// A lid switch, found with [`lids`]. Its state is read using `_LID`.
+///
+/// [`lids`]: AcpicaOperation::lids
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lid(AcpiHandle);
+
+impl PowerDevice for Lid {
+    type State = LidState;
+    type Event = LidState;
+
+    fn handle(&self) -> AcpiHandle {
+        self.0
+    }
+
+    fn state(&self) -> Result<LidState, AcpiError> {
+        LidState::read(self.0)
+    }
+
+    fn on_change<F>(&self, callback: F) -> Result<NotifyHandlerGuard, AcpiError>
+    where
+        F: Fn(LidState) + Send + Sync + 'static,
+    {
+        on_status_change(self.0, LidState::read, callback)
+    }
+}
+
+/// Whether a control method button is enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonState {
+    /// The button is enabled and will send notifications when pressed
+    Enabled,
+    /// The button has been disabled by the firmware
+    Disabled,
+}
+
+impl ButtonState {
+    /// Control method buttons have no state of their own, so this reads whether the device is enabled from `_STA`
+    fn read(handle: AcpiHandle) -> Result<Self, AcpiError> {
+        if handle.status()?.enabled() {
+            Ok(Self::Enabled)
+        } else {
+            Ok(Self::Disabled)
+        }
+    }
+}
+
+/// An event from a [`PowerButton`] or [`SleepButton`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonEvent {
+    /// The button was pressed while the system was running
+    Pressed,
+    /// The button woke the system from a sleep state
+    Wake,
+}
+
+impl ButtonEvent {
+    fn install<F>(handle: AcpiHandle, callback: F) -> Result<NotifyHandlerGuard, AcpiError>
+    where
+        F: Fn(Self) + Send + Sync + 'static,
+    {
+        handle.install_notify_handler(NotifyHandlerKind::All, move |_, notification| {
+            match notification {
+                Notification::DeviceWake => callback(Self::Wake),
+                n if n.to_u8() == NOTIFY_STATUS_CHANGE => callback(Self::Pressed),
+                _ => {}
+            }
+        })
+    }
+}
+
+/// Defines a control method button type, which differs from the others only in its docs
+macro_rules! control_method_button {
+    ($name:ident, $kind:literal, $finder:ident) => {
+        #[doc = concat!("A control method ", $kind, " button, found with [`", stringify!($finder), "`].")]
+        ///
+        /// The button's state is whether it's enabled, according to `_STA`. Buttons which are implemented as fixed
+        /// hardware are handled with [`install_fixed_event_handler`] instead.
+        ///
+        #[doc = concat!("[`", stringify!($finder), "`]: AcpicaOperation::", stringify!($finder))]
+        /// [`install_fixed_event_handler`]: AcpicaOperation::install_fixed_event_handler
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name(AcpiHandle);
+
+        impl PowerDevice for $name {
+            type State = ButtonState;
+            type Event = ButtonEvent;
+
+            fn handle(&self) -> AcpiHandle {
+                self.0
+            }
+
+            fn state(&self) -> Result<ButtonState, AcpiError> {
+                ButtonState::read(self.0)
+            }
+
+            fn on_change<F>(&self, callback: F) -> Result<NotifyHandlerGuard, AcpiError>
+            where
+                F: Fn(ButtonEvent) + Send + Sync + 'static,
+            {
+                ButtonEvent::install(self.0, callback)
+            }
+        }
+    };
+}
+
+control_method_button!(PowerButton, "power", power_buttons);
+control_method_button!(SleepButton, "sleep", sleep_buttons);
+
+impl AcpicaOperation<true, true, true, true> {
+    /// Finds the present AC adapters (`ACPI0003`) in the namespace
+    #[must_use]
+    pub fn ac_adapters(&self) -> Vec<AcAdapter> {
+        self.find_devices(AC_ADAPTER_HARDWARE_ID)
+            .into_iter()
+            .map(AcAdapter)
+            .collect()
+    }
+
+    /// Finds the present lid switches (`PNP0C0D`) in the namespace
+    #[must_use]
+    pub fn lids(&self) -> Vec<Lid> {
+        self.find_devices(LID_HARDWARE_ID)
+            .into_iter()
+            .map(Lid)
+            .collect()
+    }
+
+    /// Finds the present control method power buttons (`PNP0C0C`) in the namespace
+    #[must_use]
+    pub fn power_buttons(&self) -> Vec<PowerButton> {
+        self.find_devices(POWER_BUTTON_HARDWARE_ID)
+            .into_iter()
+            .map(PowerButton)
+            .collect()
+    }
+
+    /// Finds the present control method sleep buttons (`PNP0C0E`) in the namespace
+    #[must_use]
+    pub fn sleep_buttons(&self) -> Vec<SleepButton> {
+        self.find_devices(SLEEP_BUTTON_HARDWARE_ID)
+            .into_iter()
+            .map(SleepButton)
+            .collect()
+    }
+}
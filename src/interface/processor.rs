@@ -0,0 +1,733 @@
+//! Code to find processors in the namespace and read their performance and idle states
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    bindings::types::FfiAcpiGenericAddress,
+    devices::{AcpiHandle, WalkDecision},
+    status::AcpiError,
+    types::{
+        object::{package_integer, AcpiObject, AcpiObjectType, AcpiValue},
+        tables::madt::{ApicFlags, GiccFlags, Madt, MadtRecord},
+        AcpiGenericAddress, GasAddressSpace,
+    },
+    AcpicaOperation,
+};
+
+/// The hardware ID of a processor device
+const PROCESSOR_HARDWARE_ID: &str = "ACPI0007";
+
+/// The tag of a generic register resource descriptor, which AML uses to describe registers in buffers
+const GENERIC_REGISTER_DESCRIPTOR: u8 = 0x82;
+/// The length of a generic register resource descriptor, including its tag and length fields
+const GENERIC_REGISTER_DESCRIPTOR_LENGTH: usize = 15;
+
+/// Gets the integer at `index` in a package, checking that it fits in 32 bits
+fn package_u32(package: &[AcpiValue], index: usize) -> Result<u32, AcpiError> {
+    u32::try_from(package_integer(package, index)?).map_err(|_| AcpiError::AmlNumericOverflow)
+}
+
+/// Gets the package at `index` in a package
+fn package_package(package: &[AcpiValue], index: usize) -> Result<&[AcpiValue], AcpiError> {
+    package
+        .get(index)
+        .ok_or(AcpiError::AmlPackageLimit)?
+        .as_package()
+        .ok_or(AcpiError::AmlOperandType)
+}
+
+/// Decodes the register described by a generic register resource descriptor in a buffer
+fn generic_register(buffer: &[u8]) -> Result<AcpiGenericAddress, AcpiError> {
+    let descriptor = buffer
+        .get(..GENERIC_REGISTER_DESCRIPTOR_LENGTH)
+        .ok_or(AcpiError::AmlBufferLimit)?;
+
+    if descriptor[0] != GENERIC_REGISTER_DESCRIPTOR {
+        return Err(AcpiError::AmlInvalidResourceType);
+    }
+
+    let mut address = [0; 8];
+    address.copy_from_slice(&descriptor[7..]);
+
+    Ok(AcpiGenericAddress::from_ffi(FfiAcpiGenericAddress {
+        space_id: descriptor[3],
+        bit_width: descriptor[4],
+        bit_offset: descriptor[5],
+        access_width: descriptor[6],
+        address: u64::from_le_bytes(address),
+    }))
+}
+
+/// Gets the register described by the buffer at `index` in a package
+fn package_register(package: &[AcpiValue], index: usize) -> Result<AcpiGenericAddress, AcpiError> {
+    let buffer = package
+        .get(index)
+        .ok_or(AcpiError::AmlPackageLimit)?
+        .as_buffer()
+        .ok_or(AcpiError::AmlOperandType)?;
+
+    generic_register(buffer)
+}
+
+/// Checks whether a register is the all-zero system memory register which firmware uses for registers that
+/// aren't implemented. Other address spaces can use address 0 for real registers, such as FFH counters on ARM.
+fn is_null_register(register: &AcpiGenericAddress) -> bool {
+    register.space_id == GasAddressSpace::SystemMemory
+        && register.address == 0
+        && register.bit_width == 0
+        && register.bit_offset == 0
+        && register.access_width == 0
+}
+
+/// Gets the register described by the buffer at `index` in a package,
+/// or [`None`] if it's the null register because the register isn't implemented
+fn package_optional_register(
+    package: &[AcpiValue],
+    index: usize,
+) -> Result<Option<AcpiGenericAddress>, AcpiError> {
+    let register = package_register(package, index)?;
+
+    if is_null_register(&register) {
+        Ok(None)
+    } else {
+        Ok(Some(register))
+    }
+}
+
+/// The interrupt controller entry in the MADT which matches a processor
+#[derive(Debug, Clone, Copy)]
+pub enum ProcessorInterruptController {
+    /// A [`ProcessorLocalApic`][MadtRecord::ProcessorLocalApic] record
+    LocalApic {
+        /// The processor's local APIC ID
+        apic_id: u8,
+        /// Flags about the processor and APIC
+        flags: ApicFlags,
+    },
+    /// A [`ProcessorLocalX2Apic`][MadtRecord::ProcessorLocalX2Apic] record
+    LocalX2Apic {
+        /// The processor's local x2APIC ID
+        x2apic_id: u32,
+        /// Flags about the processor and x2APIC
+        flags: ApicFlags,
+    },
+    /// A [`GicCpuInterface`][MadtRecord::GicCpuInterface] record
+    Gicc {
+        /// The GIC's identifier for the processor's CPU interface
+        cpu_interface_number: u32,
+        /// The processor's MPIDR value
+        mpidr: u64,
+        /// Flags about the processor and its interrupts
+        flags: GiccFlags,
+    },
+}
+
+impl ProcessorInterruptController {
+    /// Finds the record in the MADT whose ACPI processor UID is `uid`
+    fn find(madt: &Madt, uid: u32) -> Option<Self> {
+        madt.records().find_map(|record| match record {
+            MadtRecord::ProcessorLocalApic {
+                processor_id,
+                apic_id,
+                flags,
+            } if u32::from(processor_id) == uid => Some(Self::LocalApic { apic_id, flags }),
+            MadtRecord::ProcessorLocalX2Apic {
+                id, flags, acpi_id, ..
+            } if acpi_id == uid => Some(Self::LocalX2Apic {
+                x2apic_id: id,
+                flags,
+            }),
+            MadtRecord::GicCpuInterface {
+                cpu_interface_number,
+                acpi_processor_uid,
+                flags,
+                mpidr,
+                ..
+            } if acpi_processor_uid == uid => Some(Self::Gicc {
+                cpu_interface_number,
+                mpidr,
+                flags,
+            }),
+            _ => None,
+        })
+    }
+}
+
+/// A performance state, from a processor's `_PSS` package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerformanceState {
+    /// The core frequency in MHz
+    pub core_frequency: u32,
+    /// The typical power dissipation in mW
+    pub power: u32,
+    /// The worst case time the processor is unavailable while changing to this state, in microseconds
+    pub latency: u32,
+    /// The worst case time bus masters are blocked while changing to this state, in microseconds
+    pub bus_master_latency: u32,
+    /// The value to write to the control register from `_PCT` to enter this state
+    pub control: u32,
+    /// The value read from the status register from `_PCT` once this state has been entered
+    pub status: u32,
+}
+
+impl PerformanceState {
+    fn from_pss(package: &[AcpiValue]) -> Result<Self, AcpiError> {
+        Ok(Self {
+            core_frequency: package_u32(package, 0)?,
+            power: package_u32(package, 1)?,
+            latency: package_u32(package, 2)?,
+            bus_master_latency: package_u32(package, 3)?,
+            control: package_u32(package, 4)?,
+            status: package_u32(package, 5)?,
+        })
+    }
+}
+
+/// The registers used to change performance state, from a processor's `_PCT` package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerformanceControl {
+    /// The register which [`PerformanceState::control`] is written to
+    pub control: AcpiGenericAddress,
+    /// The register which [`PerformanceState::status`] is read from
+    pub status: AcpiGenericAddress,
+}
+
+/// How the processors in a dependency domain must coordinate state changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coordination {
+    /// The OS must make the change on all the processors in the domain
+    SoftwareAll,
+    /// The OS may make the change on any one processor in the domain
+    SoftwareAny,
+    /// The OS makes the change on every processor, and the hardware coordinates them
+    HardwareAll,
+    /// An unknown coordination type
+    Unknown(u32),
+}
+
+impl Coordination {
+    fn from_u32(value: u32) -> Self {
+        match value {
+            0xFC => Self::SoftwareAll,
+            0xFD => Self::SoftwareAny,
+            0xFE => Self::HardwareAll,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// The processors which share performance state control, from a processor's `_PSD` package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerformanceDomain {
+    /// The number of the dependency domain
+    pub domain: u32,
+    /// How the processors in the domain coordinate
+    pub coordination: Coordination,
+    /// The number of processors in the domain
+    pub processor_count: u32,
+}
+
+impl PerformanceDomain {
+    fn from_psd(package: &[AcpiValue]) -> Result<Self, AcpiError> {
+        Ok(Self {
+            domain: package_u32(package, 2)?,
+            coordination: Coordination::from_u32(package_u32(package, 3)?),
+            processor_count: package_u32(package, 4)?,
+        })
+    }
+}
+
+/// A processor power state, from a processor's `_CST` package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CState {
+    /// The register to read to enter the state, or an FFH register describing a native instruction such as `MWAIT`
+    pub register: AcpiGenericAddress,
+    /// The type of state, from 1 for C1 to 3 for C3
+    pub state_type: u8,
+    /// The worst case latency to enter and exit the state, in microseconds
+    pub latency: u32,
+    /// The average power consumption in the state, in mW
+    pub power: u32,
+}
+
+impl CState {
+    fn from_cst(package: &[AcpiValue]) -> Result<Self, AcpiError> {
+        Ok(Self {
+            register: package_register(package, 0)?,
+            state_type: u8::try_from(package_integer(package, 1)?)
+                .map_err(|_| AcpiError::AmlNumericOverflow)?,
+            latency: package_u32(package, 2)?,
+            power: package_u32(package, 3)?,
+        })
+    }
+}
+
+/// How a [`LowPowerIdleState`] is entered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LpiEntryMethod {
+    /// Enter the state by reading the register, or by the native instruction an FFH register describes
+    Register(AcpiGenericAddress),
+    /// Enter the state by adding the value to the entry method of the state chosen for the child level
+    Integer(u64),
+}
+
+/// A low power idle state, from a processor's or processor container's `_LPI` package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowPowerIdleState {
+    /// The minimum time the state must be held for to save power, in microseconds
+    pub min_residency: u32,
+    /// The worst case time to wake from the state, in microseconds
+    pub worst_case_wakeup_latency: u32,
+    /// Whether the state can be used
+    pub enabled: bool,
+    /// Architecture-specific flags describing the context lost in the state
+    pub arch_context_lost_flags: u32,
+    /// The frequency of the residency counter in Hz, or 0 if it counts at an unknown rate
+    pub residency_counter_frequency: u32,
+    /// The index of the deepest parent state which can be entered together with this one, where 0 means none
+    pub enabled_parent_state: u32,
+    /// How the state is entered
+    pub entry_method: LpiEntryMethod,
+    /// A register counting the time spent in the state
+    pub residency_counter: Option<AcpiGenericAddress>,
+    /// A register counting the number of times the state has been entered
+    pub usage_counter: Option<AcpiGenericAddress>,
+    /// The state's human-readable name
+    pub name: String,
+}
+
+impl LowPowerIdleState {
+    fn from_lpi(package: &[AcpiValue]) -> Result<Self, AcpiError> {
+        let entry_method = match package.get(6).ok_or(AcpiError::AmlPackageLimit)? {
+            AcpiValue::Integer(value) => LpiEntryMethod::Integer(*value),
+            AcpiValue::Buffer(buffer) => LpiEntryMethod::Register(generic_register(buffer)?),
+            _ => return Err(AcpiError::AmlOperandType),
+        };
+
+        Ok(Self {
+            min_residency: package_u32(package, 0)?,
+            worst_case_wakeup_latency: package_u32(package, 1)?,
+            enabled: package_integer(package, 2)? & 1 != 0,
+            arch_context_lost_flags: package_u32(package, 3)?,
+            residency_counter_frequency: package_u32(package, 4)?,
+            enabled_parent_state: package_u32(package, 5)?,
+            entry_method,
+            residency_counter: package_optional_register(package, 7)?,
+            usage_counter: package_optional_register(package, 8)?,
+            name: package
+                .get(9)
+                .and_then(AcpiValue::as_str)
+                .unwrap_or_default()
+                .into(),
+        })
+    }
+}
+
+/// The low power idle states of one level of the processor hierarchy, from an `_LPI` package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowPowerIdleStates {
+    /// The ID of the hierarchy level, used with some entry methods
+    pub level_id: u64,
+    /// The states, from shallowest to deepest
+    pub states: Vec<LowPowerIdleState>,
+}
+
+/// An entry in a `_CPC` package, which is either a fixed value or a register holding the value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpcValue {
+    /// A fixed value
+    Integer(u64),
+    /// A register to read or write the value from
+    Register(AcpiGenericAddress),
+}
+
+impl CpcValue {
+    /// Gets the entry at `index`, or [`None`] if it's past the end of the package
+    /// or is a register which isn't implemented
+    fn from_package(package: &[AcpiValue], index: usize) -> Result<Option<Self>, AcpiError> {
+        match package.get(index) {
+            None => Ok(None),
+            Some(AcpiValue::Integer(value)) => Ok(Some(Self::Integer(*value))),
+            Some(AcpiValue::Buffer(buffer)) => {
+                let register = generic_register(buffer)?;
+                Ok((!is_null_register(&register)).then_some(Self::Register(register)))
+            }
+            Some(_) => Err(AcpiError::AmlOperandType),
+        }
+    }
+
+    fn required(package: &[AcpiValue], index: usize) -> Result<Self, AcpiError> {
+        Self::from_package(package, index)?.ok_or(AcpiError::AmlPackageLimit)
+    }
+}
+
+/// A processor's collaborative processor performance control registers, from its `_CPC` package.
+/// Entries added in later revisions, or which aren't implemented, are [`None`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollaborativePerformanceControl {
+    /// The revision of the `_CPC` package
+    pub revision: u64,
+    /// The highest performance level the processor can reach, as an abstract unitless value
+    pub highest_performance: CpcValue,
+    /// The highest performance level the processor can sustain
+    pub nominal_performance: CpcValue,
+    /// The lowest performance level at which power savings are nonlinear
+    pub lowest_nonlinear_performance: CpcValue,
+    /// The lowest performance level the processor can reach
+    pub lowest_performance: CpcValue,
+    /// The performance level the platform currently guarantees
+    pub guaranteed_performance: Option<CpcValue>,
+    /// Where the OS writes the performance level it wants
+    pub desired_performance: CpcValue,
+    /// Where the OS writes the lowest performance level the platform may choose
+    pub minimum_performance: Option<CpcValue>,
+    /// Where the OS writes the highest performance level the platform may choose
+    pub maximum_performance: Option<CpcValue>,
+    /// Where the OS writes the deviation below the desired performance it will tolerate
+    pub performance_reduction_tolerance: Option<CpcValue>,
+    /// Where the OS writes the time window over which delivered performance is measured, in milliseconds
+    pub time_window: Option<CpcValue>,
+    /// The minimum time before the performance counters wrap, in seconds
+    pub counter_wraparound_time: Option<CpcValue>,
+    /// A counter which increments at the reference performance rate
+    pub reference_performance_counter: CpcValue,
+    /// A counter which increments at the rate of the performance delivered
+    pub delivered_performance_counter: CpcValue,
+    /// Flags which are set when delivered performance was limited below the desired level
+    pub performance_limited: CpcValue,
+    /// Where the OS writes 1 to enable CPPC
+    pub cppc_enable: Option<CpcValue>,
+    /// Where the OS writes 1 to let the platform select performance autonomously
+    pub autonomous_selection_enable: Option<CpcValue>,
+    /// Where the OS writes the window over which autonomous selection measures activity
+    pub autonomous_activity_window: Option<CpcValue>,
+    /// Where the OS writes its preference between energy efficiency and performance
+    pub energy_performance_preference: Option<CpcValue>,
+    /// The performance level which the reference performance counter corresponds to
+    pub reference_performance: Option<CpcValue>,
+    /// The frequency of the lowest performance level in MHz
+    pub lowest_frequency: Option<CpcValue>,
+    /// The frequency of the nominal performance level in MHz
+    pub nominal_frequency: Option<CpcValue>,
+}
+
+impl CollaborativePerformanceControl {
+    fn from_cpc(package: &[AcpiValue]) -> Result<Self, AcpiError> {
+        let optional = |index| CpcValue::from_package(package, index);
+        let required = |index| CpcValue::required(package, index);
+
+        // The first entry is the number of entries, and the second is the revision
+        Ok(Self {
+            revision: package_integer(package, 1)?,
+            highest_performance: required(2)?,
+            nominal_performance: required(3)?,
+            lowest_nonlinear_performance: required(4)?,
+            lowest_performance: required(5)?,
+            guaranteed_performance: optional(6)?,
+            desired_performance: required(7)?,
+            minimum_performance: optional(8)?,
+            maximum_performance: optional(9)?,
+            performance_reduction_tolerance: optional(10)?,
+            time_window: optional(11)?,
+            counter_wraparound_time: optional(12)?,
+            reference_performance_counter: required(13)?,
+            delivered_performance_counter: required(14)?,
+            performance_limited: required(15)?,
+            cppc_enable: optional(16)?,
+            autonomous_selection_enable: optional(17)?,
+            autonomous_activity_window: optional(18)?,
+            energy_performance_preference: optional(19)?,
+            reference_performance: optional(20)?,
+            lowest_frequency: optional(21)?,
+            nominal_frequency: optional(22)?,
+        })
+    }
+}
+
+/// A processor, found with [`processors`].
+///
+/// Processors are either declared with the legacy `Processor` AML object, or as `ACPI0007` devices.
+///
+/// [`processors`]: AcpicaOperation::processors
+#[derive(Debug, Clone, Copy)]
+pub struct Processor {
+    handle: AcpiHandle,
+    uid: u32,
+    interrupt_controller: Option<ProcessorInterruptController>,
+}
+
+impl Processor {
+    /// Gets the processor's object in the AML namespace
+    #[must_use]
+    pub fn handle(&self) -> AcpiHandle {
+        self.handle
+    }
+
+    /// Gets the UID which matches the processor to its MADT entry.
+    /// This is the processor ID of a `Processor` object, or the `_UID` of a processor device.
+    #[must_use]
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// Gets the processor's entry in the MADT, or [`None`] if there isn't one
+    #[must_use]
+    pub fn interrupt_controller(&self) -> Option<ProcessorInterruptController> {
+        self.interrupt_controller
+    }
+
+    /// Returns [`None`] if evaluating `path` raised [`AcpiError::NotFound`]
+    fn evaluate_optional_package(&self, path: &str) -> Result<Option<Vec<AcpiValue>>, AcpiError> {
+        match self.handle.evaluate_package(path, &[]) {
+            Ok(package) => Ok(Some(package)),
+            Err(AcpiError::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads the processor's performance states from `_PSS`, from highest to lowest performance.
+    /// Returns an empty list if the processor doesn't support performance states.
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlPackageLimit`] or [`AcpiError::AmlOperandType`] if the package is malformed
+    /// * Any error raised while evaluating `_PSS`
+    pub fn performance_states(&self) -> Result<Vec<PerformanceState>, AcpiError> {
+        let Some(package) = self.evaluate_optional_package("_PSS")? else {
+            return Ok(Vec::new());
+        };
+
+        (0..package.len())
+            .map(|i| PerformanceState::from_pss(package_package(&package, i)?))
+            .collect()
+    }
+
+    /// Reads the index in [`performance_states`] of the highest performance state the OS may currently use,
+    /// using `_PPC`. The processor notifies with `0x80` when this changes.
+    ///
+    /// [`performance_states`]: Processor::performance_states
+    ///
+    /// # Errors
+    /// Any error raised while evaluating `_PPC`, other than it not existing
+    pub fn performance_limit(&self) -> Result<usize, AcpiError> {
+        match self.handle.evaluate_integer("_PPC", &[]) {
+            Ok(index) => usize::try_from(index).map_err(|_| AcpiError::AmlNumericOverflow),
+            Err(AcpiError::NotFound) => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Reads the registers used to change performance state from `_PCT`
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlInvalidResourceType`] or [`AcpiError::AmlBufferLimit`] if a register is malformed
+    /// * Any error raised while evaluating `_PCT`
+    pub fn performance_control(&self) -> Result<PerformanceControl, AcpiError> {
+        let package = self.handle.evaluate_package("_PCT", &[])?;
+
+        Ok(PerformanceControl {
+            control: package_register(&package, 0)?,
+            status: package_register(&package, 1)?,
+        })
+    }
+
+    /// Reads the processors the performance state must be coordinated with from `_PSD`,
+    /// or [`None`] if the processor doesn't depend on any others
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlPackageLimit`] or [`AcpiError::AmlOperandType`] if the package is malformed
+    /// * Any error raised while evaluating `_PSD`
+    pub fn performance_domain(&self) -> Result<Option<PerformanceDomain>, AcpiError> {
+        self.evaluate_optional_package("_PSD")?
+            .map(|package| PerformanceDomain::from_psd(package_package(&package, 0)?))
+            .transpose()
+    }
+
+    /// Reads the processor's power states from `_CST`, from shallowest to deepest.
+    /// Returns an empty list if the processor doesn't have `_CST`.
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlPackageLimit`], [`AcpiError::AmlOperandType`] or [`AcpiError::AmlInvalidResourceType`]
+    ///   if the package is malformed
+    /// * Any error raised while evaluating `_CST`
+    pub fn c_states(&self) -> Result<Vec<CState>, AcpiError> {
+        let Some(package) = self.evaluate_optional_package("_CST")? else {
+            return Ok(Vec::new());
+        };
+
+        // The first element is the number of states
+        (1..package.len())
+            .map(|i| CState::from_cst(package_package(&package, i)?))
+            .collect()
+    }
+
+    /// Reads the processor's low power idle states from `_LPI`, or [`None`] if it doesn't have `_LPI`.
+    /// The parent processor containers may have their own `_LPI` states, which can be read from [`parent`].
+    ///
+    /// [`parent`]: AcpiHandle::parent
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlPackageLimit`], [`AcpiError::AmlOperandType`] or [`AcpiError::AmlInvalidResourceType`]
+    ///   if the package is malformed
+    /// * Any error raised while evaluating `_LPI`
+    pub fn low_power_idle_states(&self) -> Result<Option<LowPowerIdleStates>, AcpiError> {
+        let Some(package) = self.evaluate_optional_package("_LPI")? else {
+            return Ok(None);
+        };
+
+        // The first three elements are the revision, level ID and number of states
+        let states = (3..package.len())
+            .map(|i| LowPowerIdleState::from_lpi(package_package(&package, i)?))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(LowPowerIdleStates {
+            level_id: package_integer(&package, 1)?,
+            states,
+        }))
+    }
+
+    /// Reads the processor's collaborative processor performance control registers from `_CPC`,
+    /// or [`None`] if it doesn't support CPPC
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlPackageLimit`], [`AcpiError::AmlOperandType`] or [`AcpiError::AmlInvalidResourceType`]
+    ///   if the package is malformed
+    /// * Any error raised while evaluating `_CPC`
+    pub fn collaborative_performance_control(
+        &self,
+    ) -> Result<Option<CollaborativePerformanceControl>, AcpiError> {
+        self.evaluate_optional_package("_CPC")?
+            .map(|package| CollaborativePerformanceControl::from_cpc(&package))
+            .transpose()
+    }
+}
+
+/// Gets the UID of a processor device from its `_UID`, which may be an integer or a numeric string
+fn device_uid(device: AcpiHandle) -> Result<u32, AcpiError> {
+    let uid = device
+        .evaluate("_UID", &[])?
+        .ok_or(AcpiError::AmlNoReturnValue)?
+        .to_value();
+
+    match uid {
+        AcpiValue::Integer(uid) => u32::try_from(uid).map_err(|_| AcpiError::AmlNumericOverflow),
+        AcpiValue::String(uid) => uid.parse().map_err(|_| AcpiError::AmlOperandValue),
+        _ => Err(AcpiError::AmlOperandType),
+    }
+}
+
+/// Gets the processor ID of a legacy `Processor` object
+#[allow(clippy::missing_panics_doc)]
+fn processor_object_uid(processor: AcpiHandle) -> Result<u32, AcpiError> {
+    let value = processor.evaluate_typed("", &[], AcpiObjectType::Processor)?;
+
+    let AcpiObject::Processor(processor) = value.object() else {
+        unreachable!("ACPICA should have checked the type of the return value");
+    };
+
+    Ok(processor.proc_id())
+}
+
+impl AcpicaOperation<true, true, true, true> {
+    /// Finds the present processors in the namespace, both `Processor` objects and `ACPI0007` devices,
+    /// and matches them to their entries in the MADT
+    ///
+    /// # Errors
+    /// Any error raised while reading a processor's UID
+    #[allow(clippy::missing_panics_doc)]
+    pub fn processors(&self) -> Result<Vec<Processor>, AcpiError> {
+        let mut processor_objects = Vec::new();
+
+        self.walk_namespace(
+            self.root(),
+            AcpiObjectType::Processor,
+            u32::MAX,
+            |handle, _| {
+                if handle.status().is_ok_and(|status| status.present()) {
+                    processor_objects.push(handle);
+                }
+
+                WalkDecision::<()>::Continue
+            },
+            |_, _| WalkDecision::Continue,
+        )
+        // AcpiWalkNamespace only returns BadParameter as an error, so if this fails it is this library's fault.
+        .unwrap();
+
+        let madt = self.table(*b"APIC").map(|_| self.madt());
+
+        let mut processors = Vec::new();
+        let mut add = |handle, uid| {
+            processors.push(Processor {
+                handle,
+                uid,
+                interrupt_controller: madt
+                    .as_ref()
+                    .and_then(|madt| ProcessorInterruptController::find(madt, uid)),
+            });
+        };
+
+        for handle in processor_objects {
+            add(handle, processor_object_uid(handle)?);
+        }
+
+        for handle in self.find_devices(PROCESSOR_HARDWARE_ID) {
+            add(handle, device_uid(handle)?);
+        }
+
+        Ok(processors)
+    }
+}
+
+/// Tests that generic register descriptors are decoded from `_PCT`-style buffers
+#[test]
+fn test_generic_register() {
+    let buffer = [
+        0x82, 0x0C, 0x00, 0x7F, 0x40, 0x00, 0x04, 0x99, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x79, 0x00,
+    ];
+
+    let register = generic_register(&buffer).unwrap();
+    assert_eq!(register.space_id, GasAddressSpace::FunctionalFixedHardware);
+    assert_eq!(register.bit_width, 64);
+    assert_eq!(
+        register.access_width,
+        AcpiGenericAddress::ACCESS_WIDTH_8_BYTES
+    );
+    assert_eq!(register.address, 0x199);
+
+    assert_eq!(
+        generic_register(&buffer[..10]),
+        Err(AcpiError::AmlBufferLimit)
+    );
+}
+
+/// Tests that only the all-zero system memory register is treated as unimplemented in a `_CPC` package
+#[test]
+fn test_cpc_null_register() {
+    use alloc::vec;
+
+    let ffh_counter = vec![
+        0x82, 0x0C, 0x00, 0x7F, 0x40, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x79, 0x00,
+    ];
+    let mut null_register = vec![0; ffh_counter.len()];
+    null_register[..3].copy_from_slice(&ffh_counter[..3]);
+    null_register[15..].copy_from_slice(&ffh_counter[15..]);
+
+    let package = [
+        AcpiValue::Buffer(ffh_counter),
+        AcpiValue::Buffer(null_register),
+    ];
+
+    let Ok(CpcValue::Register(register)) = CpcValue::required(&package, 0) else {
+        panic!("FFH register at address 0 should be decoded");
+    };
+    assert_eq!(register.space_id, GasAddressSpace::FunctionalFixedHardware);
+    assert_eq!(register.address, 0);
+
+    assert_eq!(CpcValue::from_package(&package, 1), Ok(None));
+    assert_eq!(
+        CpcValue::required(&package, 1),
+        Err(AcpiError::AmlPackageLimit)
+    );
+}
@@ -0,0 +1,290 @@
+//! Code to move the system into and out of sleep states, and to reset it
+
+use core::{convert::Infallible, ptr::addr_of};
+
+use crate::{
+    bindings::{
+        consts::{
+            ACPI_FADT_RESET_REGISTER, ACPI_STATE_S1, ACPI_STATE_S2, ACPI_STATE_S3, ACPI_STATE_S4,
+            ACPI_STATE_S5,
+        },
+        functions::{
+            AcpiEnterSleepState, AcpiEnterSleepStatePrep, AcpiGetSleepTypeData,
+            AcpiLeaveSleepState, AcpiLeaveSleepStatePrep, AcpiOsWritePciConfiguration, AcpiReset,
+            AcpiSetFirmwareWakingVector,
+        },
+        statics::AcpiGbl_FACS,
+        types::FfiAcpiPciId,
+    },
+    status::AcpiError,
+    types::{AcpiPhysicalAddress, GasAddressSpace},
+    AcpicaOperation,
+};
+
+/// A system sleep state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SleepState {
+    /// Power on suspend. The CPU caches are flushed and the CPUs stop executing, but all context is kept.
+    S1,
+    /// Like [`S1`][SleepState::S1], but the CPUs and caches lose power
+    S2,
+    /// Suspend to RAM. Only memory keeps its contents, and the firmware jumps to the waking vector on wake.
+    S3,
+    /// Suspend to disk. The OS must save the contents of memory before entering this state.
+    S4,
+    /// Soft off
+    S5,
+}
+
+impl SleepState {
+    const ALL: [Self; 5] = [Self::S1, Self::S2, Self::S3, Self::S4, Self::S5];
+
+    pub(crate) fn to_u8(self) -> u8 {
+        match self {
+            Self::S1 => ACPI_STATE_S1,
+            Self::S2 => ACPI_STATE_S2,
+            Self::S3 => ACPI_STATE_S3,
+            Self::S4 => ACPI_STATE_S4,
+            Self::S5 => ACPI_STATE_S5,
+        }
+    }
+}
+
+impl AcpicaOperation<true, true, true, true> {
+    /// Gets the sleep states which the system supports, i.e. those with an `_Sx` object in the AML namespace
+    pub fn supported_sleep_states(&self) -> impl Iterator<Item = SleepState> {
+        SleepState::ALL.into_iter().filter(|state| {
+            let mut type_a = 0;
+            let mut type_b = 0;
+
+            // SAFETY: ACPICA is fully initialized and the pointers are valid for writes
+            unsafe {
+                AcpiGetSleepTypeData(
+                    state.to_u8(),
+                    core::ptr::addr_of_mut!(type_a),
+                    core::ptr::addr_of_mut!(type_b),
+                )
+            }
+            .as_result()
+            .is_ok()
+        })
+    }
+
+    /// Prepares the system to enter a sleep state by evaluating the `_PTS` and `_SST` methods.
+    /// This must be called with interrupts enabled, as the AML code may need to wait on events.
+    ///
+    /// The returned [`PreparedSleep`] is used to enter the sleep state once the OS has saved any state it needs
+    /// and disabled interrupts.
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if the system doesn't support the sleep state
+    /// * Any error raised while evaluating the AML methods
+    pub fn prepare_sleep(&self, state: SleepState) -> Result<PreparedSleep, AcpiError> {
+        // SAFETY: ACPICA is fully initialized
+        unsafe { AcpiEnterSleepStatePrep(state.to_u8()).as_result()? };
+
+        Ok(PreparedSleep { state })
+    }
+
+    /// Sets the address which the firmware jumps to when the system wakes from [`S3`][SleepState::S3].
+    ///
+    /// `vector` is jumped to in real mode. If `vector_64` is given and the firmware supports it,
+    /// it is jumped to in protected or long mode instead.
+    ///
+    /// # Errors
+    /// * [`AcpiError::NoAcpiTables`] if the system has no FACS
+    pub fn set_firmware_waking_vector(
+        &self,
+        vector: AcpiPhysicalAddress,
+        vector_64: Option<AcpiPhysicalAddress>,
+    ) -> Result<(), AcpiError> {
+        // `AcpiSetFirmwareWakingVector` silently does nothing if there's no FACS
+        // SAFETY: ACPICA sets this when the tables are loaded, and never changes it afterwards
+        if unsafe { addr_of!(AcpiGbl_FACS).read() }.is_null() {
+            return Err(AcpiError::NoAcpiTables);
+        }
+
+        let vector_64 = vector_64.unwrap_or(AcpiPhysicalAddress::NULL);
+
+        // SAFETY: ACPICA is fully initialized
+        unsafe { AcpiSetFirmwareWakingVector(vector.0, vector_64.0).as_result() }
+    }
+
+    /// Prepares to return to the working state after the system has woken from a sleep state by jumping to the
+    /// firmware waking vector, rather than returning from [`PreparedSleep::enter`].
+    ///
+    /// # Safety
+    /// * This must be called with interrupts disabled, after the system has woken from `state`
+    ///
+    /// # Errors
+    /// Any error raised while restoring the sleep registers or evaluating AML methods
+    pub unsafe fn prepare_wake(&self, state: SleepState) -> Result<PendingWake, AcpiError> {
+        // SAFETY: The caller guarantees that interrupts are disabled
+        unsafe { AcpiLeaveSleepStatePrep(state.to_u8()).as_result()? };
+
+        Ok(PendingWake { state })
+    }
+
+    /// Turns the system off by entering [`S5`][SleepState::S5], which `sleep` must have been prepared for with
+    /// [`prepare_sleep`]. Any drivers which need to save state should have been shut down before this is called.
+    ///
+    /// [`prepare_sleep`]: AcpicaOperation::prepare_sleep
+    ///
+    /// # Safety
+    /// * Interrupts must be disabled on the current CPU, and all other CPUs must be stopped
+    ///
+    /// # Errors
+    /// * [`AcpiError::BadParameter`] if `sleep` was prepared for a state other than [`S5`][SleepState::S5]
+    /// * [`AcpiError::NoHardwareResponse`] if the system didn't turn off
+    /// * Any error raised while writing to the sleep registers or evaluating AML methods
+    pub unsafe fn power_off(&self, sleep: PreparedSleep) -> Result<Infallible, AcpiError> {
+        if sleep.state != SleepState::S5 {
+            return Err(AcpiError::BadParameter);
+        }
+
+        // SAFETY: The caller guarantees that interrupts are disabled and other CPUs are stopped
+        let _wake = unsafe { sleep.enter()? };
+
+        Err(AcpiError::NoHardwareResponse)
+    }
+}
+
+impl<const TL: bool, const E: bool, const I: bool> AcpicaOperation<true, TL, E, I> {
+    /// Resets the system by writing the FADT's reset value to its reset register.
+    /// The reset register can be in system memory, system I/O or PCI configuration space.
+    ///
+    /// This only returns if the system couldn't be reset, in which case the OS should fall back to another method,
+    /// such as the keyboard controller.
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotExist`] if the FADT has no reset register, or its flags say that the register isn't supported
+    /// * [`AcpiError::Support`] if the reset register is in an address space other than memory, I/O or PCI configuration space
+    /// * [`AcpiError::NoHardwareResponse`] if the reset value was written but the system didn't reset
+    /// * Any error raised while writing to the reset register
+    pub fn reset(&self) -> Result<Infallible, AcpiError> {
+        let fadt = self.fadt();
+
+        let (Some(register), Some(value)) = (fadt.reset_register(), fadt.reset_value()) else {
+            return Err(AcpiError::NotExist);
+        };
+
+        if fadt.flags() & ACPI_FADT_RESET_REGISTER == 0 || register.address == 0 {
+            return Err(AcpiError::NotExist);
+        }
+
+        match register.space_id {
+            GasAddressSpace::SystemMemory | GasAddressSpace::SystemIO => {
+                // SAFETY: The tables are initialized, so ACPICA has parsed the FADT
+                unsafe { AcpiReset().as_result()? };
+            }
+            GasAddressSpace::PciConfigurationSpace => {
+                // ACPICA only supports memory and I/O reset registers, so the write is done here.
+                // The address is split into words of device, function and offset, on segment 0 bus 0.
+                #[allow(clippy::cast_possible_truncation)]
+                let mut id = FfiAcpiPciId {
+                    segment: 0,
+                    bus: 0,
+                    device: (register.address >> 32) as u16,
+                    function: (register.address >> 16) as u16,
+                };
+                #[allow(clippy::cast_possible_truncation)]
+                let offset = u32::from(register.address as u16);
+
+                // SAFETY: `id` is valid for reads, and the FADT says that the write resets the system
+                unsafe {
+                    AcpiOsWritePciConfiguration(
+                        core::ptr::addr_of_mut!(id),
+                        offset,
+                        u64::from(value),
+                        8,
+                    )
+                    .as_result()?;
+                };
+            }
+            _ => return Err(AcpiError::Support),
+        }
+
+        Err(AcpiError::NoHardwareResponse)
+    }
+}
+
+/// A sleep state which the system is ready to enter, returned by [`prepare_sleep`]
+///
+/// [`prepare_sleep`]: AcpicaOperation::prepare_sleep
+#[derive(Debug)]
+#[must_use = "The sleep state should be entered or the preparation cancelled"]
+pub struct PreparedSleep {
+    state: SleepState,
+}
+
+impl PreparedSleep {
+    /// Gets the sleep state which will be entered
+    #[must_use]
+    pub fn state(&self) -> SleepState {
+        self.state
+    }
+
+    /// Enters the sleep state.
+    ///
+    /// For [`S1`][SleepState::S1] and [`S2`][SleepState::S2], this returns once the system has woken.
+    /// For [`S3`][SleepState::S3], the firmware jumps to the waking vector set by [`set_firmware_waking_vector`]
+    /// on wake, and for [`S4`][SleepState::S4] and [`S5`][SleepState::S5] the system turns off, so this only returns
+    /// if entering the state failed.
+    ///
+    /// Once this returns successfully, ACPICA has prepared to return to the working state, and
+    /// [`PendingWake::wake`] should be called after interrupts are re-enabled.
+    ///
+    /// [`set_firmware_waking_vector`]: AcpicaOperation::set_firmware_waking_vector
+    ///
+    /// # Safety
+    /// * Interrupts must be disabled on the current CPU, and all other CPUs must be stopped
+    /// * For [`S3`][SleepState::S3], the OS must have saved any state it needs to resume from the waking vector
+    ///
+    /// # Errors
+    /// Any error raised while writing to the sleep registers or evaluating AML methods
+    pub unsafe fn enter(self) -> Result<PendingWake, AcpiError> {
+        // SAFETY: The caller guarantees that interrupts are disabled
+        unsafe {
+            AcpiEnterSleepState(self.state.to_u8()).as_result()?;
+            AcpiLeaveSleepStatePrep(self.state.to_u8()).as_result()?;
+        }
+
+        Ok(PendingWake { state: self.state })
+    }
+
+    /// Cancels the transition to the sleep state by evaluating the `_WAK` method, which undoes the effects of `_PTS`.
+    ///
+    /// # Errors
+    /// Any error raised while evaluating AML methods
+    pub fn cancel(self) -> Result<(), AcpiError> {
+        // SAFETY: The state was prepared by `prepare_sleep`
+        unsafe { AcpiLeaveSleepState(self.state.to_u8()).as_result() }
+    }
+}
+
+/// A wake from a sleep state which still needs to be completed by calling [`wake`]
+///
+/// [`wake`]: PendingWake::wake
+#[derive(Debug)]
+#[must_use = "The wake should be completed by calling `wake`"]
+pub struct PendingWake {
+    state: SleepState,
+}
+
+impl PendingWake {
+    /// Gets the sleep state which the system is waking from
+    #[must_use]
+    pub fn state(&self) -> SleepState {
+        self.state
+    }
+
+    /// Returns the system to the working state by evaluating the `_SST` and `_WAK` methods and re-enabling runtime GPEs.
+    /// This must be called with interrupts enabled.
+    ///
+    /// # Errors
+    /// Any error raised while evaluating AML methods
+    pub fn wake(self) -> Result<(), AcpiError> {
+        // SAFETY: `AcpiLeaveSleepStatePrep` has been called for this state
+        unsafe { AcpiLeaveSleepState(self.state.to_u8()).as_result() }
+    }
+}
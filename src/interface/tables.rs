@@ -4,7 +4,7 @@ use crate::{
         types::tables::FfiAcpiTableHeader,
     },
     status::AcpiError,
-    types::tables::{fadt::Fadt, mcfg::Mcfg, AcpiTableHeader, Madt, Uefi},
+    types::tables::{ecdt::Ecdt, fadt::Fadt, mcfg::Mcfg, AcpiTableHeader, Madt, Uefi},
     AcpicaOperation,
 };
 
@@ -145,4 +145,14 @@ impl<const TL: bool, const E: bool, const I: bool> AcpicaOperation<true, TL, E,
         // SAFETY: The signature is "APIC" so the table is an MADT
         unsafe { Some(Mcfg::from_ffi(&*ptr)) }
     }
+
+    /// Gets the ECDT, if the system has an embedded controller which needs to be available before the namespace is initialized
+    #[must_use]
+    pub fn ecdt(&self) -> Option<Ecdt<'_>> {
+        let ptr = core::ptr::from_ref(self.table(*b"ECDT")?.as_ffi());
+
+        let ptr = ptr.cast();
+        // SAFETY: The signature is "ECDT" so the table is an ECDT
+        unsafe { Some(Ecdt::from_ffi(&*ptr)) }
+    }
 }
@@ -0,0 +1,636 @@
+//! Code to read thermal zones and run an active cooling policy, turning fans on and off as trip points are crossed
+
+use alloc::{sync::Arc, vec::Vec};
+use core::fmt::Display;
+use log::warn;
+use spin::Mutex;
+
+use crate::{
+    devices::{AcpiHandle, DeviceState, WalkDecision},
+    notify::{NotifyHandlerGuard, NotifyHandlerKind},
+    power::{PowerManager, PowerResource},
+    status::AcpiError,
+    types::object::{AcpiArgument, AcpiObjectType},
+    AcpicaOperation,
+};
+
+/// The hardware ID of a fan
+const FAN_HARDWARE_ID: &str = "PNP0C0B";
+
+/// The number of `_ACx` trip points a thermal zone can have
+const MAX_ACTIVE_TRIP_POINTS: usize = 10;
+
+/// The notification sent when a thermal zone's temperature has crossed a trip point
+const NOTIFY_THERMAL_STATUS: u8 = 0x80;
+/// The notification sent when a thermal zone's trip points have changed
+const NOTIFY_THERMAL_TRIP_POINTS: u8 = 0x81;
+/// The notification sent when the devices in a thermal zone's `_ALx` or `_PSL` lists have changed
+const NOTIFY_THERMAL_DEVICE_LISTS: u8 = 0x82;
+
+/// A temperature, stored in tenths of a kelvin as it is by AML
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Temperature(u32);
+
+impl Temperature {
+    /// The offset between kelvin and degrees Celsius, in tenths of a degree
+    const ZERO_CELSIUS: i64 = 2732;
+
+    /// Creates a temperature from tenths of a kelvin
+    #[must_use]
+    pub const fn from_deci_kelvin(deci_kelvin: u32) -> Self {
+        Self(deci_kelvin)
+    }
+
+    /// Gets the temperature in tenths of a kelvin
+    #[must_use]
+    pub const fn deci_kelvin(self) -> u32 {
+        self.0
+    }
+
+    /// Gets the temperature in tenths of a degree Celsius
+    #[must_use]
+    pub const fn deci_celsius(self) -> i64 {
+        self.0 as i64 - Self::ZERO_CELSIUS
+    }
+
+    /// Gets the temperature in thousandths of a degree Celsius
+    #[must_use]
+    pub const fn milli_celsius(self) -> i64 {
+        self.deci_celsius() * 100
+    }
+
+    fn from_integer(value: u64) -> Result<Self, AcpiError> {
+        u32::try_from(value)
+            .map(Self)
+            .map_err(|_| AcpiError::AmlNumericOverflow)
+    }
+}
+
+impl Display for Temperature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let deci_celsius = self.deci_celsius();
+        let sign = if deci_celsius < 0 { "-" } else { "" };
+        let deci_celsius = deci_celsius.unsigned_abs();
+
+        write!(f, "{sign}{}.{}°C", deci_celsius / 10, deci_celsius % 10)
+    }
+}
+
+/// Which cooling policy the OS prefers, passed to a thermal zone's `_SCP` method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoolingPolicy {
+    /// Cool the system by turning on fans, preferring performance over noise
+    Active,
+    /// Cool the system by reducing performance, preferring silence over performance
+    Passive,
+}
+
+/// One of a thermal zone's `_ACx` trip points, above which the devices in the matching `_ALx` list should be on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveTripPoint {
+    /// The temperature of the trip point
+    pub temperature: Temperature,
+    /// The devices or power resources to turn on above the trip point
+    pub devices: Vec<AcpiHandle>,
+}
+
+/// The parameters a thermal zone gives for passive cooling
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassiveCooling {
+    /// The temperature above which passive cooling should be used, from `_PSV`
+    pub temperature: Temperature,
+    /// The first thermal constant, from `_TC1`
+    pub tc1: u32,
+    /// The second thermal constant, from `_TC2`
+    pub tc2: u32,
+    /// How often the temperature should be sampled while cooling, in tenths of a second, from `_TSP`
+    pub sampling_period: u32,
+    /// The processors whose performance should be reduced, from `_PSL`
+    pub devices: Vec<AcpiHandle>,
+}
+
+impl PassiveCooling {
+    /// Calculates the change in performance the ACPI passive cooling equation calls for,
+    /// given the current and previous temperature samples.
+    /// The result is a percentage: negative values mean performance should be reduced.
+    #[must_use]
+    pub fn performance_delta(&self, current: Temperature, previous: Temperature) -> i64 {
+        let trend = i64::from(current.0) - i64::from(previous.0);
+        let excess = i64::from(current.0) - i64::from(self.temperature.0);
+
+        -(i64::from(self.tc1) * trend + i64::from(self.tc2) * excess)
+    }
+}
+
+/// A thermal zone, found with [`thermal_zones`]
+///
+/// [`thermal_zones`]: AcpicaOperation::thermal_zones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThermalZone(AcpiHandle);
+
+impl ThermalZone {
+    /// Gets the thermal zone's object in the AML namespace
+    #[must_use]
+    pub fn handle(&self) -> AcpiHandle {
+        self.0
+    }
+
+    fn read_temperature(self, path: &str) -> Result<Temperature, AcpiError> {
+        Temperature::from_integer(self.0.evaluate_integer(path, &[])?)
+    }
+
+    fn read_optional(self, path: &str) -> Result<Option<u64>, AcpiError> {
+        match self.0.evaluate_integer(path, &[]) {
+            Ok(value) => Ok(Some(value)),
+            Err(AcpiError::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn read_optional_temperature(self, path: &str) -> Result<Option<Temperature>, AcpiError> {
+        self.read_optional(path)?
+            .map(Temperature::from_integer)
+            .transpose()
+    }
+
+    fn read_devices(self, path: &str) -> Result<Vec<AcpiHandle>, AcpiError> {
+        self.0
+            .evaluate_package(path, &[])?
+            .iter()
+            .map(|device| device.as_reference().ok_or(AcpiError::AmlOperandType))
+            .collect()
+    }
+
+    /// Reads the zone's current temperature, using `_TMP`
+    ///
+    /// # Errors
+    /// Any error raised while evaluating `_TMP`
+    pub fn temperature(&self) -> Result<Temperature, AcpiError> {
+        self.read_temperature("_TMP")
+    }
+
+    /// Reads the temperature at which the system should shut down, using `_CRT`
+    ///
+    /// # Errors
+    /// Any error raised while evaluating `_CRT`, other than it not existing
+    pub fn critical(&self) -> Result<Option<Temperature>, AcpiError> {
+        self.read_optional_temperature("_CRT")
+    }
+
+    /// Reads the temperature at which the system should enter S4 if it's supported, using `_HOT`
+    ///
+    /// # Errors
+    /// Any error raised while evaluating `_HOT`, other than it not existing
+    pub fn hot(&self) -> Result<Option<Temperature>, AcpiError> {
+        self.read_optional_temperature("_HOT")
+    }
+
+    /// Reads the zone's passive cooling parameters, or [`None`] if it doesn't have a `_PSV` trip point
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlOperandType`] if `_PSL` contains something other than references
+    /// * Any error raised while evaluating `_PSV`, `_TC1`, `_TC2`, `_TSP` or `_PSL`
+    pub fn passive_cooling(&self) -> Result<Option<PassiveCooling>, AcpiError> {
+        let Some(temperature) = self.read_optional_temperature("_PSV")? else {
+            return Ok(None);
+        };
+
+        let constant = |path| {
+            u32::try_from(self.0.evaluate_integer(path, &[])?)
+                .map_err(|_| AcpiError::AmlNumericOverflow)
+        };
+
+        Ok(Some(PassiveCooling {
+            temperature,
+            tc1: constant("_TC1")?,
+            tc2: constant("_TC2")?,
+            sampling_period: constant("_TSP")?,
+            devices: self.read_devices("_PSL")?,
+        }))
+    }
+
+    /// Reads the zone's active trip points from `_AC0` upwards, with the devices listed in the matching `_ALx`.
+    /// `_AC0` is the hottest trip point, and the list stops at the first missing `_ACx`.
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlOperandType`] if an `_ALx` contains something other than references
+    /// * Any error raised while evaluating an `_ACx` or `_ALx`, other than `_ACx` not existing
+    pub fn active_trip_points(&self) -> Result<Vec<ActiveTripPoint>, AcpiError> {
+        let mut trip_points = Vec::new();
+
+        for i in 0..MAX_ACTIVE_TRIP_POINTS {
+            let Some(temperature) = self.read_optional_temperature(&alloc::format!("_AC{i}"))?
+            else {
+                break;
+            };
+
+            trip_points.push(ActiveTripPoint {
+                temperature,
+                devices: self.read_devices(&alloc::format!("_AL{i}"))?,
+            });
+        }
+
+        Ok(trip_points)
+    }
+
+    /// Reads how often the zone should be polled in tenths of a second, using `_TZP`.
+    /// Returns [`None`] if the zone doesn't need polling because it sends notifications when trip points are crossed.
+    ///
+    /// # Errors
+    /// Any error raised while evaluating `_TZP`, other than it not existing
+    pub fn polling_interval(&self) -> Result<Option<u64>, AcpiError> {
+        Ok(self
+            .read_optional("_TZP")?
+            .filter(|&interval| interval != 0))
+    }
+
+    /// Tells the firmware which cooling policy the OS prefers, using `_SCP`.
+    /// The firmware may then change the zone's trip points and send a notification.
+    ///
+    /// # Errors
+    /// * [`AcpiError::NotFound`] if the zone doesn't have an `_SCP` method
+    /// * Any error raised while evaluating `_SCP`
+    pub fn set_cooling_policy(&self, policy: CoolingPolicy) -> Result<(), AcpiError> {
+        let mode = match policy {
+            CoolingPolicy::Active => 0,
+            CoolingPolicy::Passive => 1,
+        };
+
+        self.0
+            .evaluate("_SCP", &[AcpiArgument::Integer(mode)])
+            .map(|_| ())
+    }
+
+    /// Starts an active cooling policy for the zone, which switches cooling devices using `power`.
+    /// See [`ThermalPolicy`] for details.
+    ///
+    /// # Errors
+    /// * Any error raised while evaluating `_SCP`, other than it not existing
+    /// * Any error raised while reading the zone's trip points or installing the notify handler
+    pub fn start_policy<F>(
+        &self,
+        policy: CoolingPolicy,
+        power: Arc<PowerManager>,
+        callback: F,
+    ) -> Result<ThermalPolicy, AcpiError>
+    where
+        F: Fn(ThermalZone, ThermalReading) + Send + Sync + 'static,
+    {
+        ThermalPolicy::new(*self, policy, power, callback)
+    }
+}
+
+/// The most severe trip point which a thermal zone's temperature has reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TripPoint {
+    /// The `_CRT` trip point. The system should shut down immediately.
+    Critical,
+    /// The `_HOT` trip point. The system should enter S4 if it's supported.
+    Hot,
+    /// The `_PSV` trip point. The OS should reduce processor performance.
+    Passive,
+    /// The `_ACx` trip point with the given index, which is the hottest active trip point reached, as `_AC0` is the
+    /// hottest. The policy has turned on the devices in `_ALx` and every later `_ALy`, but not those in `_AL0` to
+    /// `_AL(x-1)`.
+    Active(usize),
+}
+
+/// The result of updating a [`ThermalPolicy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThermalReading {
+    /// The zone's temperature
+    pub temperature: Temperature,
+    /// The most severe trip point reached, or [`None`] if the zone is below all its trip points
+    pub trip_point: Option<TripPoint>,
+}
+
+/// The trip points read by a [`ThermalPolicy`]
+#[derive(Debug)]
+struct TripPoints {
+    critical: Option<Temperature>,
+    hot: Option<Temperature>,
+    passive: Option<Temperature>,
+    active: Vec<ActiveTripPoint>,
+}
+
+impl TripPoints {
+    fn read(zone: ThermalZone) -> Result<Self, AcpiError> {
+        Ok(Self {
+            critical: zone.critical()?,
+            hot: zone.hot()?,
+            passive: zone.passive_cooling()?.map(|p| p.temperature),
+            active: zone.active_trip_points()?,
+        })
+    }
+}
+
+/// The trip points and device states tracked by a [`ThermalPolicy`]
+#[derive(Debug)]
+struct PolicyState {
+    trip_points: TripPoints,
+    /// The cooling devices which the policy has turned on
+    devices_on: Vec<AcpiHandle>,
+}
+
+/// The cooling devices to switch after a [`PolicyState`] has been updated
+struct CoolingChanges {
+    turn_off: Vec<AcpiHandle>,
+    turn_on: Vec<AcpiHandle>,
+}
+
+impl PolicyState {
+    /// Works out which cooling devices should be on at `temperature`, and records them as on.
+    /// The devices aren't switched, so that the lock on the state doesn't need to be held while AML runs.
+    fn update(&mut self, temperature: Temperature) -> (ThermalReading, CoolingChanges) {
+        let trip_points = &self.trip_points;
+        let reached = |trip: Option<Temperature>| trip.is_some_and(|trip| temperature >= trip);
+
+        // A device can be listed for several trip points, so it's only turned off once none of them are reached
+        let mut wanted = Vec::new();
+        for trip_point in trip_points
+            .active
+            .iter()
+            .filter(|t| temperature >= t.temperature)
+        {
+            for device in &trip_point.devices {
+                if !wanted.contains(device) {
+                    wanted.push(*device);
+                }
+            }
+        }
+
+        let mut turn_off = Vec::new();
+        self.devices_on.retain(|&device| {
+            let keep = wanted.contains(&device);
+            if !keep {
+                turn_off.push(device);
+            }
+
+            keep
+        });
+
+        let turn_on: Vec<_> = wanted
+            .into_iter()
+            .filter(|device| !self.devices_on.contains(device))
+            .collect();
+        self.devices_on.extend_from_slice(&turn_on);
+
+        let trip_point = if reached(trip_points.critical) {
+            Some(TripPoint::Critical)
+        } else if reached(trip_points.hot) {
+            Some(TripPoint::Hot)
+        } else if reached(trip_points.passive) {
+            Some(TripPoint::Passive)
+        } else {
+            trip_points
+                .active
+                .iter()
+                .position(|t| temperature >= t.temperature)
+                .map(TripPoint::Active)
+        };
+
+        let reading = ThermalReading {
+            temperature,
+            trip_point,
+        };
+
+        (reading, CoolingChanges { turn_off, turn_on })
+    }
+}
+
+/// An active cooling policy for a thermal zone, started with [`ThermalZone::start_policy`].
+///
+/// Whenever the policy is updated, it reads the zone's temperature and turns on the devices listed for each active
+/// trip point which has been reached, turning them off again once the temperature falls. The policy is updated when
+/// the zone sends a notification, and the callback is then called with the result. Zones which have a
+/// [`polling_interval`] don't send notifications, so [`poll`] should be called at that interval instead.
+///
+/// Cooling devices are switched through a [`PowerManager`], so that power resources shared with other devices stay
+/// on while they're needed. Power resources in an `_ALx` list are acquired and released, and devices such as fans
+/// are moved between [`D0`] and [`D3Cold`].
+///
+/// Handling the critical, hot and passive trip points is left to the caller. Cooling devices are left in their
+/// current state and the notify handler is removed when this struct is dropped.
+///
+/// [`polling_interval`]: ThermalZone::polling_interval
+/// [`poll`]: ThermalPolicy::poll
+/// [`D0`]: DeviceState::D0
+/// [`D3Cold`]: DeviceState::D3Cold
+#[derive(Debug)]
+pub struct ThermalPolicy {
+    zone: ThermalZone,
+    state: Arc<Mutex<PolicyState>>,
+    power: Arc<PowerManager>,
+    _notify_handler: NotifyHandlerGuard,
+}
+
+impl ThermalPolicy {
+    fn new<F>(
+        zone: ThermalZone,
+        policy: CoolingPolicy,
+        power: Arc<PowerManager>,
+        callback: F,
+    ) -> Result<Self, AcpiError>
+    where
+        F: Fn(ThermalZone, ThermalReading) + Send + Sync + 'static,
+    {
+        match zone.set_cooling_policy(policy) {
+            Ok(()) | Err(AcpiError::NotFound) => {}
+            Err(e) => return Err(e),
+        }
+
+        let state = Arc::new(Mutex::new(PolicyState {
+            trip_points: TripPoints::read(zone)?,
+            devices_on: Vec::new(),
+        }));
+
+        let handler_state = Arc::clone(&state);
+        let handler_power = Arc::clone(&power);
+        let notify_handler = zone.0.install_notify_handler(
+            NotifyHandlerKind::Device,
+            move |handle, notification| {
+                let zone = ThermalZone(handle);
+
+                match notification.to_u8() {
+                    NOTIFY_THERMAL_STATUS => {}
+                    NOTIFY_THERMAL_TRIP_POINTS | NOTIFY_THERMAL_DEVICE_LISTS => {
+                        match TripPoints::read(zone) {
+                            Ok(trip_points) => handler_state.lock().trip_points = trip_points,
+                            Err(e) => {
+                                warn!("Failed to read thermal zone trip points: {e:?}");
+                                return;
+                            }
+                        }
+                    }
+                    _ => return,
+                }
+
+                match update_policy(zone, &handler_state, &handler_power) {
+                    Ok(reading) => callback(zone, reading),
+                    Err(e) => warn!("Failed to update thermal zone: {e:?}"),
+                }
+            },
+        )?;
+
+        let policy = Self {
+            zone,
+            state,
+            power,
+            _notify_handler: notify_handler,
+        };
+
+        // Bring the cooling devices into line with the current temperature
+        policy.poll()?;
+
+        Ok(policy)
+    }
+
+    /// Gets the thermal zone which the policy is running for
+    #[must_use]
+    pub fn zone(&self) -> ThermalZone {
+        self.zone
+    }
+
+    /// Gets the cooling devices which the policy has turned on
+    #[must_use]
+    pub fn devices_on(&self) -> Vec<AcpiHandle> {
+        self.state.lock().devices_on.clone()
+    }
+
+    /// Updates the policy, returning the zone's temperature and the trip point reached.
+    /// The callback is not called.
+    ///
+    /// # Errors
+    /// Any error raised while evaluating `_TMP`
+    pub fn poll(&self) -> Result<ThermalReading, AcpiError> {
+        update_policy(self.zone, &self.state, &self.power)
+    }
+}
+
+/// Reads the zone's temperature, and turns cooling devices on or off to match the active trip points reached.
+/// The state is only locked while working out which devices to switch, not while they're switched.
+fn update_policy(
+    zone: ThermalZone,
+    state: &Mutex<PolicyState>,
+    power: &PowerManager,
+) -> Result<ThermalReading, AcpiError> {
+    let temperature = zone.temperature()?;
+    let (reading, changes) = state.lock().update(temperature);
+
+    for device in changes.turn_off {
+        if let Err(e) = set_cooling_device(power, device, false) {
+            warn!("Failed to turn off cooling device: {e:?}");
+
+            let mut state = state.lock();
+            if !state.devices_on.contains(&device) {
+                state.devices_on.push(device);
+            }
+        }
+    }
+
+    for device in changes.turn_on {
+        if let Err(e) = set_cooling_device(power, device, true) {
+            warn!("Failed to turn on cooling device: {e:?}");
+            state.lock().devices_on.retain(|&d| d != device);
+        }
+    }
+
+    Ok(reading)
+}
+
+/// Turns a device or power resource from an `_ALx` list on or off
+fn set_cooling_device(power: &PowerManager, device: AcpiHandle, on: bool) -> Result<(), AcpiError> {
+    match PowerResource::new(device) {
+        Ok(resource) if on => power.acquire(resource),
+        Ok(resource) => power.release(resource),
+        Err(AcpiError::Type) => Fan(device).set_on(power, on),
+        Err(e) => Err(e),
+    }
+}
+
+/// A fan (`PNP0C0B`), found with [`fans`] or in a thermal zone's `_ALx` lists
+///
+/// [`fans`]: AcpicaOperation::fans
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fan(AcpiHandle);
+
+impl Fan {
+    /// Gets the fan's device in the AML namespace
+    #[must_use]
+    pub fn handle(&self) -> AcpiHandle {
+        self.0
+    }
+
+    /// Checks whether the fan is on, which is when it's in [`D0`][DeviceState::D0]
+    ///
+    /// # Errors
+    /// Any error which [`PowerManager::device_state`] can return
+    pub fn is_on(&self, power: &PowerManager) -> Result<bool, AcpiError> {
+        Ok(power.device_state(self.0)? == DeviceState::D0)
+    }
+
+    /// Turns the fan on or off by moving it into [`D0`] or [`D3Cold`] with [`PowerManager::set_device_state`],
+    /// which references the power resources in its `_PR0` while it's on.
+    ///
+    /// [`D0`]: DeviceState::D0
+    /// [`D3Cold`]: DeviceState::D3Cold
+    ///
+    /// # Errors
+    /// Any error which [`PowerManager::set_device_state`] can return
+    pub fn set_on(&self, power: &PowerManager, on: bool) -> Result<(), AcpiError> {
+        let state = if on {
+            DeviceState::D0
+        } else {
+            DeviceState::D3Cold
+        };
+
+        power.set_device_state(self.0, state)
+    }
+}
+
+impl AcpicaOperation<true, true, true, true> {
+    /// Finds the thermal zones in the namespace
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn thermal_zones(&self) -> Vec<ThermalZone> {
+        let mut zones = Vec::new();
+
+        self.walk_namespace(
+            self.root(),
+            AcpiObjectType::ThermalZone,
+            u32::MAX,
+            |handle, _| {
+                zones.push(ThermalZone(handle));
+                WalkDecision::<()>::Continue
+            },
+            |_, _| WalkDecision::Continue,
+        )
+        // AcpiWalkNamespace only returns BadParameter as an error, so if this fails it is this library's fault.
+        .unwrap();
+
+        zones
+    }
+
+    /// Finds the present fans (`PNP0C0B`) in the namespace
+    #[must_use]
+    pub fn fans(&self) -> Vec<Fan> {
+        self.find_devices(FAN_HARDWARE_ID)
+            .into_iter()
+            .map(Fan)
+            .collect()
+    }
+}
+
+/// Tests the conversions from deci-Kelvin
+#[test]
+fn test_temperature_conversions() {
+    let temperature = Temperature::from_deci_kelvin(3232);
+
+    assert_eq!(temperature.deci_celsius(), 500);
+    assert_eq!(temperature.milli_celsius(), 50_000);
+    assert_eq!(alloc::format!("{temperature}"), "50.0°C");
+    assert_eq!(
+        alloc::format!("{}", Temperature::from_deci_kelvin(2727)),
+        "-0.5°C"
+    );
+}
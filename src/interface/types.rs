@@ -2,6 +2,7 @@
 
 mod generic_address;
 pub mod object;
+pub mod resource;
 pub mod tables;
 
 use core::{
@@ -1,4 +1,10 @@
-use crate::bindings::types::FfiAcpiGenericAddress;
+use crate::{
+    bindings::{
+        functions::{AcpiRead, AcpiWrite},
+        types::FfiAcpiGenericAddress,
+    },
+    status::AcpiError,
+};
 
 /// A type of address space that a [generic address structure][AcpiGenericAddress] can point into
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -72,13 +78,13 @@ impl GasAddressSpace {
             0x09 => Self::GenericSerialBus,
             0x0A => Self::PlatformCommunicationsChannel,
             0x0B => Self::PlatformRuntimeMechanism,
-            0x0C => Self::FunctionalFixedHardware,
+            0x7F => Self::FunctionalFixedHardware,
             s => Self::Other(s),
         }
     }
 
-    /// Gets the [`GasAddressSpace`] from its [`u8`] representation
-    fn to_u8(self) -> u8 {
+    /// Gets the [`u8`] representation of the [`GasAddressSpace`]
+    pub(crate) fn to_u8(self) -> u8 {
         match self {
             Self::SystemMemory => 0x00,
             Self::SystemIO => 0x01,
@@ -92,7 +98,7 @@ impl GasAddressSpace {
             Self::GenericSerialBus => 0x09,
             Self::PlatformCommunicationsChannel => 0x0A,
             Self::PlatformRuntimeMechanism => 0x0B,
-            Self::FunctionalFixedHardware => 0x0C,
+            Self::FunctionalFixedHardware => 0x7F,
             Self::Other(s) => s,
         }
     }
@@ -101,7 +107,7 @@ impl GasAddressSpace {
 /// GAS - Generic Address Structure
 ///
 /// This struct represents an address in some address space - that could be main memory, port I/O, PCI configuration space, etc.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct AcpiGenericAddress {
     /// What address space to access for this value
     pub space_id: GasAddressSpace,
@@ -146,4 +152,67 @@ impl AcpiGenericAddress {
             address: value.address,
         }
     }
+
+    pub(crate) fn to_ffi(self) -> FfiAcpiGenericAddress {
+        FfiAcpiGenericAddress {
+            space_id: self.space_id.to_u8(),
+            bit_width: self.bit_width,
+            bit_offset: self.bit_offset,
+            access_width: self.access_width,
+            address: self.address,
+        }
+    }
+
+    /// Reads the value of the register using ACPICA's `AcpiRead` function.
+    /// The value is shifted and masked according to [`bit_offset`] and [`bit_width`].
+    ///
+    /// [`bit_offset`]: AcpiGenericAddress::bit_offset
+    /// [`bit_width`]: AcpiGenericAddress::bit_width
+    ///
+    /// # Safety
+    /// * The OS interface must have been registered with [`register_interface`]
+    /// * Reading the register must be sound, e.g. it must not be memory which is mapped elsewhere with a different type
+    ///
+    /// [`register_interface`]: crate::register_interface
+    ///
+    /// # Errors
+    /// * [`AcpiError::Support`] if the register isn't in system memory or system I/O space
+    /// * [`AcpiError::BadAddress`] if the address is 0
+    pub unsafe fn read(&self) -> Result<u64, AcpiError> {
+        let mut value = 0;
+        let mut register = self.to_ffi();
+
+        // SAFETY: The caller guarantees that the read is sound
+        unsafe {
+            AcpiRead(
+                core::ptr::addr_of_mut!(value),
+                core::ptr::addr_of_mut!(register),
+            )
+            .as_result()?;
+        };
+
+        Ok(value)
+    }
+
+    /// Writes a value to the register using ACPICA's `AcpiWrite` function.
+    /// The value is shifted and masked according to [`bit_offset`] and [`bit_width`].
+    ///
+    /// [`bit_offset`]: AcpiGenericAddress::bit_offset
+    /// [`bit_width`]: AcpiGenericAddress::bit_width
+    ///
+    /// # Safety
+    /// * The OS interface must have been registered with [`register_interface`]
+    /// * Writing the register must be sound, i.e. it must not have memory-safety related side-effects
+    ///
+    /// [`register_interface`]: crate::register_interface
+    ///
+    /// # Errors
+    /// * [`AcpiError::Support`] if the register isn't in system memory or system I/O space
+    /// * [`AcpiError::BadAddress`] if the address is 0
+    pub unsafe fn write(&self, value: u64) -> Result<(), AcpiError> {
+        let mut register = self.to_ffi();
+
+        // SAFETY: The caller guarantees that the write is sound
+        unsafe { AcpiWrite(value, core::ptr::addr_of_mut!(register)).as_result() }
+    }
 }
@@ -1,25 +1,73 @@
 //! The [`AcpiObject`] type
 
-use core::ffi::CStr;
+use core::{ffi::CStr, fmt::Debug, marker::PhantomData};
 
+use alloc::{string::String, vec::Vec};
 use log::{debug, warn};
 
-use crate::bindings::{
-    consts::{
-        ACPI_TYPE_ANY, ACPI_TYPE_BUFFER, ACPI_TYPE_INTEGER, ACPI_TYPE_LOCAL_REFERENCE,
-        ACPI_TYPE_PACKAGE, ACPI_TYPE_POWER, ACPI_TYPE_PROCESSOR, ACPI_TYPE_STRING,
-    },
-    types::{
-        object::{FfiAcpiObject, FfiAcpiObjectType},
-        FfiAcpiHandle, FfiAcpiIoAddress,
+use crate::{
+    bindings::{
+        buffer::AcpiAllocatedBuffer,
+        consts::{
+            ACPI_TYPE_ANY, ACPI_TYPE_BUFFER, ACPI_TYPE_BUFFER_FIELD, ACPI_TYPE_DDB_HANDLE,
+            ACPI_TYPE_DEBUG_OBJECT, ACPI_TYPE_DEVICE, ACPI_TYPE_EVENT, ACPI_TYPE_FIELD_UNIT,
+            ACPI_TYPE_INTEGER, ACPI_TYPE_LOCAL_REFERENCE, ACPI_TYPE_METHOD, ACPI_TYPE_MUTEX,
+            ACPI_TYPE_PACKAGE, ACPI_TYPE_POWER, ACPI_TYPE_PROCESSOR, ACPI_TYPE_REGION,
+            ACPI_TYPE_STRING, ACPI_TYPE_THERMAL,
+        },
+        types::{
+            object::{
+                FfiAcpiObject, FfiAcpiObjectType, FfiObjectTypeBuffer, FfiObjectTypeInteger,
+                FfiObjectTypePackage, FfiObjectTypeReference, FfiObjectTypeString,
+            },
+            FfiAcpiHandle, FfiAcpiIoAddress, FfiAcpiObjectList,
+        },
     },
+    devices::AcpiHandle,
+    status::AcpiError,
 };
 
-/// A package [`AcpiObject`]
-#[derive(Debug)]
-pub struct AcpiObjectPackage {
+/// A package [`AcpiObject`]. The elements of the package can be accessed using [`iter`] or [`get`].
+///
+/// [`iter`]: AcpiObjectPackage::iter
+/// [`get`]: AcpiObjectPackage::get
+#[derive(Debug, Clone, Copy)]
+pub struct AcpiObjectPackage<'a> {
     count: u32,
     elements: *mut FfiAcpiObject,
+    _p: PhantomData<&'a FfiAcpiObject>,
+}
+
+impl<'a> AcpiObjectPackage<'a> {
+    /// Gets the number of elements in the package
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    /// Checks whether the package has no elements
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Gets the element at index `i`, or [`None`] if `i` is out of bounds
+    #[must_use]
+    pub fn get(&self, i: usize) -> Option<AcpiObject<'a>> {
+        if i < self.len() {
+            // SAFETY: The package was created from a valid ACPICA object, so `elements` points to
+            // `count` valid objects which live for `'a`
+            Some(unsafe { AcpiObject::from_ffi(self.elements.add(i)) })
+        } else {
+            None
+        }
+    }
+
+    /// Iterates over the elements of the package
+    pub fn iter(&self) -> impl Iterator<Item = AcpiObject<'a>> {
+        let package = *self;
+        (0..self.len()).filter_map(move |i| package.get(i))
+    }
 }
 
 /// A string [`AcpiObject`]
@@ -37,27 +85,75 @@ pub struct AcpiObjectBuffer {
 }
 
 /// A reference to another [`AcpiObject`]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct AcpiObjectReference {
     actual_type: FfiAcpiObjectType,
     handle: FfiAcpiHandle,
 }
 
+impl AcpiObjectReference {
+    /// Gets the type of the object being referred to
+    #[must_use]
+    pub fn actual_type(&self) -> AcpiObjectType {
+        AcpiObjectType::from_type_id(self.actual_type)
+    }
+
+    /// Gets a handle to the object being referred to
+    #[must_use]
+    pub fn handle(&self) -> AcpiHandle {
+        AcpiHandle(self.handle)
+    }
+}
+
 /// An [`AcpiObject`] describing the features of a processor
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AcpiObjectProcessor {
     proc_id: u32,
     pblk_address: FfiAcpiIoAddress,
     pblk_length: u32,
 }
 
+impl AcpiObjectProcessor {
+    /// Gets the processor's ID
+    #[must_use]
+    pub fn proc_id(&self) -> u32 {
+        self.proc_id
+    }
+
+    /// Gets the I/O port address of the processor's control registers
+    #[must_use]
+    pub fn pblk_address(&self) -> u64 {
+        self.pblk_address
+    }
+
+    /// Gets the length of the processor's control registers
+    #[must_use]
+    pub fn pblk_length(&self) -> u32 {
+        self.pblk_length
+    }
+}
+
 /// An [`AcpiObject`] describing a power resource
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AcpiObjectPowerResource {
     system_level: u32,
     resource_order: u32,
 }
 
+impl AcpiObjectPowerResource {
+    /// Gets the deepest system sleep level which the power resource needs to be on for
+    #[must_use]
+    pub fn system_level(&self) -> u32 {
+        self.system_level
+    }
+
+    /// Gets the order in which the power resource should be turned on and off, relative to other power resources
+    #[must_use]
+    pub fn resource_order(&self) -> u32 {
+        self.resource_order
+    }
+}
+
 /// An object used in the processing of ACPI data, mostly AML execution.
 #[derive(Debug)]
 pub enum AcpiObject<'a> {
@@ -71,7 +167,7 @@ pub enum AcpiObject<'a> {
     /// The object is a buffer of bytes
     Buffer(&'a [u8]),
     /// The object is a package containing other AML data
-    Package(AcpiObjectPackage),
+    Package(AcpiObjectPackage<'a>),
     /// The object is a reference to another [`AcpiObject`]
     Reference(AcpiObjectReference),
     /// The object describes the features of a processor
@@ -126,6 +222,7 @@ impl<'a> AcpiObject<'a> {
                 Self::Package(AcpiObjectPackage {
                     count: package.count,
                     elements: package.elements,
+                    _p: PhantomData,
                 })
             }
             _t @ ACPI_TYPE_LOCAL_REFERENCE => {
@@ -200,9 +297,127 @@ impl<'a> AcpiObject<'a> {
             AcpiObject::PowerResource(_) => AcpiObjectType::PowerResource,
         }
     }
+
+    /// Copies the object into an [`AcpiValue`], which does not borrow any data from ACPICA.
+    /// Packages are copied recursively.
+    #[must_use]
+    pub fn to_value(&self) -> AcpiValue {
+        match self {
+            AcpiObject::Any => AcpiValue::Any,
+            AcpiObject::Integer(i) => AcpiValue::Integer(*i),
+            AcpiObject::String(s) => AcpiValue::String(String::from(*s)),
+            AcpiObject::Buffer(b) => AcpiValue::Buffer(b.to_vec()),
+            AcpiObject::Package(p) => AcpiValue::Package(p.iter().map(|o| o.to_value()).collect()),
+            AcpiObject::Reference(r) => AcpiValue::Reference(r.handle()),
+            AcpiObject::Processor(p) => AcpiValue::Processor(*p),
+            AcpiObject::PowerResource(p) => AcpiValue::PowerResource(*p),
+        }
+    }
+}
+
+/// An owned copy of an [`AcpiObject`], created using [`AcpiObject::to_value`].
+///
+/// Unlike [`AcpiObject`], this type does not borrow from the buffer the object was returned in,
+/// so it can be stored and passed around freely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AcpiValue {
+    /// The object can be any type, or the type is not known.
+    /// From ACPICA comments: "\[Any\] is used to indicate a NULL package element or an unresolved named reference."
+    Any,
+    /// The object is an integer
+    Integer(u64),
+    /// The object is a string
+    String(String),
+    /// The object is a buffer of bytes
+    Buffer(Vec<u8>),
+    /// The object is a package containing other AML data
+    Package(Vec<AcpiValue>),
+    /// The object is a reference to an object in the AML namespace
+    Reference(AcpiHandle),
+    /// The object describes the features of a processor
+    Processor(AcpiObjectProcessor),
+    /// The object describes a power resource
+    PowerResource(AcpiObjectPowerResource),
+}
+
+impl AcpiValue {
+    /// Gets the type of the value
+    #[must_use]
+    pub fn get_type(&self) -> AcpiObjectType {
+        match self {
+            AcpiValue::Any => AcpiObjectType::Any,
+            AcpiValue::Integer(_) => AcpiObjectType::Integer,
+            AcpiValue::String(_) => AcpiObjectType::String,
+            AcpiValue::Buffer(_) => AcpiObjectType::Buffer,
+            AcpiValue::Package(_) => AcpiObjectType::Package,
+            AcpiValue::Reference(_) => AcpiObjectType::Reference,
+            AcpiValue::Processor(_) => AcpiObjectType::Processor,
+            AcpiValue::PowerResource(_) => AcpiObjectType::PowerResource,
+        }
+    }
+
+    /// Gets the value as an integer, if it is one
+    #[must_use]
+    pub fn as_integer(&self) -> Option<u64> {
+        match self {
+            AcpiValue::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Gets the value as a string, if it is one
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AcpiValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Gets the value as a buffer, if it is one
+    #[must_use]
+    pub fn as_buffer(&self) -> Option<&[u8]> {
+        match self {
+            AcpiValue::Buffer(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Gets the elements of the value, if it is a package
+    #[must_use]
+    pub fn as_package(&self) -> Option<&[AcpiValue]> {
+        match self {
+            AcpiValue::Package(p) => Some(p),
+            _ => None,
+        }
+    }
+
+    /// Gets the handle the value refers to, if it is a reference
+    #[must_use]
+    pub fn as_reference(&self) -> Option<AcpiHandle> {
+        match self {
+            AcpiValue::Reference(h) => Some(*h),
+            _ => None,
+        }
+    }
+}
+
+/// Gets the integer at `index` in a package returned by an AML method
+pub(crate) fn package_integer(package: &[AcpiValue], index: usize) -> Result<u64, AcpiError> {
+    package
+        .get(index)
+        .ok_or(AcpiError::AmlPackageLimit)?
+        .as_integer()
+        .ok_or(AcpiError::AmlOperandType)
 }
 
 /// A type of an [`AcpiObject`]. This is used when the type of data is known but the value is not.
+///
+/// Some types, such as [`Device`] or [`Method`], are only used for objects in the AML namespace,
+/// and never for the value of an [`AcpiObject`].
+///
+/// [`Device`]: AcpiObjectType::Device
+/// [`Method`]: AcpiObjectType::Method
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AcpiObjectType {
     /// The object can be any type, or the type is not known.
@@ -216,15 +431,58 @@ pub enum AcpiObjectType {
     Buffer,
     /// The object is a package containing other AML data
     Package,
+    /// The object is a field in an operation region
+    FieldUnit,
+    /// The object is a device
+    Device,
+    /// The object is an AML event, used for synchronisation between methods
+    Event,
+    /// The object is an AML method
+    Method,
+    /// The object is an AML mutex
+    Mutex,
+    /// The object is an operation region
+    Region,
     /// The object is a reference to another [`AcpiObject`]
     Reference,
     /// The object describes the features of a processor
     Processor,
     /// The object describes a power resource
     PowerResource,
+    /// The object is a thermal zone
+    ThermalZone,
+    /// The object is a field in a buffer
+    BufferField,
+    /// The object is a handle to a table loaded by AML code
+    DdbHandle,
+    /// The object is the AML debug object
+    DebugObject,
 }
 
 impl AcpiObjectType {
+    pub(crate) fn to_type_id(self) -> FfiAcpiObjectType {
+        match self {
+            Self::Any => ACPI_TYPE_ANY,
+            Self::Integer => ACPI_TYPE_INTEGER,
+            Self::String => ACPI_TYPE_STRING,
+            Self::Buffer => ACPI_TYPE_BUFFER,
+            Self::Package => ACPI_TYPE_PACKAGE,
+            Self::FieldUnit => ACPI_TYPE_FIELD_UNIT,
+            Self::Device => ACPI_TYPE_DEVICE,
+            Self::Event => ACPI_TYPE_EVENT,
+            Self::Method => ACPI_TYPE_METHOD,
+            Self::Mutex => ACPI_TYPE_MUTEX,
+            Self::Region => ACPI_TYPE_REGION,
+            Self::Reference => ACPI_TYPE_LOCAL_REFERENCE,
+            Self::Processor => ACPI_TYPE_PROCESSOR,
+            Self::PowerResource => ACPI_TYPE_POWER,
+            Self::ThermalZone => ACPI_TYPE_THERMAL,
+            Self::BufferField => ACPI_TYPE_BUFFER_FIELD,
+            Self::DdbHandle => ACPI_TYPE_DDB_HANDLE,
+            Self::DebugObject => ACPI_TYPE_DEBUG_OBJECT,
+        }
+    }
+
     pub(crate) fn from_type_id(id: FfiAcpiObjectType) -> Self {
         match id {
             _t @ ACPI_TYPE_ANY => Self::Any,
@@ -232,11 +490,343 @@ impl AcpiObjectType {
             _t @ ACPI_TYPE_STRING => Self::String,
             _t @ ACPI_TYPE_BUFFER => Self::Buffer,
             _t @ ACPI_TYPE_PACKAGE => Self::Package,
+            _t @ ACPI_TYPE_FIELD_UNIT => Self::FieldUnit,
+            _t @ ACPI_TYPE_DEVICE => Self::Device,
+            _t @ ACPI_TYPE_EVENT => Self::Event,
+            _t @ ACPI_TYPE_METHOD => Self::Method,
+            _t @ ACPI_TYPE_MUTEX => Self::Mutex,
+            _t @ ACPI_TYPE_REGION => Self::Region,
             _t @ ACPI_TYPE_LOCAL_REFERENCE => Self::Reference,
             _t @ ACPI_TYPE_PROCESSOR => Self::Processor,
             _t @ ACPI_TYPE_POWER => Self::PowerResource,
+            _t @ ACPI_TYPE_THERMAL => Self::ThermalZone,
+            _t @ ACPI_TYPE_BUFFER_FIELD => Self::BufferField,
+            _t @ ACPI_TYPE_DDB_HANDLE => Self::DdbHandle,
+            _t @ ACPI_TYPE_DEBUG_OBJECT => Self::DebugObject,
 
             _ => Self::Any,
         }
     }
 }
+
+/// An argument passed to an AML method by [`evaluate`].
+///
+/// The data is borrowed for the duration of the call - ACPICA copies it into its own objects
+/// before the method is executed. To build a list of arguments which owns its data, use [`AcpiArgumentList`].
+///
+/// [`evaluate`]: crate::devices::AcpiHandle::evaluate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcpiArgument<'a> {
+    /// An integer argument
+    Integer(u64),
+    /// A string argument. The string does not need to be null-terminated.
+    String(&'a str),
+    /// A buffer argument
+    Buffer(&'a [u8]),
+    /// A package argument containing other arguments
+    Package(&'a [AcpiArgument<'a>]),
+    /// A reference to an object in the AML namespace
+    Reference(AcpiHandle),
+}
+
+/// A list of arguments to an AML method, laid out in the format ACPICA expects.
+///
+/// The list owns copies of all its strings, buffers and nested packages, so the pointers passed to ACPICA
+/// stay valid for as long as the list exists. Arguments can be added using the builder methods:
+///
+/// ```ignore
+/// // Arguments to a `_DSM` method
+/// let args = AcpiArgumentList::new()
+///     .uuid("E5C937D0-3553-4D7A-9117-EA4D19C3434D")?
+///     .integer(revision)
+///     .integer(function)
+///     .package(AcpiArgumentList::new());
+///
+/// let value = handle.evaluate_with("_DSM", &args)?;
+/// ```
+#[derive(Default)]
+pub struct AcpiArgumentList {
+    objects: Vec<FfiAcpiObject>,
+    packages: Vec<Vec<FfiAcpiObject>>,
+    data: Vec<Vec<u8>>,
+}
+
+impl AcpiArgumentList {
+    /// Creates an empty argument list
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the number of arguments in the list
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Checks whether the list has no arguments
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Adds an argument to the end of the list, copying any data it borrows.
+    ///
+    /// # Panics
+    /// If a string, buffer or package is longer than [`u32::MAX`]
+    pub fn push(&mut self, argument: AcpiArgument) {
+        let object = match argument {
+            AcpiArgument::Integer(value) => FfiAcpiObject {
+                integer: FfiObjectTypeInteger {
+                    object_type: ACPI_TYPE_INTEGER,
+                    value,
+                },
+            },
+            AcpiArgument::String(s) => {
+                let (pointer, length) = self.store(s.as_bytes());
+
+                FfiAcpiObject {
+                    string: FfiObjectTypeString {
+                        object_type: ACPI_TYPE_STRING,
+                        length,
+                        pointer: pointer.cast(),
+                    },
+                }
+            }
+            AcpiArgument::Buffer(b) => {
+                let (pointer, length) = self.store(b);
+
+                FfiAcpiObject {
+                    buffer: FfiObjectTypeBuffer {
+                        object_type: ACPI_TYPE_BUFFER,
+                        length,
+                        pointer,
+                    },
+                }
+            }
+            AcpiArgument::Package(elements) => {
+                let mut package = Self::new();
+                for element in elements {
+                    package.push(*element);
+                }
+
+                self.package_object(package)
+            }
+            AcpiArgument::Reference(handle) => FfiAcpiObject {
+                reference: FfiObjectTypeReference {
+                    object_type: ACPI_TYPE_LOCAL_REFERENCE,
+                    actual_type: ACPI_TYPE_ANY,
+                    handle: handle.0,
+                },
+            },
+        };
+
+        self.objects.push(object);
+    }
+
+    /// Adds an argument to the end of the list. See [`push`][AcpiArgumentList::push].
+    #[must_use]
+    pub fn argument(mut self, argument: AcpiArgument) -> Self {
+        self.push(argument);
+        self
+    }
+
+    /// Adds an integer argument to the end of the list
+    #[must_use]
+    pub fn integer(self, value: u64) -> Self {
+        self.argument(AcpiArgument::Integer(value))
+    }
+
+    /// Adds a string argument to the end of the list
+    #[must_use]
+    pub fn string(self, value: &str) -> Self {
+        self.argument(AcpiArgument::String(value))
+    }
+
+    /// Adds a buffer argument to the end of the list
+    #[must_use]
+    pub fn buffer(self, value: &[u8]) -> Self {
+        self.argument(AcpiArgument::Buffer(value))
+    }
+
+    /// Adds a reference to an object in the AML namespace to the end of the list
+    #[must_use]
+    pub fn reference(self, handle: AcpiHandle) -> Self {
+        self.argument(AcpiArgument::Reference(handle))
+    }
+
+    /// Adds a package argument to the end of the list, containing the arguments in `elements`.
+    ///
+    /// # Panics
+    /// If `elements` contains more than [`u32::MAX`] arguments
+    #[must_use]
+    pub fn package(mut self, elements: Self) -> Self {
+        let object = self.package_object(elements);
+        self.objects.push(object);
+        self
+    }
+
+    /// Adds a 16 byte buffer argument to the end of the list, containing a UUID in the format produced by the AML `ToUUID` operator.
+    /// This is the format used by the first argument of `_DSM` and `_OSC` methods.
+    ///
+    /// `uuid` should be in the form `"xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx"`, where each `x` is a hex digit.
+    ///
+    /// # Errors
+    /// [`AcpiError::BadParameter`] if `uuid` is not a valid UUID string.
+    pub fn uuid(self, uuid: &str) -> Result<Self, AcpiError> {
+        let bytes = uuid_to_bytes(uuid).ok_or(AcpiError::BadParameter)?;
+        Ok(self.buffer(&bytes))
+    }
+
+    /// Copies `bytes` into storage owned by the list, returning a pointer to the copy and its length
+    fn store(&mut self, bytes: &[u8]) -> (*mut u8, u32) {
+        let length = bytes
+            .len()
+            .try_into()
+            .expect("Argument length should fit in a u32");
+
+        let mut copy = bytes.to_vec();
+        // Moving the vec into `self.data` does not move its heap allocation, so this pointer stays valid
+        let pointer = copy.as_mut_ptr();
+        self.data.push(copy);
+
+        (pointer, length)
+    }
+
+    /// Takes ownership of the storage of `package`, and creates a package object pointing to its elements
+    fn package_object(&mut self, package: Self) -> FfiAcpiObject {
+        let Self {
+            mut objects,
+            packages,
+            data,
+        } = package;
+
+        let count = objects
+            .len()
+            .try_into()
+            .expect("Package length should fit in a u32");
+        // Moving the vec into `self.packages` does not move its heap allocation, so this pointer stays valid
+        let elements = objects.as_mut_ptr();
+
+        self.packages.push(objects);
+        self.packages.extend(packages);
+        self.data.extend(data);
+
+        FfiAcpiObject {
+            package: FfiObjectTypePackage {
+                object_type: ACPI_TYPE_PACKAGE,
+                count,
+                elements,
+            },
+        }
+    }
+
+    /// Gets the list in the format ACPICA expects.
+    /// The returned list points into `self`, so it must not be used after `self` is dropped or modified.
+    pub(crate) fn as_ffi(&self) -> FfiAcpiObjectList {
+        FfiAcpiObjectList {
+            count: self
+                .objects
+                .len()
+                .try_into()
+                .expect("Argument list length should fit in a u32"),
+            // ACPICA copies the arguments rather than writing to them, so this cast is sound
+            pointer: self.objects.as_ptr().cast_mut(),
+        }
+    }
+}
+
+impl From<&[AcpiArgument<'_>]> for AcpiArgumentList {
+    fn from(arguments: &[AcpiArgument<'_>]) -> Self {
+        let mut list = Self::new();
+        for argument in arguments {
+            list.push(*argument);
+        }
+        list
+    }
+}
+
+impl Debug for AcpiArgumentList {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AcpiArgumentList")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Converts a UUID string into the byte order used by the AML `ToUUID` operator.
+/// The first three fields are stored little-endian, and the last two are stored big-endian.
+fn uuid_to_bytes(uuid: &str) -> Option<[u8; 16]> {
+    if !uuid.chars().all(|c| c == '-' || c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let groups: Vec<&str> = uuid.split('-').collect();
+    if !groups.iter().map(|g| g.len()).eq([8, 4, 4, 4, 12]) {
+        return None;
+    }
+
+    let mut bytes = [0; 16];
+    let mut i = 0;
+
+    for (n, group) in groups.iter().enumerate() {
+        let mut group_bytes = (0..group.len())
+            .step_by(2)
+            .map(|j| u8::from_str_radix(&group[j..j + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+
+        if n < 3 {
+            group_bytes.reverse();
+        }
+
+        bytes[i..i + group_bytes.len()].copy_from_slice(&group_bytes);
+        i += group_bytes.len();
+    }
+
+    Some(bytes)
+}
+
+/// The value returned from an AML method.
+///
+/// This struct owns the memory which ACPICA allocated for the return value, and frees it when dropped.
+/// The value can be accessed using the [`object`] method.
+///
+/// [`object`]: AcpiReturnObject::object
+#[derive(Debug)]
+pub struct AcpiReturnObject(AcpiAllocatedBuffer);
+
+impl AcpiReturnObject {
+    pub(crate) fn from_buffer(buffer: AcpiAllocatedBuffer) -> Self {
+        Self(buffer)
+    }
+
+    /// Gets the returned value. Any strings, buffers, or packages in the value borrow from this struct.
+    #[must_use]
+    pub fn object(&self) -> AcpiObject<'_> {
+        // SAFETY: ACPICA writes an `ACPI_OBJECT` at the start of the return buffer,
+        // followed by any data which the object points to.
+        unsafe { AcpiObject::from_ffi(self.0.as_ptr().cast()) }
+    }
+
+    /// Copies the returned value into an [`AcpiValue`], so that the buffer ACPICA allocated can be freed.
+    #[must_use]
+    pub fn to_value(&self) -> AcpiValue {
+        self.object().to_value()
+    }
+}
+
+/// Tests that [`uuid_to_bytes`] produces the same byte order as the AML `ToUUID` operator
+#[test]
+fn test_uuid_to_bytes() {
+    assert_eq!(
+        uuid_to_bytes("33DB4D5B-1FF7-4DCE-9563-0EC8CA6BEB1D"),
+        Some([
+            0x5B, 0x4D, 0xDB, 0x33, 0xF7, 0x1F, 0xCE, 0x4D, 0x95, 0x63, 0x0E, 0xC8, 0xCA, 0x6B,
+            0xEB, 0x1D
+        ])
+    );
+
+    assert_eq!(uuid_to_bytes("33DB4D5B-1FF7-4DCE-9563"), None);
+    assert_eq!(uuid_to_bytes("33DB4D5B-1FF7-4DCE-9563-0EC8CA6BEB1G"), None);
+    assert_eq!(uuid_to_bytes("+3DB4D5B-1FF7-4DCE-9563-0EC8CA6BEB1D"), None);
+}
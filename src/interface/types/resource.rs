@@ -0,0 +1,1765 @@
+//! The [`Resource`] type, which describes hardware resources used by a device
+
+use core::{
+    mem::{offset_of, size_of},
+    ptr::{addr_of, null_mut},
+};
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::{
+    bindings::{
+        buffer::VecToBufferExt,
+        consts::{
+            ACPI_ACTIVE_BOTH, ACPI_ACTIVE_HIGH, ACPI_ACTIVE_LOW, ACPI_ADDRESS_FIXED,
+            ACPI_ADDRESS_NOT_FIXED, ACPI_BUS_MASTER, ACPI_BUS_NUMBER_RANGE, ACPI_CACHABLE_MEMORY,
+            ACPI_CONSUMER, ACPI_DECODE_10, ACPI_DECODE_16, ACPI_DENSE_TRANSLATION,
+            ACPI_EDGE_SENSITIVE, ACPI_EXCLUSIVE, ACPI_IO_RANGE, ACPI_LEVEL_SENSITIVE,
+            ACPI_MEMORY_RANGE, ACPI_NON_CACHEABLE_MEMORY, ACPI_NOT_BUS_MASTER,
+            ACPI_NOT_WAKE_CAPABLE, ACPI_POS_DECODE, ACPI_PREFETCHABLE_MEMORY, ACPI_PRODUCER,
+            ACPI_READ_ONLY_MEMORY, ACPI_READ_WRITE_MEMORY, ACPI_RESOURCE_GPIO_TYPE_INT,
+            ACPI_RESOURCE_GPIO_TYPE_IO, ACPI_RESOURCE_SERIAL_TYPE_I2C,
+            ACPI_RESOURCE_SERIAL_TYPE_SPI, ACPI_RESOURCE_SERIAL_TYPE_UART,
+            ACPI_RESOURCE_TYPE_ADDRESS16, ACPI_RESOURCE_TYPE_ADDRESS32,
+            ACPI_RESOURCE_TYPE_ADDRESS64, ACPI_RESOURCE_TYPE_DMA, ACPI_RESOURCE_TYPE_END_DEPENDENT,
+            ACPI_RESOURCE_TYPE_END_TAG, ACPI_RESOURCE_TYPE_EXTENDED_ADDRESS64,
+            ACPI_RESOURCE_TYPE_EXTENDED_IRQ, ACPI_RESOURCE_TYPE_FIXED_DMA,
+            ACPI_RESOURCE_TYPE_FIXED_IO, ACPI_RESOURCE_TYPE_FIXED_MEMORY32,
+            ACPI_RESOURCE_TYPE_GENERIC_REGISTER, ACPI_RESOURCE_TYPE_GPIO, ACPI_RESOURCE_TYPE_IO,
+            ACPI_RESOURCE_TYPE_IRQ, ACPI_RESOURCE_TYPE_MEMORY24, ACPI_RESOURCE_TYPE_MEMORY32,
+            ACPI_RESOURCE_TYPE_PIN_FUNCTION, ACPI_RESOURCE_TYPE_SERIAL_BUS,
+            ACPI_RESOURCE_TYPE_START_DEPENDENT, ACPI_RESOURCE_TYPE_VENDOR, ACPI_SHARED,
+            ACPI_SPARSE_TRANSLATION, ACPI_SUB_DECODE, ACPI_WAKE_CAPABLE,
+            ACPI_WRITE_COMBINING_MEMORY, AML_RESOURCE_I2C_MIN_DATA_LEN,
+            AML_RESOURCE_SPI_MIN_DATA_LEN, AML_RESOURCE_UART_MIN_DATA_LEN,
+        },
+        types::{
+            FfiAcpiAddress16Attribute, FfiAcpiAddress32Attribute, FfiAcpiAddress64Attribute,
+            FfiAcpiBuffer, FfiAcpiGenericAddress, FfiAcpiIoAttribute, FfiAcpiMemoryAttribute,
+            FfiAcpiResource, FfiAcpiResourceAddress, FfiAcpiResourceAddress16,
+            FfiAcpiResourceAddress32, FfiAcpiResourceAddress64, FfiAcpiResourceAttribute,
+            FfiAcpiResourceCommonSerialbus, FfiAcpiResourceDma, FfiAcpiResourceEndTag,
+            FfiAcpiResourceExtendedAddress64, FfiAcpiResourceExtendedIrq, FfiAcpiResourceFixedDma,
+            FfiAcpiResourceFixedIo, FfiAcpiResourceFixedMemory32, FfiAcpiResourceGenericRegister,
+            FfiAcpiResourceGpio, FfiAcpiResourceI2cSerialbus, FfiAcpiResourceIo,
+            FfiAcpiResourceIrq, FfiAcpiResourceMemory24, FfiAcpiResourceMemory32,
+            FfiAcpiResourcePinFunction, FfiAcpiResourceSource, FfiAcpiResourceSpiSerialbus,
+            FfiAcpiResourceStartDependent, FfiAcpiResourceUartSerialbus, FfiAcpiResourceVendor,
+        },
+    },
+    status::AcpiError,
+};
+
+use super::AcpiGenericAddress;
+
+/// Whether an interrupt is triggered by its level or by an edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptTrigger {
+    /// The interrupt is asserted for as long as the line is at its active level
+    Level,
+    /// The interrupt is asserted when the line changes to its active level
+    Edge,
+}
+
+impl InterruptTrigger {
+    fn from_u8(value: u8) -> Self {
+        if value == ACPI_EDGE_SENSITIVE {
+            Self::Edge
+        } else {
+            Self::Level
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Level => ACPI_LEVEL_SENSITIVE,
+            Self::Edge => ACPI_EDGE_SENSITIVE,
+        }
+    }
+}
+
+/// Which level or edge of an interrupt line is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptPolarity {
+    /// The interrupt is active when the line is high, or on a rising edge
+    ActiveHigh,
+    /// The interrupt is active when the line is low, or on a falling edge
+    ActiveLow,
+    /// The interrupt is active on both edges. This is only valid for GPIO interrupts.
+    ActiveBoth,
+}
+
+impl InterruptPolarity {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            ACPI_ACTIVE_LOW => Self::ActiveLow,
+            ACPI_ACTIVE_BOTH => Self::ActiveBoth,
+            _ => Self::ActiveHigh,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::ActiveHigh => ACPI_ACTIVE_HIGH,
+            Self::ActiveLow => ACPI_ACTIVE_LOW,
+            Self::ActiveBoth => ACPI_ACTIVE_BOTH,
+        }
+    }
+}
+
+/// The caching type of a memory range described by an [`AddressResource`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryCaching {
+    /// The memory must not be cached
+    NonCacheable,
+    /// The memory can be cached
+    Cacheable,
+    /// The memory can be cached, and writes can be combined
+    WriteCombining,
+    /// The memory can be cached, and reads have no side effects so the memory can be prefetched
+    Prefetchable,
+}
+
+impl MemoryCaching {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            ACPI_CACHABLE_MEMORY => Self::Cacheable,
+            ACPI_WRITE_COMBINING_MEMORY => Self::WriteCombining,
+            ACPI_PREFETCHABLE_MEMORY => Self::Prefetchable,
+            _ => Self::NonCacheable,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::NonCacheable => ACPI_NON_CACHEABLE_MEMORY,
+            Self::Cacheable => ACPI_CACHABLE_MEMORY,
+            Self::WriteCombining => ACPI_WRITE_COMBINING_MEMORY,
+            Self::Prefetchable => ACPI_PREFETCHABLE_MEMORY,
+        }
+    }
+}
+
+/// A reference to another device in the AML namespace which a resource comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceSource {
+    /// An index into the resources of the source device
+    pub index: u8,
+    /// The path to the source device
+    pub path: String,
+}
+
+impl ResourceSource {
+    /// # Safety
+    /// The string pointer in `source` must either be null or point to `string_length` valid bytes
+    unsafe fn from_ffi(source: FfiAcpiResourceSource) -> Option<Self> {
+        let pointer = source.string_ptr;
+        let length = source.string_length;
+
+        if pointer.is_null() || length == 0 {
+            return None;
+        }
+
+        // SAFETY: The pointer is valid for `length` bytes
+        let bytes = unsafe { copy_unaligned(pointer.cast::<u8>(), length.into()) };
+        let path = bytes.split(|b| *b == 0).next().unwrap_or_default();
+
+        Some(Self {
+            index: source.index,
+            path: String::from_utf8_lossy(path).to_string(),
+        })
+    }
+}
+
+/// An `IRQ` or `IRQNoFlags` resource, describing ISA interrupts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IrqResource {
+    /// How the interrupt is triggered
+    pub triggering: InterruptTrigger,
+    /// Which level or edge of the interrupt is active
+    pub polarity: InterruptPolarity,
+    /// Whether the interrupt can be shared with other devices
+    pub shareable: bool,
+    /// Whether the interrupt can wake the system from a sleep state
+    pub wake_capable: bool,
+    /// The interrupt numbers. In a `_CRS` resource there is usually one, but a `_PRS` resource lists every possible choice.
+    pub interrupts: Vec<u8>,
+}
+
+/// An `Interrupt` resource, describing interrupts which may be connected to an interrupt controller other than the ISA PIC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedIrqResource {
+    /// Whether the device produces the interrupts rather than consuming them
+    pub producer: bool,
+    /// How the interrupt is triggered
+    pub triggering: InterruptTrigger,
+    /// Which level or edge of the interrupt is active
+    pub polarity: InterruptPolarity,
+    /// Whether the interrupt can be shared with other devices
+    pub shareable: bool,
+    /// Whether the interrupt can wake the system from a sleep state
+    pub wake_capable: bool,
+    /// The interrupt numbers. If [`resource_source`] is [`None`], these are global system interrupts.
+    ///
+    /// [`resource_source`]: ExtendedIrqResource::resource_source
+    pub interrupts: Vec<u32>,
+    /// The interrupt controller which the interrupts are connected to, if it isn't the system's main interrupt controller
+    pub resource_source: Option<ResourceSource>,
+}
+
+/// A `DMA` resource, describing ISA DMA channels
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DmaResource {
+    /// The DMA channel speed: 0 for compatibility mode, or 1, 2 or 3 for type A, B or F
+    pub channel_speed: u8,
+    /// Whether the device is a bus master
+    pub bus_master: bool,
+    /// The transfer size: 0 for 8 bit, 1 for 8 and 16 bit, or 2 for 16 bit
+    pub transfer: u8,
+    /// The DMA channel numbers
+    pub channels: Vec<u8>,
+}
+
+/// A `FixedDMA` resource, describing a DMA channel and request line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedDmaResource {
+    /// The DMA request line
+    pub request_line: u16,
+    /// The DMA channel
+    pub channel: u16,
+    /// The transfer width: 0 to 5 for 8, 16, 32, 64, 128 or 256 bits
+    pub width: u8,
+}
+
+/// An `IO` resource, describing a range of I/O ports which the device can be configured to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoResource {
+    /// Whether the device decodes all 16 bits of the port address, rather than just the lower 10
+    pub decode_16_bit: bool,
+    /// The alignment of the base port
+    pub alignment: u8,
+    /// The number of ports in the range
+    pub address_length: u8,
+    /// The lowest possible base port
+    pub minimum: u16,
+    /// The highest possible base port
+    pub maximum: u16,
+}
+
+/// A `FixedIO` resource, describing a fixed range of I/O ports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedIoResource {
+    /// The base port
+    pub address: u16,
+    /// The number of ports in the range
+    pub address_length: u8,
+}
+
+/// A `Memory24` resource, describing a range of ISA memory.
+/// The values are in units of 256 bytes, as they are stored in the descriptor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Memory24Resource {
+    /// Whether the memory can be written to
+    pub writeable: bool,
+    /// The lowest possible base address
+    pub minimum: u16,
+    /// The highest possible base address
+    pub maximum: u16,
+    /// The alignment of the base address
+    pub alignment: u16,
+    /// The length of the range
+    pub address_length: u16,
+}
+
+/// A `Memory32` resource, describing a range of memory which the device can be configured to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Memory32Resource {
+    /// Whether the memory can be written to
+    pub writeable: bool,
+    /// The lowest possible base address
+    pub minimum: u32,
+    /// The highest possible base address
+    pub maximum: u32,
+    /// The alignment of the base address
+    pub alignment: u32,
+    /// The length of the range
+    pub address_length: u32,
+}
+
+/// A `Memory32Fixed` resource, describing a fixed range of memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedMemory32Resource {
+    /// Whether the memory can be written to
+    pub writeable: bool,
+    /// The base address
+    pub address: u32,
+    /// The length of the range
+    pub address_length: u32,
+}
+
+/// The type of addresses described by an [`AddressResource`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressSpace {
+    /// A range of memory
+    Memory {
+        /// Whether the memory can be written to
+        writeable: bool,
+        /// How the memory can be cached
+        caching: MemoryCaching,
+        /// The type of memory: 0 for normal memory, 1 for reserved memory, 2 for ACPI reclaimable memory or 3 for ACPI NVS memory
+        range_type: u8,
+        /// Whether the range is I/O on the primary side of the bridge, and is translated to memory on the secondary side
+        translation: bool,
+    },
+    /// A range of I/O ports
+    Io {
+        /// Which ISA ranges are used: 1 for non-ISA ranges, 2 for ISA ranges, or 3 for the entire range
+        range_type: u8,
+        /// Whether the range is memory on the primary side of the bridge, and is translated to I/O on the secondary side
+        translation: bool,
+        /// Whether the translation is sparse rather than dense, if [`translation`] is `true`
+        ///
+        /// [`translation`]: AddressSpace::Io::translation
+        sparse_translation: bool,
+    },
+    /// A range of bus numbers
+    BusNumber,
+    /// A vendor-defined type of address
+    Other {
+        /// The type of address. Values from 192 to 255 are vendor-defined.
+        resource_type: u8,
+        /// Flags specific to the type of address
+        type_specific: u8,
+    },
+}
+
+impl AddressSpace {
+    fn from_ffi(address: &FfiAcpiResourceAddress) -> Self {
+        // SAFETY: Every field of the union is made of plain bytes, so any of them can be read
+        let (mem, io, type_specific) = unsafe {
+            (
+                address.info.mem,
+                address.info.io,
+                address.info.type_specific,
+            )
+        };
+
+        match address.resource_type {
+            ACPI_MEMORY_RANGE => Self::Memory {
+                writeable: mem.write_protect == ACPI_READ_WRITE_MEMORY,
+                caching: MemoryCaching::from_u8(mem.caching),
+                range_type: mem.range_type,
+                translation: mem.translation != 0,
+            },
+            ACPI_IO_RANGE => Self::Io {
+                range_type: io.range_type,
+                translation: io.translation != 0,
+                sparse_translation: io.translation_type == ACPI_SPARSE_TRANSLATION,
+            },
+            ACPI_BUS_NUMBER_RANGE => Self::BusNumber,
+            resource_type => Self::Other {
+                resource_type,
+                type_specific,
+            },
+        }
+    }
+
+    fn to_ffi(self) -> (u8, FfiAcpiResourceAttribute) {
+        match self {
+            Self::Memory {
+                writeable,
+                caching,
+                range_type,
+                translation,
+            } => (
+                ACPI_MEMORY_RANGE,
+                FfiAcpiResourceAttribute {
+                    mem: FfiAcpiMemoryAttribute {
+                        write_protect: writeable_to_u8(writeable),
+                        caching: caching.to_u8(),
+                        range_type,
+                        translation: u8::from(translation),
+                    },
+                },
+            ),
+            Self::Io {
+                range_type,
+                translation,
+                sparse_translation,
+            } => (
+                ACPI_IO_RANGE,
+                FfiAcpiResourceAttribute {
+                    io: FfiAcpiIoAttribute {
+                        range_type,
+                        translation: u8::from(translation),
+                        translation_type: flag(
+                            sparse_translation,
+                            ACPI_SPARSE_TRANSLATION,
+                            ACPI_DENSE_TRANSLATION,
+                        ),
+                        reserved1: 0,
+                    },
+                },
+            ),
+            Self::BusNumber => (ACPI_BUS_NUMBER_RANGE, ATTRIBUTE_NONE),
+            Self::Other {
+                resource_type,
+                type_specific,
+            } => (
+                resource_type,
+                // The other bytes are set so that the whole union is initialised
+                FfiAcpiResourceAttribute {
+                    io: FfiAcpiIoAttribute {
+                        range_type: type_specific,
+                        translation: 0,
+                        translation_type: 0,
+                        reserved1: 0,
+                    },
+                },
+            ),
+        }
+    }
+}
+
+/// A [`FfiAcpiResourceAttribute`] with no flags set
+const ATTRIBUTE_NONE: FfiAcpiResourceAttribute = FfiAcpiResourceAttribute {
+    io: FfiAcpiIoAttribute {
+        range_type: 0,
+        translation: 0,
+        translation_type: 0,
+        reserved1: 0,
+    },
+};
+
+/// A `WordSpace`, `DWordSpace` or `QWordSpace` resource (or one of their variants such as `DWordMemory`),
+/// describing a range of addresses. These are mostly used by bridges to describe the windows they forward.
+///
+/// `T` is [`u16`], [`u32`] or [`u64`] depending on the size of the descriptor.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressResource<T> {
+    /// The type of addresses in the range
+    pub space: AddressSpace,
+    /// Whether the device produces the range (for instance, a bridge forwarding it to its children) rather than consuming it
+    pub producer: bool,
+    /// Whether the bridge subtractively decodes the range, rather than positively decoding it
+    pub subtractive_decode: bool,
+    /// Whether [`minimum`] is fixed
+    ///
+    /// [`minimum`]: AddressResource::minimum
+    pub min_address_fixed: bool,
+    /// Whether [`maximum`] is fixed
+    ///
+    /// [`maximum`]: AddressResource::maximum
+    pub max_address_fixed: bool,
+    /// A mask of the address bits which are decoded
+    pub granularity: T,
+    /// The lowest possible base address
+    pub minimum: T,
+    /// The highest possible base address
+    pub maximum: T,
+    /// The offset added to addresses on the primary side of a bridge to get addresses on the secondary side
+    pub translation_offset: T,
+    /// The length of the range
+    pub address_length: T,
+    /// The device which the range comes from, if it isn't the device's parent
+    pub resource_source: Option<ResourceSource>,
+}
+
+impl<T> AddressResource<T> {
+    /// # Safety
+    /// `address` must have been provided by ACPICA so that its resource source is valid
+    unsafe fn from_ffi(
+        address: &FfiAcpiResourceAddress,
+        [granularity, minimum, maximum, translation_offset, address_length]: [T; 5],
+        resource_source: Option<FfiAcpiResourceSource>,
+    ) -> Self {
+        Self {
+            space: AddressSpace::from_ffi(address),
+            producer: address.producer_consumer != ACPI_CONSUMER,
+            subtractive_decode: address.decode == ACPI_SUB_DECODE,
+            min_address_fixed: address.min_address_fixed == ACPI_ADDRESS_FIXED,
+            max_address_fixed: address.max_address_fixed == ACPI_ADDRESS_FIXED,
+            granularity,
+            minimum,
+            maximum,
+            translation_offset,
+            address_length,
+            // SAFETY: The resource source was provided by ACPICA
+            resource_source: resource_source.and_then(|s| unsafe { ResourceSource::from_ffi(s) }),
+        }
+    }
+
+    /// Gets the fields which are shared by all address resources
+    fn common_to_ffi(&self) -> FfiAcpiResourceAddress {
+        let (resource_type, info) = self.space.to_ffi();
+
+        FfiAcpiResourceAddress {
+            resource_type,
+            producer_consumer: flag(self.producer, ACPI_PRODUCER, ACPI_CONSUMER),
+            decode: flag(self.subtractive_decode, ACPI_SUB_DECODE, ACPI_POS_DECODE),
+            min_address_fixed: flag(
+                self.min_address_fixed,
+                ACPI_ADDRESS_FIXED,
+                ACPI_ADDRESS_NOT_FIXED,
+            ),
+            max_address_fixed: flag(
+                self.max_address_fixed,
+                ACPI_ADDRESS_FIXED,
+                ACPI_ADDRESS_NOT_FIXED,
+            ),
+            info,
+        }
+    }
+}
+
+/// An `ExtendedSpace` resource (or one of its variants such as `ExtendedMemory`), describing a range of addresses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedAddressResource {
+    /// The range of addresses. [`resource_source`] is always [`None`].
+    ///
+    /// [`resource_source`]: AddressResource::resource_source
+    pub address: AddressResource<u64>,
+    /// The revision of the descriptor
+    pub revision_id: u8,
+    /// Attributes specific to the type of addresses, such as the `EFI_MEMORY_*` attributes of a memory range
+    pub type_specific: u64,
+}
+
+/// The type of connection described by a [`GpioResource`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpioConnection {
+    /// A `GpioInt` connection, where the pins are used as interrupts
+    Interrupt {
+        /// How the interrupt is triggered
+        triggering: InterruptTrigger,
+        /// Which level or edge of the interrupt is active
+        polarity: InterruptPolarity,
+        /// Whether the interrupt can wake the system from a sleep state
+        wake_capable: bool,
+    },
+    /// A `GpioIo` connection, where the pins are used for input or output
+    Io {
+        /// Whether the pins are restricted to input or output: 0 for no restriction, 1 for input only,
+        /// 2 for output only, or 3 for no restriction but the pin configuration should be preserved
+        io_restriction: u8,
+    },
+}
+
+/// A `GpioInt` or `GpioIo` resource, describing pins on a GPIO controller
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpioResource {
+    /// The revision of the descriptor
+    pub revision_id: u8,
+    /// How the pins are used
+    pub connection: GpioConnection,
+    /// Whether the device produces the pins rather than consuming them
+    pub producer: bool,
+    /// The pull up or pull down configuration of the pins: 0 for the default, 1 for pull up, 2 for pull down, 3 for no pull,
+    /// or a vendor-defined value from 128 to 255
+    pub pin_config: u8,
+    /// Whether the pins can be shared with other devices
+    pub shareable: bool,
+    /// The output drive strength of the pins, in hundredths of milliamps
+    pub drive_strength: u16,
+    /// The debounce timeout of the pins, in hundredths of milliseconds
+    pub debounce_timeout: u16,
+    /// The pin numbers on the GPIO controller
+    pub pins: Vec<u16>,
+    /// The GPIO controller which the pins belong to
+    pub resource_source: Option<ResourceSource>,
+    /// Vendor-defined data
+    pub vendor_data: Vec<u8>,
+}
+
+/// Fields which are shared by all serial bus resources
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerialBusCommon {
+    /// The revision of the descriptor
+    pub revision_id: u8,
+    /// Whether the device produces the connection rather than consuming it
+    pub producer: bool,
+    /// Whether the device initiates communication, rather than the controller
+    pub device_initiated: bool,
+    /// Whether the connection can be shared with other devices
+    pub shared: bool,
+    /// The revision of the bus-specific data in the descriptor
+    pub type_revision_id: u8,
+    /// The serial bus controller which the device is connected to
+    pub resource_source: Option<ResourceSource>,
+    /// Vendor-defined data
+    pub vendor_data: Vec<u8>,
+}
+
+impl SerialBusCommon {
+    /// # Safety
+    /// `common` must have been provided by ACPICA so that its pointers are valid
+    unsafe fn from_ffi(common: &FfiAcpiResourceCommonSerialbus) -> Self {
+        Self {
+            revision_id: common.revision_id,
+            producer: common.producer_consumer != ACPI_CONSUMER,
+            device_initiated: common.slave_mode != 0,
+            shared: common.connection_sharing == ACPI_SHARED,
+            type_revision_id: common.type_revision_id,
+            // SAFETY: The resource source was provided by ACPICA
+            resource_source: unsafe { ResourceSource::from_ffi(common.resource_source) },
+            // SAFETY: The vendor data is valid for `vendor_length` bytes
+            vendor_data: unsafe { copy_unaligned(common.vendor_data, common.vendor_length.into()) },
+        }
+    }
+}
+
+/// An `I2cSerialBusV2` resource, describing a connection to an I2C bus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct I2cSerialBusResource {
+    /// Fields shared by all serial bus resources
+    pub common: SerialBusCommon,
+    /// Whether the device uses 10 bit addresses, rather than 7 bit addresses
+    pub ten_bit_addressing: bool,
+    /// The address of the device on the bus
+    pub slave_address: u16,
+    /// The maximum speed of the connection in hertz
+    pub connection_speed: u32,
+}
+
+/// An `SpiSerialBusV2` resource, describing a connection to an SPI bus
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpiSerialBusResource {
+    /// Fields shared by all serial bus resources
+    pub common: SerialBusCommon,
+    /// Whether the connection uses 3 wires, rather than 4
+    pub three_wire: bool,
+    /// Whether the device selection line is active high, rather than active low
+    pub device_select_active_high: bool,
+    /// The size of a data word in bits
+    pub data_bit_length: u8,
+    /// Whether data is sampled on the second phase of the clock, rather than the first
+    pub clock_phase_second: bool,
+    /// Whether the clock is high when idle, rather than low
+    pub clock_polarity_high: bool,
+    /// The device selection value, which is specific to the controller
+    pub device_selection: u16,
+    /// The maximum speed of the connection in hertz
+    pub connection_speed: u32,
+}
+
+/// A `UartSerialBusV2` resource, describing a connection to a UART
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UartSerialBusResource {
+    /// Fields shared by all serial bus resources
+    pub common: SerialBusCommon,
+    /// Whether data is sent big endian, rather than little endian
+    pub big_endian: bool,
+    /// The number of data bits: 0 to 4 for 5 to 9 bits
+    pub data_bits: u8,
+    /// The number of stop bits: 0 for none, 1 for one, 2 for one and a half, or 3 for two
+    pub stop_bits: u8,
+    /// The flow control: 0 for none, 1 for hardware, or 2 for XON/XOFF
+    pub flow_control: u8,
+    /// The parity: 0 for none, 1 for even, 2 for odd, 3 for mark, or 4 for space
+    pub parity: u8,
+    /// A bitmask of the enabled control lines
+    pub lines_enabled: u8,
+    /// The size of the receive FIFO in bytes
+    pub rx_fifo_size: u16,
+    /// The size of the transmit FIFO in bytes
+    pub tx_fifo_size: u16,
+    /// The default baud rate
+    pub default_baud_rate: u32,
+}
+
+/// A `PinFunction` resource, describing pins which need to be configured for a particular function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinFunctionResource {
+    /// The revision of the descriptor
+    pub revision_id: u8,
+    /// The pull up or pull down configuration of the pins, with the same meaning as [`GpioResource::pin_config`]
+    pub pin_config: u8,
+    /// Whether the pins can be shared with other devices
+    pub shareable: bool,
+    /// The function number, which is specific to the pin controller
+    pub function_number: u16,
+    /// The pin numbers on the pin controller
+    pub pins: Vec<u16>,
+    /// The pin controller which the pins belong to
+    pub resource_source: Option<ResourceSource>,
+    /// Vendor-defined data
+    pub vendor_data: Vec<u8>,
+}
+
+/// A hardware resource used by a device, such as a range of memory, I/O ports, or an interrupt.
+///
+/// A list of these is returned by a device's `_CRS` method (the resources the device currently uses)
+/// and `_PRS` method (the resources the device could be configured to use).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resource {
+    /// An ISA interrupt
+    Irq(IrqResource),
+    /// An ISA DMA channel
+    Dma(DmaResource),
+    /// The start of a set of alternative resources in a `_PRS` list
+    StartDependent {
+        /// How good the configuration is: 0 for good, 1 for acceptable, 2 for sub-optimal
+        compatibility_priority: u8,
+        /// How good the performance and robustness of the configuration is, with the same values as `compatibility_priority`
+        performance_robustness: u8,
+    },
+    /// The end of a set of alternative resources in a `_PRS` list
+    EndDependent,
+    /// A range of I/O ports
+    Io(IoResource),
+    /// A fixed range of I/O ports
+    FixedIo(FixedIoResource),
+    /// Vendor-defined data
+    Vendor(Vec<u8>),
+    /// A range of ISA memory
+    Memory24(Memory24Resource),
+    /// A range of memory
+    Memory32(Memory32Resource),
+    /// A fixed range of memory
+    FixedMemory32(FixedMemory32Resource),
+    /// A range of addresses with 16 bit values
+    Address16(AddressResource<u16>),
+    /// A range of addresses with 32 bit values
+    Address32(AddressResource<u32>),
+    /// A range of addresses with 64 bit values
+    Address64(AddressResource<u64>),
+    /// A range of addresses with 64 bit values and type-specific attributes
+    ExtendedAddress64(ExtendedAddressResource),
+    /// A set of interrupts
+    ExtendedIrq(ExtendedIrqResource),
+    /// A register described by a generic address structure
+    GenericRegister(AcpiGenericAddress),
+    /// A set of GPIO pins
+    Gpio(GpioResource),
+    /// A DMA channel and request line
+    FixedDma(FixedDmaResource),
+    /// A connection to an I2C bus
+    I2cSerialBus(I2cSerialBusResource),
+    /// A connection to an SPI bus
+    SpiSerialBus(SpiSerialBusResource),
+    /// A connection to a UART
+    UartSerialBus(UartSerialBusResource),
+    /// A set of pins configured for a particular function
+    PinFunction(PinFunctionResource),
+    /// A resource which this library doesn't parse, with its ACPICA resource type
+    Other(u32),
+}
+
+impl Resource {
+    /// Copies the data from an ACPICA resource descriptor.
+    /// Returns [`None`] if the resource is the end tag of a resource list.
+    ///
+    /// # Safety
+    /// `resource` must point to a valid resource descriptor provided by ACPICA
+    #[allow(clippy::too_many_lines)]
+    pub(crate) unsafe fn from_ffi(resource: *const FfiAcpiResource) -> Option<Self> {
+        // SAFETY: `resource` is valid
+        let resource = unsafe { &*resource };
+        let data = &resource.data;
+
+        // SAFETY for union accesses: The field being read matches the type of the resource, which ACPICA set
+        let r = match resource.resource_type {
+            ACPI_RESOURCE_TYPE_END_TAG => return None,
+            ACPI_RESOURCE_TYPE_IRQ => {
+                // SAFETY: The resource is an IRQ
+                let irq = unsafe { data.irq };
+                Self::Irq(IrqResource {
+                    triggering: InterruptTrigger::from_u8(irq.triggering),
+                    polarity: InterruptPolarity::from_u8(irq.polarity),
+                    shareable: irq.shareable == ACPI_SHARED,
+                    wake_capable: irq.wake_capable == ACPI_WAKE_CAPABLE,
+                    // SAFETY: ACPICA allocates `interrupt_count` interrupts
+                    interrupts: unsafe {
+                        copy_unaligned(
+                            addr_of!(data.irq.interrupts).cast(),
+                            irq.interrupt_count.into(),
+                        )
+                    },
+                })
+            }
+            ACPI_RESOURCE_TYPE_DMA => {
+                // SAFETY: The resource is a DMA channel
+                let dma = unsafe { data.dma };
+                Self::Dma(DmaResource {
+                    channel_speed: dma.resource_type,
+                    bus_master: dma.bus_master == ACPI_BUS_MASTER,
+                    transfer: dma.transfer,
+                    // SAFETY: ACPICA allocates `channel_count` channels
+                    channels: unsafe {
+                        copy_unaligned(addr_of!(data.dma.channels).cast(), dma.channel_count.into())
+                    },
+                })
+            }
+            ACPI_RESOURCE_TYPE_START_DEPENDENT => {
+                // SAFETY: The resource is a start dependent function
+                let start = unsafe { data.start_dpf };
+                Self::StartDependent {
+                    compatibility_priority: start.compatibility_priority,
+                    performance_robustness: start.performance_robustness,
+                }
+            }
+            ACPI_RESOURCE_TYPE_END_DEPENDENT => Self::EndDependent,
+            ACPI_RESOURCE_TYPE_IO => {
+                // SAFETY: The resource is an I/O range
+                let io = unsafe { data.io };
+                Self::Io(IoResource {
+                    decode_16_bit: io.io_decode == ACPI_DECODE_16,
+                    alignment: io.alignment,
+                    address_length: io.address_length,
+                    minimum: io.minimum,
+                    maximum: io.maximum,
+                })
+            }
+            ACPI_RESOURCE_TYPE_FIXED_IO => {
+                // SAFETY: The resource is a fixed I/O range
+                let io = unsafe { data.fixed_io };
+                Self::FixedIo(FixedIoResource {
+                    address: io.address,
+                    address_length: io.address_length,
+                })
+            }
+            ACPI_RESOURCE_TYPE_VENDOR => {
+                // SAFETY: The resource is vendor data
+                let vendor = unsafe { data.vendor };
+                // SAFETY: ACPICA allocates `byte_length` bytes
+                Self::Vendor(unsafe {
+                    copy_unaligned(
+                        addr_of!(data.vendor.byte_data).cast(),
+                        vendor.byte_length.into(),
+                    )
+                })
+            }
+            ACPI_RESOURCE_TYPE_MEMORY24 => {
+                // SAFETY: The resource is a memory range
+                let memory = unsafe { data.memory24 };
+                Self::Memory24(Memory24Resource {
+                    writeable: memory.write_protect == ACPI_READ_WRITE_MEMORY,
+                    minimum: memory.minimum,
+                    maximum: memory.maximum,
+                    alignment: memory.alignment,
+                    address_length: memory.address_length,
+                })
+            }
+            ACPI_RESOURCE_TYPE_MEMORY32 => {
+                // SAFETY: The resource is a memory range
+                let memory = unsafe { data.memory32 };
+                Self::Memory32(Memory32Resource {
+                    writeable: memory.write_protect == ACPI_READ_WRITE_MEMORY,
+                    minimum: memory.minimum,
+                    maximum: memory.maximum,
+                    alignment: memory.alignment,
+                    address_length: memory.address_length,
+                })
+            }
+            ACPI_RESOURCE_TYPE_FIXED_MEMORY32 => {
+                // SAFETY: The resource is a fixed memory range
+                let memory = unsafe { data.fixed_memory32 };
+                Self::FixedMemory32(FixedMemory32Resource {
+                    writeable: memory.write_protect == ACPI_READ_WRITE_MEMORY,
+                    address: memory.address,
+                    address_length: memory.address_length,
+                })
+            }
+            ACPI_RESOURCE_TYPE_ADDRESS16 => {
+                // SAFETY: The resource is a 16 bit address range
+                let (common, address) = unsafe { (data.address, data.address16) };
+                let a = address.address;
+                // SAFETY: The resource was provided by ACPICA
+                Self::Address16(unsafe {
+                    AddressResource::from_ffi(
+                        &common,
+                        [
+                            a.granularity,
+                            a.minimum,
+                            a.maximum,
+                            a.translation_offset,
+                            a.address_length,
+                        ],
+                        Some(address.resource_source),
+                    )
+                })
+            }
+            ACPI_RESOURCE_TYPE_ADDRESS32 => {
+                // SAFETY: The resource is a 32 bit address range
+                let (common, address) = unsafe { (data.address, data.address32) };
+                let a = address.address;
+                // SAFETY: The resource was provided by ACPICA
+                Self::Address32(unsafe {
+                    AddressResource::from_ffi(
+                        &common,
+                        [
+                            a.granularity,
+                            a.minimum,
+                            a.maximum,
+                            a.translation_offset,
+                            a.address_length,
+                        ],
+                        Some(address.resource_source),
+                    )
+                })
+            }
+            ACPI_RESOURCE_TYPE_ADDRESS64 => {
+                // SAFETY: The resource is a 64 bit address range
+                let (common, address) = unsafe { (data.address, data.address64) };
+                let a = address.address;
+                // SAFETY: The resource was provided by ACPICA
+                Self::Address64(unsafe {
+                    AddressResource::from_ffi(
+                        &common,
+                        [
+                            a.granularity,
+                            a.minimum,
+                            a.maximum,
+                            a.translation_offset,
+                            a.address_length,
+                        ],
+                        Some(address.resource_source),
+                    )
+                })
+            }
+            ACPI_RESOURCE_TYPE_EXTENDED_ADDRESS64 => {
+                // SAFETY: The resource is an extended address range
+                let (common, address) = unsafe { (data.address, data.ext_address64) };
+                let a = address.address;
+                Self::ExtendedAddress64(ExtendedAddressResource {
+                    // SAFETY: The resource was provided by ACPICA
+                    address: unsafe {
+                        AddressResource::from_ffi(
+                            &common,
+                            [
+                                a.granularity,
+                                a.minimum,
+                                a.maximum,
+                                a.translation_offset,
+                                a.address_length,
+                            ],
+                            None,
+                        )
+                    },
+                    revision_id: address.revision_id,
+                    type_specific: address.type_specific,
+                })
+            }
+            ACPI_RESOURCE_TYPE_EXTENDED_IRQ => {
+                // SAFETY: The resource is an extended IRQ
+                let irq = unsafe { data.extended_irq };
+                Self::ExtendedIrq(ExtendedIrqResource {
+                    producer: irq.producer_consumer != ACPI_CONSUMER,
+                    triggering: InterruptTrigger::from_u8(irq.triggering),
+                    polarity: InterruptPolarity::from_u8(irq.polarity),
+                    shareable: irq.shareable == ACPI_SHARED,
+                    wake_capable: irq.wake_capable == ACPI_WAKE_CAPABLE,
+                    // SAFETY: ACPICA allocates `interrupt_count` interrupts
+                    interrupts: unsafe {
+                        copy_unaligned(
+                            addr_of!(data.extended_irq.interrupts).cast(),
+                            irq.interrupt_count.into(),
+                        )
+                    },
+                    // SAFETY: The resource source was provided by ACPICA
+                    resource_source: unsafe { ResourceSource::from_ffi(irq.resource_source) },
+                })
+            }
+            ACPI_RESOURCE_TYPE_GENERIC_REGISTER => {
+                // SAFETY: The resource is a generic register
+                let register = unsafe { data.generic_reg };
+                Self::GenericRegister(AcpiGenericAddress::from_ffi(FfiAcpiGenericAddress {
+                    space_id: register.space_id,
+                    bit_width: register.bit_width,
+                    bit_offset: register.bit_offset,
+                    access_width: register.access_size,
+                    address: register.address,
+                }))
+            }
+            ACPI_RESOURCE_TYPE_GPIO => {
+                // SAFETY: The resource is a GPIO connection
+                let gpio = unsafe { data.gpio };
+                let connection = if u32::from(gpio.connection_type) == ACPI_RESOURCE_GPIO_TYPE_INT {
+                    GpioConnection::Interrupt {
+                        triggering: InterruptTrigger::from_u8(gpio.triggering),
+                        polarity: InterruptPolarity::from_u8(gpio.polarity),
+                        wake_capable: gpio.wake_capable == ACPI_WAKE_CAPABLE,
+                    }
+                } else {
+                    GpioConnection::Io {
+                        io_restriction: gpio.io_restriction,
+                    }
+                };
+
+                Self::Gpio(GpioResource {
+                    revision_id: gpio.revision_id,
+                    connection,
+                    producer: gpio.producer_consumer != ACPI_CONSUMER,
+                    pin_config: gpio.pin_config,
+                    shareable: gpio.shareable == ACPI_SHARED,
+                    drive_strength: gpio.drive_strength,
+                    debounce_timeout: gpio.debounce_timeout,
+                    // SAFETY: The pin table is valid for `pin_table_length` pins
+                    pins: unsafe { copy_unaligned(gpio.pin_table, gpio.pin_table_length.into()) },
+                    // SAFETY: The resource source was provided by ACPICA
+                    resource_source: unsafe { ResourceSource::from_ffi(gpio.resource_source) },
+                    // SAFETY: The vendor data is valid for `vendor_length` bytes
+                    vendor_data: unsafe {
+                        copy_unaligned(gpio.vendor_data, gpio.vendor_length.into())
+                    },
+                })
+            }
+            ACPI_RESOURCE_TYPE_FIXED_DMA => {
+                // SAFETY: The resource is a fixed DMA channel
+                let dma = unsafe { data.fixed_dma };
+                Self::FixedDma(FixedDmaResource {
+                    request_line: dma.request_lines,
+                    channel: dma.channels,
+                    width: dma.width,
+                })
+            }
+            ACPI_RESOURCE_TYPE_SERIAL_BUS => {
+                // SAFETY: The resource is a serial bus connection
+                let common_ffi = unsafe { data.common_serial_bus };
+                // SAFETY: The resource was provided by ACPICA
+                let common = unsafe { SerialBusCommon::from_ffi(&common_ffi) };
+
+                match u32::from(common_ffi.resource_type) {
+                    ACPI_RESOURCE_SERIAL_TYPE_I2C => {
+                        // SAFETY: The serial bus is I2C
+                        let i2c = unsafe { data.i2c_serial_bus };
+                        Self::I2cSerialBus(I2cSerialBusResource {
+                            common,
+                            ten_bit_addressing: i2c.access_mode != 0,
+                            slave_address: i2c.slave_address,
+                            connection_speed: i2c.connection_speed,
+                        })
+                    }
+                    ACPI_RESOURCE_SERIAL_TYPE_SPI => {
+                        // SAFETY: The serial bus is SPI
+                        let spi = unsafe { data.spi_serial_bus };
+                        Self::SpiSerialBus(SpiSerialBusResource {
+                            common,
+                            three_wire: spi.wire_mode != 0,
+                            device_select_active_high: spi.device_polarity != 0,
+                            data_bit_length: spi.data_bit_length,
+                            clock_phase_second: spi.clock_phase != 0,
+                            clock_polarity_high: spi.clock_polarity != 0,
+                            device_selection: spi.device_selection,
+                            connection_speed: spi.connection_speed,
+                        })
+                    }
+                    ACPI_RESOURCE_SERIAL_TYPE_UART => {
+                        // SAFETY: The serial bus is a UART
+                        let uart = unsafe { data.uart_serial_bus };
+                        Self::UartSerialBus(UartSerialBusResource {
+                            common,
+                            big_endian: uart.endian != 0,
+                            data_bits: uart.data_bits,
+                            stop_bits: uart.stop_bits,
+                            flow_control: uart.flow_control,
+                            parity: uart.parity,
+                            lines_enabled: uart.lines_enabled,
+                            rx_fifo_size: uart.rx_fifo_size,
+                            tx_fifo_size: uart.tx_fifo_size,
+                            default_baud_rate: uart.default_baud_rate,
+                        })
+                    }
+                    _ => Self::Other(ACPI_RESOURCE_TYPE_SERIAL_BUS),
+                }
+            }
+            ACPI_RESOURCE_TYPE_PIN_FUNCTION => {
+                // SAFETY: The resource is a pin function
+                let function = unsafe { data.pin_function };
+                Self::PinFunction(PinFunctionResource {
+                    revision_id: function.revision_id,
+                    pin_config: function.pin_config,
+                    shareable: function.shareable == ACPI_SHARED,
+                    function_number: function.function_number,
+                    // SAFETY: The pin table is valid for `pin_table_length` pins
+                    pins: unsafe {
+                        copy_unaligned(function.pin_table, function.pin_table_length.into())
+                    },
+                    // SAFETY: The resource source was provided by ACPICA
+                    resource_source: unsafe { ResourceSource::from_ffi(function.resource_source) },
+                    // SAFETY: The vendor data is valid for `vendor_length` bytes
+                    vendor_data: unsafe {
+                        copy_unaligned(function.vendor_data, function.vendor_length.into())
+                    },
+                })
+            }
+            resource_type => Self::Other(resource_type),
+        };
+
+        Some(r)
+    }
+}
+
+impl Resource {
+    /// Appends the resource to `list` in the format used by ACPICA
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlInvalidResourceType`] if the resource is [`Resource::Other`]
+    /// * [`AcpiError::BadParameter`] if a list in the resource is too long to be represented in a descriptor,
+    ///   or an [`ExtendedIrq`][Resource::ExtendedIrq] resource has no interrupts
+    #[allow(clippy::too_many_lines)]
+    fn write_ffi(&self, list: &mut ResourceList) -> Result<(), AcpiError> {
+        match self {
+            Self::Irq(irq) => {
+                let data = FfiAcpiResourceIrq {
+                    // Always use the 3 byte form, which has room for the flags
+                    descriptor_length: 3,
+                    triggering: irq.triggering.to_u8(),
+                    polarity: irq.polarity.to_u8(),
+                    shareable: flag(irq.shareable, ACPI_SHARED, ACPI_EXCLUSIVE),
+                    wake_capable: flag(irq.wake_capable, ACPI_WAKE_CAPABLE, ACPI_NOT_WAKE_CAPABLE),
+                    interrupt_count: length_u8(&irq.interrupts)?,
+                    interrupts: [0],
+                };
+
+                list.push(
+                    ACPI_RESOURCE_TYPE_IRQ,
+                    data,
+                    &irq.interrupts,
+                    offset_of!(FfiAcpiResourceIrq, interrupts),
+                );
+            }
+            Self::Dma(dma) => {
+                let data = FfiAcpiResourceDma {
+                    resource_type: dma.channel_speed,
+                    bus_master: flag(dma.bus_master, ACPI_BUS_MASTER, ACPI_NOT_BUS_MASTER),
+                    transfer: dma.transfer,
+                    channel_count: length_u8(&dma.channels)?,
+                    channels: [0],
+                };
+
+                list.push(
+                    ACPI_RESOURCE_TYPE_DMA,
+                    data,
+                    &dma.channels,
+                    offset_of!(FfiAcpiResourceDma, channels),
+                );
+            }
+            Self::StartDependent {
+                compatibility_priority,
+                performance_robustness,
+            } => {
+                let data = FfiAcpiResourceStartDependent {
+                    // Always use the form with the priority byte
+                    descriptor_length: 1,
+                    compatibility_priority: *compatibility_priority,
+                    performance_robustness: *performance_robustness,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_START_DEPENDENT, data, &[], 0);
+            }
+            Self::EndDependent => list.push(ACPI_RESOURCE_TYPE_END_DEPENDENT, (), &[], 0),
+            Self::Io(io) => {
+                let data = FfiAcpiResourceIo {
+                    io_decode: flag(io.decode_16_bit, ACPI_DECODE_16, ACPI_DECODE_10),
+                    alignment: io.alignment,
+                    address_length: io.address_length,
+                    minimum: io.minimum,
+                    maximum: io.maximum,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_IO, data, &[], 0);
+            }
+            Self::FixedIo(io) => {
+                let data = FfiAcpiResourceFixedIo {
+                    address: io.address,
+                    address_length: io.address_length,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_FIXED_IO, data, &[], 0);
+            }
+            Self::Vendor(bytes) => {
+                let data = FfiAcpiResourceVendor {
+                    byte_length: length_u16(bytes)?,
+                    byte_data: [0],
+                };
+
+                list.push(
+                    ACPI_RESOURCE_TYPE_VENDOR,
+                    data,
+                    bytes,
+                    offset_of!(FfiAcpiResourceVendor, byte_data),
+                );
+            }
+            Self::Memory24(memory) => {
+                let data = FfiAcpiResourceMemory24 {
+                    write_protect: writeable_to_u8(memory.writeable),
+                    minimum: memory.minimum,
+                    maximum: memory.maximum,
+                    alignment: memory.alignment,
+                    address_length: memory.address_length,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_MEMORY24, data, &[], 0);
+            }
+            Self::Memory32(memory) => {
+                let data = FfiAcpiResourceMemory32 {
+                    write_protect: writeable_to_u8(memory.writeable),
+                    minimum: memory.minimum,
+                    maximum: memory.maximum,
+                    alignment: memory.alignment,
+                    address_length: memory.address_length,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_MEMORY32, data, &[], 0);
+            }
+            Self::FixedMemory32(memory) => {
+                let data = FfiAcpiResourceFixedMemory32 {
+                    write_protect: writeable_to_u8(memory.writeable),
+                    address: memory.address,
+                    address_length: memory.address_length,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_FIXED_MEMORY32, data, &[], 0);
+            }
+            Self::Address16(address) => {
+                let common = address.common_to_ffi();
+                let data = FfiAcpiResourceAddress16 {
+                    resource_type: common.resource_type,
+                    producer_consumer: common.producer_consumer,
+                    decode: common.decode,
+                    min_address_fixed: common.min_address_fixed,
+                    max_address_fixed: common.max_address_fixed,
+                    info: common.info,
+                    address: FfiAcpiAddress16Attribute {
+                        granularity: address.granularity,
+                        minimum: address.minimum,
+                        maximum: address.maximum,
+                        translation_offset: address.translation_offset,
+                        address_length: address.address_length,
+                    },
+                    resource_source: list.resource_source(address.resource_source.as_ref())?,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_ADDRESS16, data, &[], 0);
+            }
+            Self::Address32(address) => {
+                let common = address.common_to_ffi();
+                let data = FfiAcpiResourceAddress32 {
+                    resource_type: common.resource_type,
+                    producer_consumer: common.producer_consumer,
+                    decode: common.decode,
+                    min_address_fixed: common.min_address_fixed,
+                    max_address_fixed: common.max_address_fixed,
+                    info: common.info,
+                    address: FfiAcpiAddress32Attribute {
+                        granularity: address.granularity,
+                        minimum: address.minimum,
+                        maximum: address.maximum,
+                        translation_offset: address.translation_offset,
+                        address_length: address.address_length,
+                    },
+                    resource_source: list.resource_source(address.resource_source.as_ref())?,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_ADDRESS32, data, &[], 0);
+            }
+            Self::Address64(address) => {
+                let common = address.common_to_ffi();
+                let data = FfiAcpiResourceAddress64 {
+                    resource_type: common.resource_type,
+                    producer_consumer: common.producer_consumer,
+                    decode: common.decode,
+                    min_address_fixed: common.min_address_fixed,
+                    max_address_fixed: common.max_address_fixed,
+                    info: common.info,
+                    address: FfiAcpiAddress64Attribute {
+                        granularity: address.granularity,
+                        minimum: address.minimum,
+                        maximum: address.maximum,
+                        translation_offset: address.translation_offset,
+                        address_length: address.address_length,
+                    },
+                    resource_source: list.resource_source(address.resource_source.as_ref())?,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_ADDRESS64, data, &[], 0);
+            }
+            Self::ExtendedAddress64(extended) => {
+                let address = &extended.address;
+                let common = address.common_to_ffi();
+                let data = FfiAcpiResourceExtendedAddress64 {
+                    resource_type: common.resource_type,
+                    producer_consumer: common.producer_consumer,
+                    decode: common.decode,
+                    min_address_fixed: common.min_address_fixed,
+                    max_address_fixed: common.max_address_fixed,
+                    info: common.info,
+                    revision_id: extended.revision_id,
+                    address: FfiAcpiAddress64Attribute {
+                        granularity: address.granularity,
+                        minimum: address.minimum,
+                        maximum: address.maximum,
+                        translation_offset: address.translation_offset,
+                        address_length: address.address_length,
+                    },
+                    type_specific: extended.type_specific,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_EXTENDED_ADDRESS64, data, &[], 0);
+            }
+            Self::ExtendedIrq(irq) => {
+                // ACPICA assumes that there is at least one interrupt when calculating the length of the descriptor
+                if irq.interrupts.is_empty() {
+                    return Err(AcpiError::BadParameter);
+                }
+
+                let data = FfiAcpiResourceExtendedIrq {
+                    producer_consumer: flag(irq.producer, ACPI_PRODUCER, ACPI_CONSUMER),
+                    triggering: irq.triggering.to_u8(),
+                    polarity: irq.polarity.to_u8(),
+                    shareable: flag(irq.shareable, ACPI_SHARED, ACPI_EXCLUSIVE),
+                    wake_capable: flag(irq.wake_capable, ACPI_WAKE_CAPABLE, ACPI_NOT_WAKE_CAPABLE),
+                    interrupt_count: length_u8(&irq.interrupts)?,
+                    resource_source: list.resource_source(irq.resource_source.as_ref())?,
+                    interrupts: [0],
+                };
+
+                let interrupts = irq
+                    .interrupts
+                    .iter()
+                    .flat_map(|i| i.to_ne_bytes())
+                    .collect::<Vec<_>>();
+
+                list.push(
+                    ACPI_RESOURCE_TYPE_EXTENDED_IRQ,
+                    data,
+                    &interrupts,
+                    offset_of!(FfiAcpiResourceExtendedIrq, interrupts),
+                );
+            }
+            Self::GenericRegister(register) => {
+                let data = FfiAcpiResourceGenericRegister {
+                    space_id: register.space_id.to_u8(),
+                    bit_width: register.bit_width,
+                    bit_offset: register.bit_offset,
+                    access_size: register.access_width,
+                    address: register.address,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_GENERIC_REGISTER, data, &[], 0);
+            }
+            Self::Gpio(gpio) => {
+                let (connection_type, triggering, polarity, wake_capable, io_restriction) =
+                    match gpio.connection {
+                        GpioConnection::Interrupt {
+                            triggering,
+                            polarity,
+                            wake_capable,
+                        } => (
+                            ACPI_RESOURCE_GPIO_TYPE_INT,
+                            triggering.to_u8(),
+                            polarity.to_u8(),
+                            flag(wake_capable, ACPI_WAKE_CAPABLE, ACPI_NOT_WAKE_CAPABLE),
+                            0,
+                        ),
+                        GpioConnection::Io { io_restriction } => {
+                            (ACPI_RESOURCE_GPIO_TYPE_IO, 0, 0, 0, io_restriction)
+                        }
+                    };
+
+                let data = FfiAcpiResourceGpio {
+                    revision_id: gpio.revision_id,
+                    connection_type: type_to_u8(connection_type),
+                    producer_consumer: flag(gpio.producer, ACPI_PRODUCER, ACPI_CONSUMER),
+                    pin_config: gpio.pin_config,
+                    shareable: flag(gpio.shareable, ACPI_SHARED, ACPI_EXCLUSIVE),
+                    wake_capable,
+                    io_restriction,
+                    triggering,
+                    polarity,
+                    drive_strength: gpio.drive_strength,
+                    debounce_timeout: gpio.debounce_timeout,
+                    pin_table_length: length_u16(&gpio.pins)?,
+                    vendor_length: length_u16(&gpio.vendor_data)?,
+                    resource_source: list.resource_source(gpio.resource_source.as_ref())?,
+                    pin_table: list.store_pins(&gpio.pins),
+                    vendor_data: list.store_bytes(gpio.vendor_data.clone()),
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_GPIO, data, &[], 0);
+            }
+            Self::FixedDma(dma) => {
+                let data = FfiAcpiResourceFixedDma {
+                    request_lines: dma.request_line,
+                    channels: dma.channel,
+                    width: dma.width,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_FIXED_DMA, data, &[], 0);
+            }
+            Self::I2cSerialBus(i2c) => {
+                let common = list.serial_bus_common(
+                    &i2c.common,
+                    ACPI_RESOURCE_SERIAL_TYPE_I2C,
+                    AML_RESOURCE_I2C_MIN_DATA_LEN,
+                )?;
+
+                let data = FfiAcpiResourceI2cSerialbus {
+                    revision_id: common.revision_id,
+                    resource_type: common.resource_type,
+                    producer_consumer: common.producer_consumer,
+                    slave_mode: common.slave_mode,
+                    connection_sharing: common.connection_sharing,
+                    type_revision_id: common.type_revision_id,
+                    type_data_length: common.type_data_length,
+                    vendor_length: common.vendor_length,
+                    resource_source: common.resource_source,
+                    vendor_data: common.vendor_data,
+                    access_mode: u8::from(i2c.ten_bit_addressing),
+                    slave_address: i2c.slave_address,
+                    connection_speed: i2c.connection_speed,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_SERIAL_BUS, data, &[], 0);
+            }
+            Self::SpiSerialBus(spi) => {
+                let common = list.serial_bus_common(
+                    &spi.common,
+                    ACPI_RESOURCE_SERIAL_TYPE_SPI,
+                    AML_RESOURCE_SPI_MIN_DATA_LEN,
+                )?;
+
+                let data = FfiAcpiResourceSpiSerialbus {
+                    revision_id: common.revision_id,
+                    resource_type: common.resource_type,
+                    producer_consumer: common.producer_consumer,
+                    slave_mode: common.slave_mode,
+                    connection_sharing: common.connection_sharing,
+                    type_revision_id: common.type_revision_id,
+                    type_data_length: common.type_data_length,
+                    vendor_length: common.vendor_length,
+                    resource_source: common.resource_source,
+                    vendor_data: common.vendor_data,
+                    wire_mode: u8::from(spi.three_wire),
+                    device_polarity: u8::from(spi.device_select_active_high),
+                    data_bit_length: spi.data_bit_length,
+                    clock_phase: u8::from(spi.clock_phase_second),
+                    clock_polarity: u8::from(spi.clock_polarity_high),
+                    device_selection: spi.device_selection,
+                    connection_speed: spi.connection_speed,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_SERIAL_BUS, data, &[], 0);
+            }
+            Self::UartSerialBus(uart) => {
+                let common = list.serial_bus_common(
+                    &uart.common,
+                    ACPI_RESOURCE_SERIAL_TYPE_UART,
+                    AML_RESOURCE_UART_MIN_DATA_LEN,
+                )?;
+
+                let data = FfiAcpiResourceUartSerialbus {
+                    revision_id: common.revision_id,
+                    resource_type: common.resource_type,
+                    producer_consumer: common.producer_consumer,
+                    slave_mode: common.slave_mode,
+                    connection_sharing: common.connection_sharing,
+                    type_revision_id: common.type_revision_id,
+                    type_data_length: common.type_data_length,
+                    vendor_length: common.vendor_length,
+                    resource_source: common.resource_source,
+                    vendor_data: common.vendor_data,
+                    endian: u8::from(uart.big_endian),
+                    data_bits: uart.data_bits,
+                    stop_bits: uart.stop_bits,
+                    flow_control: uart.flow_control,
+                    parity: uart.parity,
+                    lines_enabled: uart.lines_enabled,
+                    rx_fifo_size: uart.rx_fifo_size,
+                    tx_fifo_size: uart.tx_fifo_size,
+                    default_baud_rate: uart.default_baud_rate,
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_SERIAL_BUS, data, &[], 0);
+            }
+            Self::PinFunction(function) => {
+                let data = FfiAcpiResourcePinFunction {
+                    revision_id: function.revision_id,
+                    pin_config: function.pin_config,
+                    shareable: flag(function.shareable, ACPI_SHARED, ACPI_EXCLUSIVE),
+                    function_number: function.function_number,
+                    pin_table_length: length_u16(&function.pins)?,
+                    vendor_length: length_u16(&function.vendor_data)?,
+                    resource_source: list.resource_source(function.resource_source.as_ref())?,
+                    pin_table: list.store_pins(&function.pins),
+                    vendor_data: list.store_bytes(function.vendor_data.clone()),
+                };
+
+                list.push(ACPI_RESOURCE_TYPE_PIN_FUNCTION, data, &[], 0);
+            }
+            Self::Other(_) => return Err(AcpiError::AmlInvalidResourceType),
+        }
+
+        Ok(())
+    }
+}
+
+/// A list of resources in the format used by ACPICA, which can be passed to `AcpiSetCurrentResources`.
+///
+/// ACPICA's resource descriptors contain pointers to strings and tables which aren't stored in the descriptor itself.
+/// This struct owns that data, so the list is only valid for as long as this struct is alive.
+#[derive(Debug)]
+pub(crate) struct ResourceList {
+    /// The descriptors, one after the other, ending with an end tag
+    descriptors: Vec<u8>,
+    /// Strings and vendor data pointed to by the descriptors
+    bytes: Vec<Vec<u8>>,
+    /// Pin tables pointed to by the descriptors
+    pins: Vec<Vec<u16>>,
+}
+
+impl ResourceList {
+    /// Converts `resources` into ACPICA's format
+    ///
+    /// # Errors
+    /// * [`AcpiError::AmlInvalidResourceType`] if one of the resources is [`Resource::Other`]
+    /// * [`AcpiError::BadParameter`] if one of the resources can't be represented in a descriptor
+    pub(crate) fn new(resources: &[Resource]) -> Result<Self, AcpiError> {
+        let mut list = Self {
+            descriptors: Vec::new(),
+            bytes: Vec::new(),
+            pins: Vec::new(),
+        };
+
+        for resource in resources {
+            resource.write_ffi(&mut list)?;
+        }
+
+        list.push(
+            ACPI_RESOURCE_TYPE_END_TAG,
+            FfiAcpiResourceEndTag { checksum: 0 },
+            &[],
+            0,
+        );
+
+        Ok(list)
+    }
+
+    /// Gets a buffer containing the descriptors
+    pub(crate) fn as_ffi(&mut self) -> FfiAcpiBuffer<'_> {
+        self.descriptors.to_buffer()
+    }
+
+    /// Appends a descriptor to the list.
+    ///
+    /// `data` is written after the descriptor's header, and `tail` is written at `tail_offset` bytes into `data`.
+    /// This is used for lists which are stored in the descriptor, which are declared as one element arrays in ACPICA's structs.
+    fn push<T>(&mut self, resource_type: u32, data: T, tail: &[u8], tail_offset: usize) {
+        let header_length = offset_of!(FfiAcpiResource, data);
+        let data_length = size_of::<T>().max(tail_offset + tail.len());
+
+        // ACPICA rounds the length of each descriptor up to a multiple of the native word size
+        let length = (header_length + data_length).next_multiple_of(size_of::<usize>());
+
+        let start = self.descriptors.len();
+        self.descriptors.resize(start + length, 0);
+        let descriptor = &mut self.descriptors[start..];
+
+        // Descriptors are far smaller than 4GiB, so the length fits in a u32
+        #[allow(clippy::cast_possible_truncation)]
+        let length = length as u32;
+
+        descriptor[..4].copy_from_slice(&resource_type.to_ne_bytes());
+        descriptor[4..8].copy_from_slice(&length.to_ne_bytes());
+
+        // SAFETY: The descriptor has room for `data` after the header
+        unsafe {
+            descriptor
+                .as_mut_ptr()
+                .add(header_length)
+                .cast::<T>()
+                .write_unaligned(data);
+        }
+
+        descriptor[header_length + tail_offset..][..tail.len()].copy_from_slice(tail);
+    }
+
+    /// Stores `bytes` so that a descriptor can point to them.
+    /// If `bytes` is empty, a null pointer is returned.
+    fn store_bytes(&mut self, mut bytes: Vec<u8>) -> *mut u8 {
+        if bytes.is_empty() {
+            return null_mut();
+        }
+
+        // Moving the `Vec` into `self.bytes` doesn't move its heap allocation, so the pointer stays valid
+        let pointer = bytes.as_mut_ptr();
+        self.bytes.push(bytes);
+        pointer
+    }
+
+    /// Stores a pin table so that a descriptor can point to it.
+    /// If `pins` is empty, a null pointer is returned.
+    fn store_pins(&mut self, pins: &[u16]) -> *mut u16 {
+        if pins.is_empty() {
+            return null_mut();
+        }
+
+        let mut pins = pins.to_vec();
+        let pointer = pins.as_mut_ptr();
+        self.pins.push(pins);
+        pointer
+    }
+
+    /// Converts a resource source into ACPICA's format, storing its path so that the descriptor can point to it
+    fn resource_source(
+        &mut self,
+        source: Option<&ResourceSource>,
+    ) -> Result<FfiAcpiResourceSource, AcpiError> {
+        let Some(source) = source else {
+            return Ok(FfiAcpiResourceSource {
+                index: 0,
+                string_length: 0,
+                string_ptr: null_mut(),
+            });
+        };
+
+        // The length of the string includes its null terminator
+        let mut path = source.path.as_bytes().to_vec();
+        path.push(0);
+
+        Ok(FfiAcpiResourceSource {
+            index: source.index,
+            string_length: length_u16(&path)?,
+            string_ptr: self.store_bytes(path).cast(),
+        })
+    }
+
+    /// Converts the fields shared by all serial bus resources into ACPICA's format.
+    /// `min_data_length` is the length of the bus-specific data in the descriptor, not including any vendor data.
+    fn serial_bus_common(
+        &mut self,
+        common: &SerialBusCommon,
+        serial_type: u32,
+        min_data_length: u16,
+    ) -> Result<FfiAcpiResourceCommonSerialbus, AcpiError> {
+        let vendor_length = length_u16(&common.vendor_data)?;
+
+        Ok(FfiAcpiResourceCommonSerialbus {
+            revision_id: common.revision_id,
+            resource_type: type_to_u8(serial_type),
+            producer_consumer: flag(common.producer, ACPI_PRODUCER, ACPI_CONSUMER),
+            slave_mode: u8::from(common.device_initiated),
+            connection_sharing: flag(common.shared, ACPI_SHARED, ACPI_EXCLUSIVE),
+            type_revision_id: common.type_revision_id,
+            type_data_length: min_data_length
+                .checked_add(vendor_length)
+                .ok_or(AcpiError::BadParameter)?,
+            vendor_length,
+            resource_source: self.resource_source(common.resource_source.as_ref())?,
+            vendor_data: self.store_bytes(common.vendor_data.clone()),
+        })
+    }
+}
+
+/// Gets `set` if `value` is `true`, or `clear` otherwise
+fn flag(value: bool, set: u8, clear: u8) -> u8 {
+    if value {
+        set
+    } else {
+        clear
+    }
+}
+
+/// Gets the `write_protect` value of a memory resource
+fn writeable_to_u8(writeable: bool) -> u8 {
+    flag(writeable, ACPI_READ_WRITE_MEMORY, ACPI_READ_ONLY_MEMORY)
+}
+
+/// Converts one of ACPICA's GPIO or serial bus type constants to the [`u8`] stored in a descriptor
+fn type_to_u8(value: u32) -> u8 {
+    // The constants are all small
+    #[allow(clippy::cast_possible_truncation)]
+    let value = value as u8;
+    value
+}
+
+/// Gets the length of a list stored in a descriptor which uses a [`u8`] for the length
+fn length_u8<T>(list: &[T]) -> Result<u8, AcpiError> {
+    u8::try_from(list.len()).map_err(|_| AcpiError::BadParameter)
+}
+
+/// Gets the length of a list stored in a descriptor which uses a [`u16`] for the length
+fn length_u16<T>(list: &[T]) -> Result<u16, AcpiError> {
+    u16::try_from(list.len()).map_err(|_| AcpiError::BadParameter)
+}
+
+/// Copies `length` values from `pointer`, which does not need to be aligned.
+/// If `pointer` is null, an empty [`Vec`] is returned.
+///
+/// # Safety
+/// If `pointer` is not null, it must be valid for reads of `length` values
+unsafe fn copy_unaligned<T: Copy>(pointer: *const T, length: usize) -> Vec<T> {
+    if pointer.is_null() {
+        return Vec::new();
+    }
+
+    (0..length)
+        // SAFETY: `pointer` is valid for `length` values
+        .map(|i| unsafe { pointer.add(i).read_unaligned() })
+        .collect()
+}
+
+/// Tests that resources written into a [`ResourceList`] can be read back by [`Resource::from_ffi`]
+#[test]
+fn test_resource_list_round_trip() {
+    let resources = [
+        Resource::Irq(IrqResource {
+            triggering: InterruptTrigger::Level,
+            polarity: InterruptPolarity::ActiveLow,
+            shareable: true,
+            wake_capable: false,
+            interrupts: alloc::vec![3, 4, 5, 6, 7, 9, 10, 11],
+        }),
+        Resource::ExtendedIrq(ExtendedIrqResource {
+            producer: false,
+            triggering: InterruptTrigger::Edge,
+            polarity: InterruptPolarity::ActiveHigh,
+            shareable: false,
+            wake_capable: true,
+            interrupts: alloc::vec![16, 17],
+            resource_source: Some(ResourceSource {
+                index: 2,
+                path: "\\_SB.PCI0".to_string(),
+            }),
+        }),
+        Resource::FixedIo(FixedIoResource {
+            address: 0x60,
+            address_length: 1,
+        }),
+    ];
+
+    let list = ResourceList::new(&resources).unwrap();
+
+    let mut parsed = Vec::new();
+    let mut offset = 0;
+
+    loop {
+        let descriptor = list.descriptors[offset..]
+            .as_ptr()
+            .cast::<FfiAcpiResource>();
+        let length =
+            u32::from_ne_bytes(list.descriptors[offset + 4..offset + 8].try_into().unwrap());
+        assert_eq!(length as usize % size_of::<usize>(), 0);
+
+        // SAFETY: The descriptor was written by `ResourceList::new`
+        match unsafe { Resource::from_ffi(descriptor) } {
+            Some(resource) => parsed.push(resource),
+            None => break,
+        }
+
+        offset += length as usize;
+    }
+
+    // Only the end tag is left
+    assert_eq!(
+        list.descriptors.len() - offset,
+        9_usize.next_multiple_of(size_of::<usize>())
+    );
+    assert_eq!(parsed, resources);
+}
@@ -4,6 +4,7 @@ use core::fmt::Debug;
 
 use crate::bindings::types::tables::FfiAcpiTableHeader;
 
+pub mod ecdt;
 pub mod fadt;
 pub mod madt;
 pub mod mcfg;
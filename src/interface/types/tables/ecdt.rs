@@ -0,0 +1,74 @@
+//! The [`Ecdt`] type
+
+use core::fmt::Debug;
+
+use crate::{bindings::types::tables::misc::FfiAcpiTableEcdt, types::AcpiGenericAddress};
+
+use super::AcpiTableHeader;
+
+/// The offset of the EC's namepath from the end of the table header
+const ECDT_ID_OFFSET: usize = 29;
+
+/// The ECDT table, which describes the embedded controller so that it can be used before the namespace is initialized
+pub struct Ecdt<'a>(&'a FfiAcpiTableEcdt);
+
+impl<'a> Ecdt<'a> {
+    pub(crate) fn from_ffi(ffi_ptr: &'a FfiAcpiTableEcdt) -> Self {
+        Self(ffi_ptr)
+    }
+
+    /// Gets the table's header
+    #[must_use]
+    pub fn header(&self) -> AcpiTableHeader<'a> {
+        AcpiTableHeader::from_ffi(&self.0.header)
+    }
+
+    /// Gets the address of the EC's command and status register
+    #[must_use]
+    pub fn control(&self) -> AcpiGenericAddress {
+        AcpiGenericAddress::from_ffi(self.0.control)
+    }
+
+    /// Gets the address of the EC's data register
+    #[must_use]
+    pub fn data(&self) -> AcpiGenericAddress {
+        AcpiGenericAddress::from_ffi(self.0.data)
+    }
+
+    /// Gets the `_UID` of the EC's device in the AML namespace
+    #[must_use]
+    pub fn uid(&self) -> u32 {
+        self.0.uid
+    }
+
+    /// Gets the number of the GPE which the EC raises, in the GPE blocks described by the FADT
+    #[must_use]
+    pub fn gpe(&self) -> u8 {
+        self.0.gpe
+    }
+
+    /// Gets the full path of the EC's device in the AML namespace, e.g. `\_SB.PCI0.LPCB.EC0`.
+    /// Returns [`None`] if the path is empty or isn't valid ASCII.
+    #[must_use]
+    pub fn id(&self) -> Option<&'a str> {
+        let id = self.header().content().get(ECDT_ID_OFFSET..)?;
+        let length = id.iter().position(|&c| c == 0).unwrap_or(id.len());
+
+        core::str::from_utf8(&id[..length])
+            .ok()
+            .filter(|id| !id.is_empty())
+    }
+}
+
+impl Debug for Ecdt<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Ecdt")
+            .field("header", &self.header())
+            .field("control", &self.control())
+            .field("data", &self.data())
+            .field("uid", &self.uid())
+            .field("gpe", &self.gpe())
+            .field("id", &self.id())
+            .finish()
+    }
+}
@@ -112,6 +112,22 @@ pub struct ApicFlags {
     _reserved: (),
 }
 
+/// Flags for the [`GicCpuInterface`][MadtRecord::GicCpuInterface] record type
+#[bitfield(u32)]
+pub struct GiccFlags {
+    /// Whether the processor is ready for use
+    enabled: bool,
+    /// Whether the performance monitoring interrupt is edge triggered, rather than level triggered
+    performance_interrupt_edge_triggered: bool,
+    /// Whether the virtual GIC maintenance interrupt is edge triggered, rather than level triggered
+    vgic_maintenance_interrupt_edge_triggered: bool,
+    /// Whether the processor can be turned on by the OS, if it is not already on.
+    online_capable: bool,
+
+    #[bits(28)]
+    _reserved: (),
+}
+
 /// Under what condition the interrupt is triggered
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InterruptPolarity {
@@ -187,10 +203,12 @@ impl InterruptTriggerMode {
 /// TODO: This is called MPS INTI flags in the spec, what does that stand for, rename this struct?
 #[bitfield(u16)]
 pub struct InterruptVectorFlags {
+    /// Under what condition the interrupt is triggered
     #[bits(2)]
-    polarity: InterruptPolarity,
+    pub polarity: InterruptPolarity,
+    /// How often the interrupt is triggered while the condition is met
     #[bits(2)]
-    trigger_mode: InterruptTriggerMode,
+    pub trigger_mode: InterruptTriggerMode,
 
     #[bits(12)]
     _reserved: (),
@@ -305,8 +323,36 @@ pub enum MadtRecord<'a> {
     },
     /// TODO
     LocalX2ApicNonMaskableInterrupt,
-    /// TODO
-    GicCpuInterface,
+    /// Record declaring the presence of a processor and its GIC CPU interface, on ARM systems
+    GicCpuInterface {
+        #[doc(hidden)]
+        reserved0: u16,
+        /// The GIC's identifier for the processor's CPU interface
+        cpu_interface_number: u32,
+        /// A value used to match this record to an object in the DSDT
+        acpi_processor_uid: u32,
+        /// Flags about the processor and its interrupts
+        flags: GiccFlags,
+        /// The version of the ARM parking protocol the processor supports, or 0 if it doesn't
+        parking_protocol_version: u32,
+        /// The GSIV of the performance monitoring interrupt
+        performance_interrupt_gsiv: u32,
+        /// The physical address of the processor's parking protocol mailbox
+        parked_address: u64,
+        /// The physical address of the GIC CPU interface's registers
+        physical_base_address: u64,
+        /// The physical address of the GIC virtual CPU interface's registers.
+        /// This and the following fields are 0 in tables older than ACPI 5.1.
+        gicv_base_address: u64,
+        /// The physical address of the GIC virtual interface control block's registers
+        gich_base_address: u64,
+        /// The GSIV of the virtual GIC maintenance interrupt
+        vgic_maintenance_interrupt: u32,
+        /// The physical address of the processor's GIC redistributor, if the redistributors aren't contiguous
+        gicr_base_address: u64,
+        /// The processor's MPIDR value
+        mpidr: u64,
+    },
     /// TODO
     GicDistributor,
     /// TODO
@@ -427,7 +473,7 @@ impl<'a> MadtRecord<'a> {
                 acpi_id: read!(u32, from),
             },
             0x0A => Self::LocalX2ApicNonMaskableInterrupt,
-            0x0B => Self::GicCpuInterface,
+            0x0B => Self::read_gicc(from),
             0x0C => Self::GicDistributor,
             0x0D => Self::GicMsiFrame,
             0x0E => Self::GicRedistributor,
@@ -446,6 +492,35 @@ impl<'a> MadtRecord<'a> {
 
         (record, rest)
     }
+
+    /// Reads the fields of a [`GicCpuInterface`][MadtRecord::GicCpuInterface] record, after the type and length.
+    /// The fields added in ACPI 5.1 are read as 0 from shorter records.
+    fn read_gicc(from: &[u8]) -> Self {
+        let mut fields = [0; 74];
+        let length = from.len().min(fields.len());
+        fields[..length].copy_from_slice(&from[..length]);
+
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(fields[offset..offset + 4].try_into().unwrap());
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(fields[offset..offset + 8].try_into().unwrap());
+
+        Self::GicCpuInterface {
+            reserved0: u16::from_le_bytes([fields[0], fields[1]]),
+            cpu_interface_number: u32_at(2),
+            acpi_processor_uid: u32_at(6),
+            flags: GiccFlags::from(u32_at(10)),
+            parking_protocol_version: u32_at(14),
+            performance_interrupt_gsiv: u32_at(18),
+            parked_address: u64_at(22),
+            physical_base_address: u64_at(30),
+            gicv_base_address: u64_at(38),
+            gich_base_address: u64_at(46),
+            vgic_maintenance_interrupt: u32_at(54),
+            gicr_base_address: u64_at(58),
+            mpidr: u64_at(66),
+        }
+    }
 }
 
 /// The flags present on an MADT